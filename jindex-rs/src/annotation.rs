@@ -0,0 +1,389 @@
+use crate::builder::{get_index_from_pool, ClassToIndexMap};
+use crate::class_index::ClassIndex;
+use crate::constant_pool::ClassIndexConstantPool;
+use crate::signature::indexed_signature::{ToIndexedType, ToRawType};
+use crate::signature::{RawSignatureType, SignatureType};
+use crate::validation::{IndexBounds, Validate};
+use cafebabe::attributes::{Annotation as ClassFileAnnotation, AnnotationElementValue};
+use compact_str::{CompactString, ToCompactString};
+use rustc_hash::FxHashMap;
+use std::borrow::Cow;
+use std::str::FromStr;
+
+/// An annotation of a class, field, method or method parameter
+#[derive(Debug, PartialEq)]
+pub struct Annotation<T> {
+    /// The annotation class. Indexed annotations whose class is not part of the index keep its
+    /// name as an unresolved type.
+    annotation_type: SignatureType<T>,
+    /// Whether the annotation is retained at runtime, or only in the class file
+    visible: bool,
+    /// The name and value of each element with a constant value
+    elements: Vec<(T, AnnotationValue<T>)>,
+}
+
+pub type RawAnnotation = Annotation<CompactString>;
+pub type IndexedAnnotation = Annotation<u32>;
+
+/// The value of an annotation element. Nested annotations are not indexed.
+#[derive(Debug, PartialEq)]
+pub enum AnnotationValue<T> {
+    Boolean(bool),
+    Byte(i8),
+    Char(u16),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(T),
+    /// The enum type and the name of the enum constant
+    Enum(Box<(SignatureType<T>, T)>),
+    Class(Box<SignatureType<T>>),
+    Array(Vec<AnnotationValue<T>>),
+}
+
+pub type RawAnnotationValue = AnnotationValue<CompactString>;
+pub type IndexedAnnotationValue = AnnotationValue<u32>;
+
+impl<T> Annotation<T> {
+    pub(crate) fn new(
+        annotation_type: SignatureType<T>,
+        visible: bool,
+        elements: Vec<(T, AnnotationValue<T>)>,
+    ) -> Self {
+        Self {
+            annotation_type,
+            visible,
+            elements,
+        }
+    }
+
+    pub fn annotation_type(&self) -> &SignatureType<T> {
+        &self.annotation_type
+    }
+
+    /// Returns the annotation class, or None if it is not part of the index
+    pub fn annotation_class(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.annotation_type.extract_base_object_type()
+    }
+
+    /// Returns true if the annotation is retained at runtime
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn elements(&self) -> &[(T, AnnotationValue<T>)] {
+        &self.elements
+    }
+}
+
+impl IndexedAnnotation {
+    /// Returns the value of the element with the given name, if it has a constant value
+    pub fn element_value(
+        &self,
+        constant_pool: &ClassIndexConstantPool,
+        name: &str,
+    ) -> Option<&IndexedAnnotationValue> {
+        self.elements
+            .iter()
+            .find(|(element_name, _)| {
+                constant_pool
+                    .string_view_at(*element_name)
+                    .into_str(constant_pool)
+                    == name
+            })
+            .map(|(_, value)| value)
+    }
+}
+
+impl RawAnnotation {
+    /// Converts an annotation read from a class file. Returns None if the annotation type is
+    /// not a class.
+    pub(crate) fn from_class_file(annotation: &ClassFileAnnotation, visible: bool) -> Option<Self> {
+        let annotation_type = match RawSignatureType::from_str(&annotation.type_descriptor) {
+            Ok(annotation_type @ SignatureType::Object(_)) => annotation_type,
+            _ => return None,
+        };
+
+        Some(Self::new(
+            annotation_type,
+            visible,
+            annotation
+                .elements
+                .iter()
+                .filter_map(|element| {
+                    RawAnnotationValue::from_class_file(&element.value)
+                        .map(|value| (element.name.to_compact_string(), value))
+                })
+                .collect(),
+        ))
+    }
+}
+
+impl RawAnnotationValue {
+    fn from_class_file(value: &AnnotationElementValue) -> Option<Self> {
+        Some(match value {
+            AnnotationElementValue::BooleanConstant(v) => AnnotationValue::Boolean(*v != 0),
+            AnnotationElementValue::ByteConstant(v) => AnnotationValue::Byte(*v as i8),
+            AnnotationElementValue::CharConstant(v) => AnnotationValue::Char(*v as u16),
+            AnnotationElementValue::ShortConstant(v) => AnnotationValue::Short(*v as i16),
+            AnnotationElementValue::IntConstant(v) => AnnotationValue::Int(*v),
+            AnnotationElementValue::LongConstant(v) => AnnotationValue::Long(*v),
+            AnnotationElementValue::FloatConstant(v) => AnnotationValue::Float(*v),
+            AnnotationElementValue::DoubleConstant(v) => AnnotationValue::Double(*v),
+            AnnotationElementValue::StringConstant(v) => {
+                AnnotationValue::String(v.to_compact_string())
+            }
+            AnnotationElementValue::EnumConstant {
+                type_name,
+                const_name,
+            } => AnnotationValue::Enum(Box::new((
+                RawSignatureType::from_str(type_name).ok()?,
+                const_name.to_compact_string(),
+            ))),
+            AnnotationElementValue::ClassLiteral { class_name } => {
+                AnnotationValue::Class(Box::new(RawSignatureType::from_str(class_name).ok()?))
+            }
+            AnnotationElementValue::ArrayValue(values) => AnnotationValue::Array(
+                values
+                    .iter()
+                    .filter_map(RawAnnotationValue::from_class_file)
+                    .collect(),
+            ),
+            AnnotationElementValue::AnnotationValue(_) => return None,
+        })
+    }
+}
+
+/// Indexes all annotations. Annotations whose class is not part of the index are kept by name, so
+/// that updating the index can resolve them later.
+pub(crate) fn index_annotations<'a>(
+    annotations: &'a [RawAnnotation],
+    constant_pool: &mut ClassIndexConstantPool,
+    constant_pool_map: &mut FxHashMap<Cow<'a, str>, u32>,
+    class_to_index_map: &ClassToIndexMap,
+) -> anyhow::Result<Vec<IndexedAnnotation>> {
+    let mut result = Vec::with_capacity(annotations.len());
+    for annotation in annotations {
+        let annotation_type = annotation.annotation_type.to_indexed_type(
+            constant_pool,
+            constant_pool_map,
+            class_to_index_map,
+        )?;
+
+        let mut elements = Vec::with_capacity(annotation.elements.len());
+        for (name, value) in annotation.elements.iter() {
+            elements.push((
+                get_index_from_pool(name, constant_pool_map, constant_pool)?,
+                value.to_indexed_type(constant_pool, constant_pool_map, class_to_index_map)?,
+            ));
+        }
+        result.push(Annotation::new(
+            annotation_type,
+            annotation.visible,
+            elements,
+        ));
+    }
+
+    Ok(result)
+}
+
+impl ToIndexedType for RawAnnotationValue {
+    type Out = IndexedAnnotationValue;
+
+    fn to_indexed_type<'a>(
+        &'a self,
+        constant_pool: &mut ClassIndexConstantPool,
+        constant_pool_map: &mut FxHashMap<Cow<'a, str>, u32>,
+        class_to_index_map: &ClassToIndexMap,
+    ) -> anyhow::Result<Self::Out> {
+        Ok(match self {
+            AnnotationValue::Boolean(v) => AnnotationValue::Boolean(*v),
+            AnnotationValue::Byte(v) => AnnotationValue::Byte(*v),
+            AnnotationValue::Char(v) => AnnotationValue::Char(*v),
+            AnnotationValue::Short(v) => AnnotationValue::Short(*v),
+            AnnotationValue::Int(v) => AnnotationValue::Int(*v),
+            AnnotationValue::Long(v) => AnnotationValue::Long(*v),
+            AnnotationValue::Float(v) => AnnotationValue::Float(*v),
+            AnnotationValue::Double(v) => AnnotationValue::Double(*v),
+            AnnotationValue::String(v) => {
+                AnnotationValue::String(get_index_from_pool(v, constant_pool_map, constant_pool)?)
+            }
+            AnnotationValue::Enum(inner) => {
+                let (enum_type, name) = inner.as_ref();
+                AnnotationValue::Enum(Box::new((
+                    enum_type.to_indexed_type(
+                        constant_pool,
+                        constant_pool_map,
+                        class_to_index_map,
+                    )?,
+                    get_index_from_pool(name, constant_pool_map, constant_pool)?,
+                )))
+            }
+            AnnotationValue::Class(class) => AnnotationValue::Class(Box::new(
+                class.to_indexed_type(constant_pool, constant_pool_map, class_to_index_map)?,
+            )),
+            AnnotationValue::Array(values) => AnnotationValue::Array(values.to_indexed_type(
+                constant_pool,
+                constant_pool_map,
+                class_to_index_map,
+            )?),
+        })
+    }
+}
+
+impl ToRawType for IndexedAnnotation {
+    type Out = RawAnnotation;
+
+    fn to_raw_type(&self, class_index: &ClassIndex) -> Self::Out {
+        Annotation::new(
+            self.annotation_type.to_raw_type(class_index),
+            self.visible,
+            self.elements
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        raw_string(class_index, *name),
+                        value.to_raw_type(class_index),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+impl ToRawType for IndexedAnnotationValue {
+    type Out = RawAnnotationValue;
+
+    fn to_raw_type(&self, class_index: &ClassIndex) -> Self::Out {
+        match self {
+            AnnotationValue::Boolean(v) => AnnotationValue::Boolean(*v),
+            AnnotationValue::Byte(v) => AnnotationValue::Byte(*v),
+            AnnotationValue::Char(v) => AnnotationValue::Char(*v),
+            AnnotationValue::Short(v) => AnnotationValue::Short(*v),
+            AnnotationValue::Int(v) => AnnotationValue::Int(*v),
+            AnnotationValue::Long(v) => AnnotationValue::Long(*v),
+            AnnotationValue::Float(v) => AnnotationValue::Float(*v),
+            AnnotationValue::Double(v) => AnnotationValue::Double(*v),
+            AnnotationValue::String(v) => AnnotationValue::String(raw_string(class_index, *v)),
+            AnnotationValue::Enum(inner) => AnnotationValue::Enum(Box::new((
+                inner.0.to_raw_type(class_index),
+                raw_string(class_index, inner.1),
+            ))),
+            AnnotationValue::Class(class) => {
+                AnnotationValue::Class(Box::new(class.to_raw_type(class_index)))
+            }
+            AnnotationValue::Array(values) => {
+                AnnotationValue::Array(values.to_raw_type(class_index))
+            }
+        }
+    }
+}
+
+fn raw_string(class_index: &ClassIndex, index: u32) -> CompactString {
+    class_index
+        .constant_pool()
+        .string_view_at(index)
+        .into_str(class_index.constant_pool())
+        .to_compact_string()
+}
+
+impl Validate for IndexedAnnotation {
+    fn validate(&self, bounds: &IndexBounds) -> anyhow::Result<()> {
+        self.annotation_type.validate(bounds)?;
+        for (name, value) in self.elements.iter() {
+            bounds.check_string(*name)?;
+            value.validate(bounds)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Validate for IndexedAnnotationValue {
+    fn validate(&self, bounds: &IndexBounds) -> anyhow::Result<()> {
+        match self {
+            AnnotationValue::String(v) => bounds.check_string(*v),
+            AnnotationValue::Enum(inner) => {
+                inner.0.validate(bounds)?;
+                bounds.check_string(inner.1)
+            }
+            AnnotationValue::Class(class) => class.validate(bounds),
+            AnnotationValue::Array(values) => values.validate(bounds),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Annotation, AnnotationValue};
+    use crate::builder::incremental::to_class_info;
+    use crate::builder::workers::{create_class_index_from_infos, DuplicateClassPolicy};
+    use crate::class_index::AnnotatedElement;
+    use crate::signature::RawSignatureType;
+    use crate::test::class_info;
+
+    #[test]
+    fn test_annotations() {
+        let mut annotated = class_info("a/Annotated", "Ljava/lang/Object;", "(I)V");
+        let marker_type = || RawSignatureType::Object("a/Marker".into());
+        annotated.annotations.push(Annotation::new(
+            marker_type(),
+            true,
+            vec![("value".into(), AnnotationValue::String("text".into()))],
+        ));
+        annotated.annotations.push(Annotation::new(
+            RawSignatureType::Object("b/Missing".into()),
+            true,
+            Vec::new(),
+        ));
+        annotated.methods[0].parameter_annotations =
+            vec![vec![Annotation::new(marker_type(), false, Vec::new())]];
+        let (_, class_index) = create_class_index_from_infos(
+            vec![
+                class_info("a/Marker", "Ljava/lang/Object;", "()V"),
+                annotated,
+            ],
+            DuplicateClassPolicy::First,
+        )
+        .unwrap();
+
+        let marker = class_index.find_class("a", "Marker").unwrap().index();
+        let elements = class_index.find_annotated(marker).unwrap();
+        assert_eq!(elements.len(), 2);
+        assert!(matches!(elements[0], AnnotatedElement::Class(_)));
+        assert!(matches!(elements[1], AnnotatedElement::Parameter(_, _, 0)));
+
+        let class = class_index.find_class("a", "Annotated").unwrap();
+        assert_eq!(class.annotations()[0].annotation_class(), Some(marker));
+        assert_eq!(class.annotations()[1].annotation_class(), None);
+        let annotated_info = to_class_info(class, &class_index, None);
+        assert_eq!(
+            *annotated_info.annotations[0].annotation_type(),
+            marker_type()
+        );
+        assert_eq!(
+            annotated_info.annotations[0].elements()[0].1,
+            AnnotationValue::String("text".into())
+        );
+        assert!(!annotated_info.methods[0].parameter_annotations[0][0].is_visible());
+
+        // Annotations are kept by name until their class is added to the index
+        let (_, new_class_index) = create_class_index_from_infos(
+            vec![
+                annotated_info,
+                class_info("b/Missing", "Ljava/lang/Object;", "()V"),
+            ],
+            DuplicateClassPolicy::First,
+        )
+        .unwrap();
+        let missing = new_class_index.find_class("b", "Missing").unwrap().index();
+        let class = new_class_index.find_class("a", "Annotated").unwrap();
+        assert_eq!(class.annotations()[1].annotation_class(), Some(missing));
+    }
+}
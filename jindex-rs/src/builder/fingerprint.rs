@@ -0,0 +1,234 @@
+use rayon::prelude::*;
+use speedy::{Readable, Writable};
+use std::fs;
+use std::fs::Metadata;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+/// Describes the state of a source at the time it was indexed. This allows checking if the source
+/// has changed since then without indexing it again.
+#[derive(Readable, Writable, Clone, Debug, Eq, PartialEq)]
+pub struct SourceFingerprint {
+    path: String,
+    /// The size in bytes. For directories, this is the total size of all contained class files.
+    size: u64,
+    /// The modification time in milliseconds since the unix epoch. For directories, this is the
+    /// latest modification time of all contained class files.
+    modified: u64,
+    /// The CRC32 checksum of the contents. For directories, this combines the checksums of all
+    /// contained class files.
+    hash: u32,
+}
+
+impl SourceFingerprint {
+    pub(crate) fn new(path: String, data: FingerprintData) -> Self {
+        Self {
+            path,
+            size: data.size,
+            modified: data.modified,
+            hash: data.hash,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Checks if the source at the recorded path still has the same contents. The contents are only
+    /// read if the size is the same, but the modification time changed.
+    pub fn is_up_to_date(&self) -> bool {
+        let path = Path::new(&self.path);
+        let data = match FingerprintData::of_path(path, false) {
+            Ok(data) => data,
+            Err(_) => return false,
+        };
+
+        if data.size != self.size {
+            return false;
+        }
+        if data.modified == self.modified {
+            return true;
+        }
+
+        // The source might have only been touched
+        FingerprintData::of_path(path, true).map_or(false, |data| data.hash == self.hash)
+    }
+}
+
+/// The size, modification time and checksum of one or more files
+#[derive(Clone, Copy, Default)]
+pub(crate) struct FingerprintData {
+    size: u64,
+    modified: u64,
+    hash: u32,
+}
+
+impl FingerprintData {
+    pub(crate) fn of_file(metadata: &Metadata, data: &[u8]) -> Self {
+        Self {
+            size: metadata.len(),
+            modified: modified_millis(metadata),
+            hash: crc32fast::hash(data),
+        }
+    }
+
+    /// Like [FingerprintData::of_file], but also includes the path of the class file relative to
+    /// its class path root in the checksum
+    pub(crate) fn of_class_file(relative_path: &Path, metadata: &Metadata, data: &[u8]) -> Self {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(relative_path.to_string_lossy().as_bytes());
+        hasher.update(data);
+
+        Self {
+            size: metadata.len(),
+            modified: modified_millis(metadata),
+            hash: hasher.finalize(),
+        }
+    }
+
+    /// Combines the data of two files. The order in which files are combined does not matter.
+    pub(crate) fn combine(self, other: Self) -> Self {
+        Self {
+            size: self.size + other.size,
+            modified: self.modified.max(other.modified),
+            hash: self.hash ^ other.hash,
+        }
+    }
+
+    fn of_path(path: &Path, read_contents: bool) -> anyhow::Result<Self> {
+        let metadata = fs::metadata(path)?;
+        if metadata.is_dir() {
+            return Self::of_directory(path, path, read_contents);
+        }
+
+        let data = if read_contents {
+            fs::read(path)?
+        } else {
+            Vec::new()
+        };
+        Ok(Self::of_file(&metadata, &data))
+    }
+
+    fn of_directory(root: &Path, directory: &Path, read_contents: bool) -> anyhow::Result<Self> {
+        fs::read_dir(directory)?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_par_iter()
+            .map(|entry| {
+                let path = entry.path();
+                if entry.file_type()?.is_dir() {
+                    return Self::of_directory(root, &path, read_contents);
+                }
+                if path.extension().map_or(true, |e| e != "class") {
+                    return Ok(Self::default());
+                }
+
+                let data = if read_contents {
+                    fs::read(&path)?
+                } else {
+                    Vec::new()
+                };
+                Ok(Self::of_class_file(
+                    path.strip_prefix(root)?,
+                    &entry.metadata()?,
+                    &data,
+                ))
+            })
+            .try_reduce(Self::default, |a, b| Ok(a.combine(b)))
+    }
+}
+
+fn modified_millis(metadata: &Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FingerprintData, SourceFingerprint};
+    use crate::builder::workers::{create_class_index_from_paths, ReadOptions};
+    use crate::test::{class_bytes, temp_dir, zip_bytes};
+    use std::fs;
+    use std::path::Path;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    fn fingerprint(path: &Path) -> SourceFingerprint {
+        SourceFingerprint::new(
+            path.to_str().unwrap().to_string(),
+            FingerprintData::of_path(path, true).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_file_fingerprint() {
+        let dir = temp_dir("file_fingerprint");
+        let path = dir.join("a.jar");
+        fs::write(&path, b"abc").unwrap();
+        let fingerprint = fingerprint(&path);
+        assert!(fingerprint.is_up_to_date());
+
+        // Only touching the file keeps it up to date
+        sleep(Duration::from_millis(20));
+        fs::write(&path, b"abc").unwrap();
+        assert!(fingerprint.is_up_to_date());
+
+        fs::write(&path, b"abcd").unwrap();
+        assert!(!fingerprint.is_up_to_date());
+
+        fs::remove_file(&path).unwrap();
+        assert!(!fingerprint.is_up_to_date());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_directory_fingerprint() {
+        let dir = temp_dir("directory_fingerprint");
+        fs::create_dir_all(dir.join("a")).unwrap();
+        fs::write(dir.join("a/X.class"), class_bytes("a/X", 52, &[])).unwrap();
+        fs::write(dir.join("a/notes.txt"), b"abc").unwrap();
+        let fingerprint = fingerprint(&dir);
+        assert!(fingerprint.is_up_to_date());
+
+        // Only class files are part of the fingerprint
+        fs::write(dir.join("a/notes.txt"), b"abcd").unwrap();
+        assert!(fingerprint.is_up_to_date());
+
+        fs::write(dir.join("a/Y.class"), class_bytes("a/Y", 52, &[])).unwrap();
+        assert!(!fingerprint.is_up_to_date());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_class_index_is_up_to_date() {
+        let dir = temp_dir("class_index_is_up_to_date");
+        let jar = dir.join("a.jar");
+        fs::write(
+            &jar,
+            zip_bytes(&[("a/X.class", &class_bytes("a/X", 52, &[]))]),
+        )
+        .unwrap();
+        let classes = dir.join("classes");
+        fs::create_dir_all(classes.join("b")).unwrap();
+        fs::write(classes.join("b/Y.class"), class_bytes("b/Y", 52, &[])).unwrap();
+        let jar = jar.to_str().unwrap().to_string();
+        let classes = classes.to_str().unwrap().to_string();
+
+        let (_, class_index) = create_class_index_from_paths(
+            vec![jar.clone(), classes.clone()],
+            ReadOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(2, class_index.classes().len());
+        assert!(class_index.is_up_to_date(&[jar.clone(), classes.clone()]));
+        assert!(class_index.is_up_to_date(&[classes.clone(), jar.clone()]));
+        assert!(!class_index.is_up_to_date(&[jar.clone()]));
+        assert!(!class_index.is_up_to_date(&[jar.clone(), classes.clone(), jar.clone() + "2"]));
+
+        fs::write(&jar, b"not a jar anymore").unwrap();
+        assert!(!class_index.is_up_to_date(&[jar, classes]));
+        let _ = fs::remove_dir_all(dir);
+    }
+}
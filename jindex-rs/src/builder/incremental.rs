@@ -0,0 +1,484 @@
+use crate::builder::fingerprint::SourceFingerprint;
+use crate::builder::workers::{
+    create_class_index_from_infos, do_multi_threaded, process_path_worker,
+};
+use crate::builder::{BuildTimeInfo, ClassInfo, FieldInfo, MethodInfo, RecordComponentInfo};
+use crate::class_index::ClassIndex;
+use crate::class_index_members::IndexedClass;
+use crate::signature::indexed_signature::ToRawType;
+use cafebabe::{FieldAccessFlags, MethodAccessFlags};
+use compact_str::ToCompactString;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Creates a new index from an existing one after the given sources were added, removed or
+/// changed. Sources are identified by the path they were originally read from. Only the added and
+/// changed sources are read again, all other classes are converted back from the existing index.
+///
+/// Types which could not be resolved in the existing index are resolved again against the new
+/// index. Some unchanged sources still have to be read again. This is the case if they contain a
+/// class which was removed as a duplicate of a class in another source, or if the update adds new
+/// classes while they contain a class whose enclosing class could not be resolved before.
+///
+/// Classes are read with the options the existing index was created with. Fails if the members
+/// of a class in the existing index cannot be loaded.
+pub fn update_class_index(
+    class_index: &ClassIndex,
+    added_sources: Vec<String>,
+    removed_sources: Vec<String>,
+    changed_sources: Vec<String>,
+) -> anyhow::Result<(BuildTimeInfo, ClassIndex)> {
+    let now = Instant::now();
+    let options = class_index.read_options();
+    // Unchanged classes are converted back from their members, a class whose members could not be
+    // loaded would otherwise lose them
+    class_index.load_all_members()?;
+
+    let removed = removed_sources
+        .iter()
+        .map(String::as_str)
+        .collect::<FxHashSet<_>>();
+
+    let source_roots = class_index
+        .sources()
+        .iter()
+        .map(|source| source_root(source))
+        .collect::<Vec<_>>();
+
+    // The order of the sources decides which class is kept if a class is found multiple times,
+    // therefore new sources are always read last. Fingerprints also include sources without any
+    // classes.
+    let mut roots: Vec<&str> = Vec::new();
+    for root in class_index
+        .source_fingerprints()
+        .iter()
+        .map(SourceFingerprint::path)
+        .chain(source_roots.iter().copied())
+        .chain(added_sources.iter().map(String::as_str))
+    {
+        if !removed.contains(root) && !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+
+    let mut classes_by_source = vec![Vec::new(); class_index.sources().len()];
+    let mut classes_without_source = Vec::new();
+    for class in class_index.classes() {
+        match class.source_index() {
+            Some(index) => classes_by_source[index as usize].push(class),
+            None => classes_without_source.push(class),
+        }
+    }
+
+    let mut to_read = changed_sources
+        .iter()
+        .chain(added_sources.iter())
+        .map(String::as_str)
+        .filter(|source| !removed.contains(source))
+        .collect::<FxHashSet<_>>();
+    // The index only contains the kept version of a class which was found multiple times. The
+    // other versions have to be read again to report the duplicates in the new index and to keep
+    // the class if the source of the kept version changes.
+    for (class, source_indices) in class_index.duplicate_class_sources() {
+        let kept_source = class_index.class_at_index(*class).source_index();
+        to_read.extend(
+            source_indices
+                .iter()
+                .filter(|index| Some(**index) != kept_source)
+                .map(|index| source_roots[*index as usize])
+                .filter(|root| !removed.contains(root)),
+        );
+    }
+
+    let mut read_classes: FxHashMap<&str, Vec<ClassInfo>> = FxHashMap::default();
+    let mut read_fingerprints: FxHashMap<&str, SourceFingerprint> = FxHashMap::default();
+    let mut queue = roots
+        .iter()
+        .copied()
+        .filter(|root| to_read.contains(root))
+        .collect::<Vec<_>>();
+    let mut checked_unresolved_types = false;
+    let mut skipped_sources = Vec::new();
+    while !queue.is_empty() {
+        let results = do_multi_threaded(
+            queue.iter().map(|root| root.to_string()).collect(),
+            &|name| process_path_worker(name, options),
+        )?;
+
+        let mut next_to_read = FxHashSet::default();
+        let mut found_new_classes = false;
+        for (root, (class_infos, fingerprint, skipped)) in queue.into_iter().zip(results) {
+            skipped_sources.extend(skipped);
+            for class_info in class_infos.iter() {
+                match find_existing_class(class_index, class_info) {
+                    Some(class) => {
+                        // Other versions of this class have to be read again to report and select
+                        // the duplicates correctly
+                        next_to_read.extend(
+                            class_index
+                                .duplicate_class_sources_of(class.index())
+                                .into_iter()
+                                .flatten()
+                                .map(|index| source_roots[*index as usize]),
+                        );
+                    }
+                    None => found_new_classes = true,
+                }
+            }
+            read_classes.insert(root, class_infos);
+            read_fingerprints.insert(root, fingerprint);
+        }
+
+        // Unresolved types in signatures keep their names and are resolved again when the new
+        // index is created, but the name of an unresolved enclosing class is not stored
+        if found_new_classes && !checked_unresolved_types {
+            checked_unresolved_types = true;
+            next_to_read.extend(source_roots.iter().enumerate().filter_map(|(index, root)| {
+                classes_by_source[index]
+                    .iter()
+                    .any(|class| has_unresolved_enclosing_class(class))
+                    .then_some(*root)
+            }));
+        }
+
+        queue = roots
+            .iter()
+            .copied()
+            .filter(|root| next_to_read.contains(root) && !read_classes.contains_key(root))
+            .collect();
+    }
+
+    let sources = class_index
+        .sources()
+        .iter()
+        .map(|source| Arc::from(source.as_str()))
+        .collect::<Vec<Arc<str>>>();
+    let mut class_info_list = classes_without_source
+        .into_iter()
+        .map(|class| to_class_info(class, class_index, None))
+        .collect::<Vec<_>>();
+    let mut fingerprints = Vec::new();
+    for root in roots {
+        if let Some(fingerprint) = read_fingerprints.remove(root).or_else(|| {
+            class_index
+                .source_fingerprints()
+                .iter()
+                .find(|fingerprint| fingerprint.path() == root)
+                .cloned()
+        }) {
+            fingerprints.push(fingerprint);
+        }
+
+        if let Some(class_infos) = read_classes.remove(root) {
+            class_info_list.extend(class_infos);
+            continue;
+        }
+
+        for (index, classes) in classes_by_source.iter().enumerate() {
+            if source_roots[index] != root {
+                continue;
+            }
+
+            class_info_list.extend(
+                classes
+                    .iter()
+                    .map(|class| to_class_info(class, class_index, Some(sources[index].clone()))),
+            );
+        }
+    }
+
+    let mut info = BuildTimeInfo {
+        class_reading_time: now.elapsed().as_millis(),
+        skipped_sources,
+        ..Default::default()
+    };
+
+    let (other_info, mut class_index) =
+        create_class_index_from_infos(class_info_list, options.duplicate_class_policy())?;
+    class_index.set_source_fingerprints(fingerprints);
+    class_index.set_read_options(options.clone());
+    info.merge(other_info);
+    Ok((info, class_index))
+}
+
+/// Jars nested in other jars are stored as `outer.jar!/inner.jar`, but can only be read again
+/// through the outer jar
+fn source_root(source: &str) -> &str {
+    source.split_once("!/").map_or(source, |(root, _)| root)
+}
+
+fn find_existing_class<'a>(
+    class_index: &'a ClassIndex,
+    class_info: &ClassInfo,
+) -> Option<&'a IndexedClass> {
+    class_index.find_class(&class_info.package_name, &class_info.class_name)
+}
+
+fn has_unresolved_enclosing_class(class: &IndexedClass) -> bool {
+    class
+        .enclosing_type_info()
+        .map_or(false, |info| info.class_name().is_none())
+}
+
+pub(crate) fn to_class_info(
+    class: &IndexedClass,
+    class_index: &ClassIndex,
+    source: Option<Arc<str>>,
+) -> ClassInfo {
+    let constant_pool = class_index.constant_pool();
+    let package_index = class_index.package_index();
+
+    let member_classes = class
+        .member_classes()
+        .iter()
+        .map(|index| {
+            class_index
+                .class_at_index(*index)
+                .class_name_with_package(package_index, constant_pool)
+                .to_compact_string()
+        })
+        .collect::<Vec<_>>();
+
+    ClassInfo {
+        package_name: package_index
+            .package_at(class.package_index())
+            .package_name_with_parents(package_index, constant_pool)
+            .to_compact_string(),
+        class_name: class.class_name(constant_pool).to_compact_string(),
+        class_name_start_index: class.class_name_start_index() as usize,
+        access_flags: class.access_flags(),
+        class_file_version: class.class_file_version(),
+        module_name: class
+            .module_name(constant_pool)
+            .map(|name| name.to_compact_string()),
+        source,
+        enclosing_type: class
+            .enclosing_type_info()
+            .map(|info| info.to_raw_type(class_index)),
+        member_classes: (!member_classes.is_empty()).then_some(member_classes),
+        signature: class.signature().to_raw_type(class_index),
+        fields: class
+            .fields()
+            .iter()
+            .map(|field| FieldInfo {
+                field_name: field.field_name(constant_pool).to_compact_string(),
+                descriptor: field.field_signature().to_raw_type(class_index),
+                access_flags: FieldAccessFlags::from_bits_truncate(field.access_flags()),
+                annotations: field.annotations().to_raw_type(class_index),
+                constant_value: field.constant_value().map(|v| v.to_raw_type(class_index)),
+            })
+            .collect(),
+        methods: class
+            .methods()
+            .iter()
+            .enumerate()
+            .map(|(index, method)| MethodInfo {
+                method_name: method.method_name(constant_pool).to_compact_string(),
+                signature: method.method_signature().to_raw_type(class_index),
+                access_flags: MethodAccessFlags::from_bits_truncate(method.access_flags()),
+                annotations: method.annotations().to_raw_type(class_index),
+                parameter_annotations: method
+                    .parameter_annotations()
+                    .iter()
+                    .map(|annotations| annotations.to_raw_type(class_index))
+                    .collect(),
+                parameter_names: (0..method.parameter_name_indices().len())
+                    .map(|index| {
+                        method
+                            .parameter_name(constant_pool, index)
+                            .map(|name| name.to_compact_string())
+                    })
+                    .collect(),
+                references: class
+                    .method_references()
+                    .get(index)
+                    .map(|references| references.to_raw_type(class_index))
+                    .unwrap_or_default(),
+            })
+            .collect(),
+        annotations: class.annotations().to_raw_type(class_index),
+        permitted_subclasses: class
+            .permitted_subclasses()
+            .iter()
+            .map(|index| {
+                class_index
+                    .class_at_index(*index)
+                    .class_name_with_package(package_index, constant_pool)
+                    .to_compact_string()
+            })
+            .collect(),
+        nest_host: class.nest_host().map(|index| {
+            class_index
+                .class_at_index(index)
+                .class_name_with_package(package_index, constant_pool)
+                .to_compact_string()
+        }),
+        nest_members: class
+            .nest_members()
+            .iter()
+            .map(|index| {
+                class_index
+                    .class_at_index(*index)
+                    .class_name_with_package(package_index, constant_pool)
+                    .to_compact_string()
+            })
+            .collect(),
+        record_components: class.record_components().map(|components| {
+            components
+                .iter()
+                .map(|component| RecordComponentInfo {
+                    name: component.name(constant_pool).to_compact_string(),
+                    signature: component.signature().to_raw_type(class_index),
+                })
+                .collect()
+        }),
+        string_literals: class
+            .string_literals()
+            .iter()
+            .map(|index| {
+                constant_pool
+                    .string_view_at(*index)
+                    .into_str(constant_pool)
+                    .to_compact_string()
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_class_info, update_class_index};
+    use crate::builder::workers::{
+        create_class_index_from_infos, create_class_index_from_paths, DuplicateClassPolicy,
+        ReadOptions,
+    };
+    use crate::signature::indexed_signature::ToSignatureIndexedType;
+    use crate::test::{class_bytes, class_info, temp_dir, zip_bytes};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_convert_indexed_class_back_to_class_info() {
+        let (_, class_index) = create_class_index_from_infos(
+            vec![
+                class_info(
+                    "a/Outer",
+                    "<T:Ljava/lang/Object;>Ljava/lang/Object;",
+                    "()La/Outer<TT;>.Inner;",
+                ),
+                class_info("a/Outer$Inner", "La/Outer;", "(Lb/Missing;)V"),
+            ],
+            DuplicateClassPolicy::First,
+        )
+        .unwrap();
+
+        let class_infos = class_index
+            .classes()
+            .iter()
+            .map(|class| to_class_info(class, &class_index, Some(Arc::from("a.jar"))))
+            .collect();
+        let (_, new_class_index) =
+            create_class_index_from_infos(class_infos, DuplicateClassPolicy::First).unwrap();
+
+        assert_eq!(class_index.classes().len(), new_class_index.classes().len());
+        for (old, new) in class_index
+            .classes()
+            .iter()
+            .zip(new_class_index.classes().iter())
+        {
+            assert_eq!(
+                old.class_name(class_index.constant_pool()),
+                new.class_name(new_class_index.constant_pool())
+            );
+            assert_eq!(
+                old.signature().to_signature_string(&class_index),
+                new.signature().to_signature_string(&new_class_index)
+            );
+            assert_eq!(
+                old.methods()[0]
+                    .method_signature()
+                    .to_signature_string(&class_index),
+                new.methods()[0]
+                    .method_signature()
+                    .to_signature_string(&new_class_index)
+            );
+        }
+    }
+
+    #[test]
+    fn test_keep_duplicates_across_updates() {
+        let dir = temp_dir("keep_duplicates");
+        let jar = |name: &str, classes: &[(&str, &[&str])]| {
+            let bytes = classes
+                .iter()
+                .map(|(class, fields)| (format!("{}.class", class), class_bytes(class, 52, fields)))
+                .collect::<Vec<_>>();
+            let entries = bytes
+                .iter()
+                .map(|(name, bytes)| (name.as_str(), bytes.as_slice()))
+                .collect::<Vec<_>>();
+            let path = dir.join(name);
+            std::fs::write(&path, zip_bytes(&entries)).unwrap();
+            path.to_str().unwrap().to_string()
+        };
+        let a = jar("a.jar", &[("a/X", &["a"])]);
+        let b = jar("b.jar", &[("a/X", &["b"]), ("a/Y", &[])]);
+        let c = jar("c.jar", &[("a/Z", &[])]);
+
+        let options = ReadOptions::default().with_references(true);
+        let (info, class_index) =
+            create_class_index_from_paths(vec![a.clone(), b, c.clone()], options.clone()).unwrap();
+        assert_eq!(1, info.duplicate_classes.len());
+
+        // Neither a.jar nor b.jar change, but the duplicate still has to be reported
+        let (info, class_index) =
+            update_class_index(&class_index, Vec::new(), Vec::new(), vec![c]).unwrap();
+        assert_eq!(1, info.duplicate_classes.len());
+        assert!(info.duplicate_classes[0].members_differ);
+
+        // The version from b.jar is kept after removing the kept version
+        let (info, class_index) =
+            update_class_index(&class_index, Vec::new(), vec![a], Vec::new()).unwrap();
+        assert!(info.duplicate_classes.is_empty());
+        let class = class_index.find_class("a", "X").unwrap();
+        assert_eq!(
+            "b",
+            class.fields()[0].field_name(class_index.constant_pool())
+        );
+        assert_eq!(&options, class_index.read_options());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_resolve_unresolved_types_in_new_index() {
+        let (_, class_index) = create_class_index_from_infos(
+            vec![class_info(
+                "a/A",
+                "Ljava/lang/Object;",
+                "(Lb/Missing$Inner;La/A.Inner;)Lb/Missing<La/A;>;",
+            )],
+            DuplicateClassPolicy::First,
+        )
+        .unwrap();
+        let class = class_index.find_class("a", "A").unwrap();
+        assert!(class.methods()[0]
+            .method_signature()
+            .to_signature_string(&class_index)
+            .contains("jindex_unresolved"));
+
+        let mut class_infos = vec![to_class_info(class, &class_index, Some(Arc::from("a.jar")))];
+        class_infos.push(class_info("b/Missing", "Ljava/lang/Object;", "()V"));
+        class_infos.push(class_info("b/Missing$Inner", "Ljava/lang/Object;", "()V"));
+        class_infos.push(class_info("a/A$Inner", "Ljava/lang/Object;", "()V"));
+        let (_, new_class_index) =
+            create_class_index_from_infos(class_infos, DuplicateClassPolicy::First).unwrap();
+
+        let class = new_class_index.find_class("a", "A").unwrap();
+        assert_eq!(
+            class.methods()[0]
+                .method_signature()
+                .to_signature_string(&new_class_index),
+            "(Lb/Missing$Inner;La/A.Inner;)Lb/Missing<La/A;>;"
+        );
+    }
+}
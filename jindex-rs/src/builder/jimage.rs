@@ -0,0 +1,196 @@
+use anyhow::{anyhow, bail};
+use std::borrow::Cow;
+
+const JIMAGE_MAGIC: u32 = 0xCAFE_DADA;
+const HEADER_SIZE: usize = 7 * 4;
+
+const ATTRIBUTE_END: u8 = 0;
+const ATTRIBUTE_MODULE: u8 = 1;
+const ATTRIBUTE_BASE: u8 = 3;
+const ATTRIBUTE_EXTENSION: u8 = 4;
+const ATTRIBUTE_OFFSET: u8 = 5;
+const ATTRIBUTE_COMPRESSED: u8 = 6;
+const ATTRIBUTE_UNCOMPRESSED: u8 = 7;
+const ATTRIBUTE_COUNT: usize = 8;
+
+/// A read-only view of a jimage file, which is the `lib/modules` file found in every JDK 9+
+/// runtime image. The format is not specified anywhere, this implementation follows
+/// `jdk.internal.jimage.BasicImageReader`.
+pub struct JImage<'a> {
+    data: &'a [u8],
+    big_endian: bool,
+    table_length: usize,
+    offsets_start: usize,
+    locations_start: usize,
+    strings_start: usize,
+    index_size: usize,
+}
+
+/// A single resource stored in a jimage file, e.g. `/java.base/java/lang/String.class`
+pub struct JImageResource<'a> {
+    pub module: Cow<'a, str>,
+    pub base: Cow<'a, str>,
+    pub extension: Cow<'a, str>,
+    /// The uncompressed contents, or None if the resource is stored compressed
+    pub data: Option<&'a [u8]>,
+}
+
+impl<'a> JImage<'a> {
+    pub fn parse(data: &'a [u8]) -> anyhow::Result<Self> {
+        if data.len() < HEADER_SIZE {
+            bail!("File too small to be a jimage file");
+        }
+
+        let big_endian = match u32::from_le_bytes(data[0..4].try_into()?) {
+            JIMAGE_MAGIC => false,
+            m if m.swap_bytes() == JIMAGE_MAGIC => true,
+            _ => bail!("Invalid jimage magic number"),
+        };
+
+        let mut image = JImage {
+            data,
+            big_endian,
+            table_length: 0,
+            offsets_start: 0,
+            locations_start: 0,
+            strings_start: 0,
+            index_size: 0,
+        };
+
+        let table_length = image.read_u32(4 * 4)? as usize;
+        let locations_size = image.read_u32(5 * 4)? as usize;
+        let strings_size = image.read_u32(6 * 4)? as usize;
+
+        // The redirect table comes first, followed by the offsets table
+        image.table_length = table_length;
+        image.offsets_start = HEADER_SIZE + table_length * 4;
+        image.locations_start = image.offsets_start + table_length * 4;
+        image.strings_start = image.locations_start + locations_size;
+        image.index_size = image.strings_start + strings_size;
+
+        if image.index_size > data.len() {
+            bail!("Truncated jimage file");
+        }
+
+        Ok(image)
+    }
+
+    pub fn resources(&self) -> impl Iterator<Item = anyhow::Result<JImageResource<'a>>> + '_ {
+        (0..self.table_length).map(|i| {
+            let location_offset = self.read_u32(self.offsets_start + i * 4)? as usize;
+            self.resource_at(self.locations_start + location_offset)
+        })
+    }
+
+    fn resource_at(&self, location_start: usize) -> anyhow::Result<JImageResource<'a>> {
+        let mut attributes = [0u64; ATTRIBUTE_COUNT];
+        let mut index = location_start;
+        loop {
+            let byte = *self
+                .data
+                .get(index)
+                .ok_or_else(|| anyhow!("Location out of bounds"))?;
+            let kind = byte >> 3;
+            if kind == ATTRIBUTE_END {
+                break;
+            }
+            if kind as usize >= ATTRIBUTE_COUNT {
+                bail!("Invalid location attribute kind {}", kind);
+            }
+
+            let length = (byte & 0x7) as usize + 1;
+            let value_bytes = self
+                .data
+                .get(index + 1..index + 1 + length)
+                .ok_or_else(|| anyhow!("Location attribute out of bounds"))?;
+            attributes[kind as usize] = value_bytes
+                .iter()
+                .fold(0u64, |value, b| (value << 8) | *b as u64);
+            index += 1 + length;
+        }
+
+        let content_start = self.index_size + attributes[ATTRIBUTE_OFFSET as usize] as usize;
+        let data = if attributes[ATTRIBUTE_COMPRESSED as usize] != 0 {
+            None
+        } else {
+            let size = attributes[ATTRIBUTE_UNCOMPRESSED as usize] as usize;
+            Some(
+                self.data
+                    .get(content_start..content_start + size)
+                    .ok_or_else(|| anyhow!("Resource content out of bounds"))?,
+            )
+        };
+
+        Ok(JImageResource {
+            module: self.string_at(attributes[ATTRIBUTE_MODULE as usize] as usize)?,
+            base: self.string_at(attributes[ATTRIBUTE_BASE as usize] as usize)?,
+            extension: self.string_at(attributes[ATTRIBUTE_EXTENSION as usize] as usize)?,
+            data,
+        })
+    }
+
+    fn string_at(&self, offset: usize) -> anyhow::Result<Cow<'a, str>> {
+        let start = self.strings_start + offset;
+        let bytes = self
+            .data
+            .get(start..self.index_size)
+            .ok_or_else(|| anyhow!("String offset out of bounds"))?;
+        let end = bytes
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or_else(|| anyhow!("Unterminated string"))?;
+
+        Ok(String::from_utf8_lossy(&bytes[..end]))
+    }
+
+    fn read_u32(&self, offset: usize) -> anyhow::Result<u32> {
+        let bytes: [u8; 4] = self
+            .data
+            .get(offset..offset + 4)
+            .ok_or_else(|| anyhow!("Offset {} out of bounds", offset))?
+            .try_into()?;
+
+        Ok(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JImage;
+
+    #[test]
+    fn test_parse_single_resource() {
+        let strings = b"\0java.base\0String\0class\0";
+        let content = b"\xCA\xFE\xBA\xBE";
+        // MODULE=1, BASE=11, EXTENSION=18, OFFSET=0, UNCOMPRESSED=4, END
+        let locations = [1 << 3, 1, 3 << 3, 11, 4 << 3, 18, 5 << 3, 0, 7 << 3, 4, 0];
+
+        let mut data = Vec::new();
+        for value in [0xCAFE_DADAu32, 1 << 16, 0, 1, 1, locations.len() as u32] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&(strings.len() as u32).to_le_bytes());
+        data.extend_from_slice(&0i32.to_le_bytes()); // Redirect table
+        data.extend_from_slice(&0u32.to_le_bytes()); // Offsets table
+        data.extend_from_slice(&locations);
+        data.extend_from_slice(strings);
+        data.extend_from_slice(content);
+
+        let image = JImage::parse(&data).unwrap();
+        let resources = image
+            .resources()
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(1, resources.len());
+
+        let resource = &resources[0];
+        assert_eq!("java.base", resource.module);
+        assert_eq!("String", resource.base);
+        assert_eq!("class", resource.extension);
+        assert_eq!(Some(&content[..]), resource.data);
+    }
+}
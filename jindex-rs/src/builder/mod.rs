@@ -1,20 +1,30 @@
+use crate::annotation::{index_annotations, RawAnnotation};
 use crate::class_index::ClassIndex;
-use crate::class_index_members::{IndexedClass, IndexedField, IndexedMethod};
+use crate::class_index_members::{
+    IndexedClass, IndexedField, IndexedMethod, IndexedRecordComponent,
+};
 use crate::constant_pool::ClassIndexConstantPool;
+use crate::constant_value::RawConstantValue;
 use crate::package_index::PackageIndex;
+use crate::reference::{index_references, RawReference};
 use crate::signature::indexed_signature::ToIndexedType;
 use crate::signature::{
     RawClassSignature, RawEnclosingTypeInfo, RawMethodSignature, RawSignatureType,
 };
 use anyhow::anyhow;
-use ascii::{AsciiChar, AsciiStr, AsciiString};
 use cafebabe::{FieldAccessFlags, MethodAccessFlags};
 use compact_str::CompactString;
 use rayon::prelude::*;
 use rustc_hash::FxHashMap;
+use std::borrow::Cow;
 use std::collections::hash_map::Entry::{Occupied, Vacant};
+use std::sync::Arc;
 use std::time::Instant;
 
+pub mod fingerprint;
+pub mod incremental;
+mod jimage;
+mod module_info;
 pub mod workers;
 
 pub(crate) type ClassToIndexMap<'a> = FxHashMap<(&'a str, &'a str), (u32, &'a IndexedClass)>;
@@ -23,6 +33,7 @@ struct ClassIndexBuilder {
     expected_method_count: u32,
     average_class_name_size: u32,
     average_method_name_size: u32,
+    sources: Vec<String>,
 }
 
 impl ClassIndexBuilder {
@@ -31,6 +42,7 @@ impl ClassIndexBuilder {
             expected_method_count: 0,
             average_class_name_size: 15,
             average_method_name_size: 8,
+            sources: Vec::new(),
         }
     }
 
@@ -39,7 +51,17 @@ impl ClassIndexBuilder {
         self
     }
 
-    fn build(self, vec: Vec<ClassInfo>) -> anyhow::Result<(BuildTimeInfo, ClassIndex)> {
+    /// Sets the sources of all classes which will be indexed, in the order they were read in
+    fn with_sources(mut self, sources: Vec<String>) -> Self {
+        self.sources = sources;
+        self
+    }
+
+    fn build(
+        self,
+        vec: Vec<ClassInfo>,
+        duplicate_classes: &[DuplicateClass],
+    ) -> anyhow::Result<(BuildTimeInfo, ClassIndex)> {
         let start_time = Instant::now();
         let element_count = vec.len() as u32;
 
@@ -51,10 +73,16 @@ impl ClassIndexBuilder {
 
         let mut package_index = PackageIndex::new();
         let mut classes: Vec<((&str, &str), IndexedClass)> = Vec::with_capacity(vec.len());
-        let mut constant_pool_map: FxHashMap<&str, u32> = FxHashMap::with_capacity_and_hasher(
+        let mut constant_pool_map: FxHashMap<Cow<str>, u32> = FxHashMap::with_capacity_and_hasher(
             vec.len() + self.expected_method_count as usize,
             Default::default(),
         );
+        let sources = self.sources;
+        let sources_map: FxHashMap<&str, u32> = sources
+            .iter()
+            .enumerate()
+            .map(|(index, source)| (source.as_str(), index as u32))
+            .collect();
 
         for class_info in vec.iter() {
             let package_index = package_index
@@ -65,11 +93,32 @@ impl ClassIndexBuilder {
                 &mut constant_pool,
             )?;
 
+            let module_name_index = match &class_info.module_name {
+                Some(name) => Some(get_index_from_pool(
+                    name,
+                    &mut constant_pool_map,
+                    &mut constant_pool,
+                )?),
+                None => None,
+            };
+
+            let source_index = match class_info.source.as_deref() {
+                Some(source) => Some(
+                    *sources_map
+                        .get(source)
+                        .ok_or_else(|| anyhow!("Source {} not found", source))?,
+                ),
+                None => None,
+            };
+
             let indexed_class = IndexedClass::new(
                 package_index,
                 class_name_index,
-                class_info.class_name_start_index as u8, //Name can't be longer than u8::MAX
+                class_info.class_name_start_index as u16, //Name can't be longer than u16::MAX
                 class_info.access_flags,
+                class_info.class_file_version,
+                module_name_index,
+                source_index,
             );
 
             classes.push((
@@ -87,7 +136,7 @@ impl ClassIndexBuilder {
         let mut classes_map: ClassToIndexMap =
             FxHashMap::with_capacity_and_hasher(classes.len(), Default::default());
         // Build a name to index map and give each class its index
-        classes.iter_mut().enumerate().for_each(|(index, class)| {
+        classes.iter().enumerate().for_each(|(index, class)| {
             class.1.set_index(index as u32);
             classes_map.insert(class.0, (index as u32, &class.1));
         });
@@ -124,6 +173,76 @@ impl ClassIndexBuilder {
                 )?);
             }
 
+            //Annotations
+            indexed_class.set_annotations(index_annotations(
+                &class_info.annotations,
+                &mut constant_pool,
+                &mut constant_pool_map,
+                &classes_map,
+            )?);
+
+            //Permitted subclasses
+            indexed_class.set_permitted_subclasses(
+                class_info
+                    .permitted_subclasses
+                    .iter()
+                    .filter_map(|c| classes_map.get(&c.rsplit_once('/').unwrap_or(("", c))))
+                    .map(|c| c.0)
+                    .collect(),
+            );
+
+            //Nest host and members
+            indexed_class.set_nest_host(
+                class_info
+                    .nest_host
+                    .as_ref()
+                    .and_then(|c| classes_map.get(&c.rsplit_once('/').unwrap_or(("", c))))
+                    .map(|c| c.0),
+            );
+            indexed_class.set_nest_members(
+                class_info
+                    .nest_members
+                    .iter()
+                    .filter_map(|c| classes_map.get(&c.rsplit_once('/').unwrap_or(("", c))))
+                    .map(|c| c.0)
+                    .collect(),
+            );
+
+            //Record components
+            let record_components = match &class_info.record_components {
+                Some(components) => {
+                    let mut indexed_components = Vec::with_capacity(components.len());
+                    for component in components.iter() {
+                        indexed_components.push(IndexedRecordComponent::new(
+                            get_index_from_pool(
+                                &component.name,
+                                &mut constant_pool_map,
+                                &mut constant_pool,
+                            )?,
+                            component.signature.to_indexed_type(
+                                &mut constant_pool,
+                                &mut constant_pool_map,
+                                &classes_map,
+                            )?,
+                        ));
+                    }
+                    Some(indexed_components)
+                }
+                None => None,
+            };
+            indexed_class.set_record_components(record_components);
+
+            //String literals
+            let mut string_literals = Vec::with_capacity(class_info.string_literals.len());
+            for literal in class_info.string_literals.iter() {
+                string_literals.push(get_index_from_pool(
+                    literal,
+                    &mut constant_pool_map,
+                    &mut constant_pool,
+                )?);
+            }
+            indexed_class.set_string_literals(string_literals);
+
             //Member classes
             if let Some(members) = &class_info.member_classes {
                 members
@@ -154,6 +273,17 @@ impl ClassIndexBuilder {
                         &mut constant_pool_map,
                         &classes_map,
                     )?,
+                    index_annotations(
+                        &field_info.annotations,
+                        &mut constant_pool,
+                        &mut constant_pool_map,
+                        &classes_map,
+                    )?,
+                    field_info.constant_value.to_indexed_type(
+                        &mut constant_pool,
+                        &mut constant_pool_map,
+                        &classes_map,
+                    )?,
                 ));
             }
 
@@ -173,6 +303,26 @@ impl ClassIndexBuilder {
                 let method_name_index =
                     get_index_from_pool(method_name, &mut constant_pool_map, &mut constant_pool)?;
 
+                let mut parameter_annotations =
+                    Vec::with_capacity(method_info.parameter_annotations.len());
+                for annotations in method_info.parameter_annotations.iter() {
+                    parameter_annotations.push(index_annotations(
+                        annotations,
+                        &mut constant_pool,
+                        &mut constant_pool_map,
+                        &classes_map,
+                    )?);
+                }
+
+                let mut parameter_names = Vec::with_capacity(method_info.parameter_names.len());
+                for name in method_info.parameter_names.iter() {
+                    parameter_names.push(get_index_from_pool(
+                        name.as_deref().unwrap_or(""),
+                        &mut constant_pool_map,
+                        &mut constant_pool,
+                    )?);
+                }
+
                 indexed_methods.push(IndexedMethod::new(
                     method_name_index,
                     method_info.access_flags.bits(),
@@ -181,6 +331,14 @@ impl ClassIndexBuilder {
                         &mut constant_pool_map,
                         &classes_map,
                     )?,
+                    index_annotations(
+                        &method_info.annotations,
+                        &mut constant_pool,
+                        &mut constant_pool_map,
+                        &classes_map,
+                    )?,
+                    parameter_annotations,
+                    parameter_names,
                 ));
             }
 
@@ -192,6 +350,48 @@ impl ClassIndexBuilder {
             })?;
         }
 
+        // References can only be resolved once the members of all classes are known
+        for class_info in vec.iter() {
+            if class_info.methods.iter().all(|m| m.references.is_empty()) {
+                continue;
+            }
+
+            let (_, indexed_class) = classes_map[&(
+                class_info.package_name.as_str(),
+                class_info.class_name.as_str(),
+            )];
+            let mut method_references = Vec::with_capacity(class_info.methods.len());
+            for method_info in class_info.methods.iter() {
+                method_references.push(index_references(
+                    &method_info.references,
+                    &constant_pool,
+                    &classes_map,
+                    &|index| &classes[index as usize].1,
+                )?);
+            }
+            indexed_class.set_method_references(method_references);
+        }
+
+        let mut duplicate_class_sources = duplicate_classes
+            .iter()
+            .filter_map(|duplicate_class| {
+                let (class_index, _) = classes_map.get(
+                    &duplicate_class
+                        .name
+                        .rsplit_once('/')
+                        .unwrap_or(("", &duplicate_class.name)),
+                )?;
+                let source_indices = duplicate_class
+                    .sources
+                    .iter()
+                    .flatten()
+                    .filter_map(|source| sources_map.get(source.as_str()).copied())
+                    .collect::<Vec<_>>();
+                Some((*class_index, source_indices))
+            })
+            .collect::<Vec<_>>();
+        duplicate_class_sources.sort_unstable_by_key(|(class_index, _)| *class_index);
+
         let classes = classes.into_iter().map(|class| class.1).collect();
 
         Ok((
@@ -199,7 +399,13 @@ impl ClassIndexBuilder {
                 indexing_time: start_time.elapsed().as_millis(),
                 ..Default::default()
             },
-            ClassIndex::new(constant_pool, package_index, classes),
+            ClassIndex::new(
+                constant_pool,
+                package_index,
+                classes,
+                sources,
+                duplicate_class_sources,
+            ),
         ))
     }
 
@@ -228,10 +434,10 @@ impl ClassIndexBuilder {
 
 pub fn get_index_from_pool<'a>(
     value: &'a str,
-    map: &mut FxHashMap<&'a str, u32>,
+    map: &mut FxHashMap<Cow<'a, str>, u32>,
     pool: &mut ClassIndexConstantPool,
 ) -> anyhow::Result<u32> {
-    let entry = map.entry(value);
+    let entry = map.entry(Cow::Borrowed(value));
     Ok(match entry {
         Occupied(o) => *o.get(),
         Vacant(v) => {
@@ -242,6 +448,21 @@ pub fn get_index_from_pool<'a>(
     })
 }
 
+/// Like [get_index_from_pool], but for strings which are created while indexing
+pub fn get_owned_index_from_pool(
+    value: String,
+    map: &mut FxHashMap<Cow<str>, u32>,
+    pool: &mut ClassIndexConstantPool,
+) -> anyhow::Result<u32> {
+    if let Some(index) = map.get(value.as_str()) {
+        return Ok(*index);
+    }
+
+    let index = pool.add_string(value.as_bytes())?;
+    map.insert(Cow::Owned(value), index);
+    Ok(index)
+}
+
 impl Default for ClassIndexBuilder {
     fn default() -> Self {
         Self::new()
@@ -249,30 +470,57 @@ impl Default for ClassIndexBuilder {
 }
 
 #[derive(Debug)]
-struct ClassInfo {
+pub(crate) struct ClassInfo {
     pub package_name: CompactString,
     pub class_name: CompactString,
     pub class_name_start_index: usize,
     pub access_flags: u16,
+    pub class_file_version: u16,
+    pub module_name: Option<CompactString>,
+    /// The jar, directory or runtime image this class was read from
+    pub source: Option<Arc<str>>,
     pub enclosing_type: Option<RawEnclosingTypeInfo>,
     pub member_classes: Option<Vec<CompactString>>,
     pub signature: RawClassSignature,
     pub fields: Vec<FieldInfo>,
     pub methods: Vec<MethodInfo>,
+    pub annotations: Vec<RawAnnotation>,
+    pub permitted_subclasses: Vec<CompactString>,
+    pub nest_host: Option<CompactString>,
+    pub nest_members: Vec<CompactString>,
+    /// The components of a record, None if the class is not a record
+    pub record_components: Option<Vec<RecordComponentInfo>>,
+    /// The distinct strings used by this class, empty if string literals are not indexed
+    pub string_literals: Vec<CompactString>,
 }
 
-#[derive(Debug)]
-struct FieldInfo {
+#[derive(Debug, PartialEq)]
+pub(crate) struct FieldInfo {
     pub field_name: CompactString,
     pub descriptor: RawSignatureType,
     pub access_flags: FieldAccessFlags,
+    pub annotations: Vec<RawAnnotation>,
+    pub constant_value: Option<RawConstantValue>,
 }
 
-#[derive(Debug)]
-struct MethodInfo {
+#[derive(Debug, PartialEq)]
+pub(crate) struct RecordComponentInfo {
+    pub name: CompactString,
+    pub signature: RawSignatureType,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct MethodInfo {
     pub method_name: CompactString,
     pub signature: RawMethodSignature,
     pub access_flags: MethodAccessFlags,
+    pub annotations: Vec<RawAnnotation>,
+    /// The annotations of each parameter, empty if no parameter is annotated
+    pub parameter_annotations: Vec<Vec<RawAnnotation>>,
+    /// The name of each parameter of the descriptor, empty if no name is known
+    pub parameter_names: Vec<Option<CompactString>>,
+    /// The references found in the code of this method, empty if references are not indexed
+    pub references: Vec<RawReference>,
 }
 
 #[derive(Debug, Default)]
@@ -280,6 +528,77 @@ pub struct BuildTimeInfo {
     pub deserialization_time: u128,
     pub class_reading_time: u128,
     pub indexing_time: u128,
+    pub duplicate_classes: Vec<DuplicateClass>,
+    pub skipped_sources: Vec<SkippedSource>,
+}
+
+/// A class which was found more than once while building an index, e.g. because two jars on the
+/// classpath contain different versions of the same library
+#[derive(Debug)]
+pub struct DuplicateClass {
+    /// The name of the class including the package, e.g. `java/lang/String`
+    pub name: String,
+    /// The sources of all found versions of this class, in the order they were read. A source is
+    /// None if the class was created from raw class bytes.
+    pub sources: Vec<Option<String>>,
+    /// Whether the found versions declare fields or methods with different names, descriptors or
+    /// access flags
+    pub members_differ: bool,
+}
+
+impl DuplicateClass {
+    fn new(classes: &[ClassInfo]) -> Self {
+        let first = &classes[0];
+        let name = if first.package_name.is_empty() {
+            first.class_name.to_string()
+        } else {
+            format!("{}/{}", first.package_name, first.class_name)
+        };
+
+        Self {
+            name,
+            sources: classes
+                .iter()
+                .map(|c| c.source.as_deref().map(str::to_owned))
+                .collect(),
+            members_differ: classes[1..].iter().any(|c| !have_equal_members(first, c)),
+        }
+    }
+}
+
+/// A jar nested in another jar which could not be read. The classes of the outer jar are still
+/// indexed.
+#[derive(Debug)]
+pub struct SkippedSource {
+    /// The name of the nested jar, e.g. `outer.jar!/inner.jar`
+    pub name: String,
+    /// The reason why the nested jar could not be read
+    pub error: String,
+}
+
+fn have_equal_members(a: &ClassInfo, b: &ClassInfo) -> bool {
+    // Annotations, parameter names and code are not part of the compared declaration
+    let equal_fields = |f: &FieldInfo, other: &FieldInfo| {
+        f.field_name == other.field_name
+            && f.descriptor == other.descriptor
+            && f.access_flags == other.access_flags
+    };
+    let equal_methods = |m: &MethodInfo, other: &MethodInfo| {
+        m.method_name == other.method_name
+            && m.signature == other.signature
+            && m.access_flags == other.access_flags
+    };
+
+    // The order of members can change between compilations, therefore we can't compare them
+    // directly
+    a.fields.len() == b.fields.len()
+        && a.methods.len() == b.methods.len()
+        && a.fields
+            .iter()
+            .all(|f| b.fields.iter().any(|other| equal_fields(f, other)))
+        && a.methods
+            .iter()
+            .all(|m| b.methods.iter().any(|other| equal_methods(m, other)))
 }
 
 impl BuildTimeInfo {
@@ -287,6 +606,8 @@ impl BuildTimeInfo {
         self.deserialization_time += other.deserialization_time;
         self.class_reading_time += other.class_reading_time;
         self.indexing_time += other.indexing_time;
+        self.duplicate_classes.extend(other.duplicate_classes);
+        self.skipped_sources.extend(other.skipped_sources);
     }
 
     pub fn total_time_millis(&self) -> u128 {
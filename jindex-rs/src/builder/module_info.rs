@@ -0,0 +1,173 @@
+use anyhow::{anyhow, bail, Context};
+
+const TAG_UTF8: u8 = 1;
+const TAG_LONG: u8 = 5;
+const TAG_DOUBLE: u8 = 6;
+const TAG_MODULE: u8 = 19;
+
+/// Reads the module name from the `Module` attribute of a `module-info.class` file. Only the
+/// parts of the class file needed to find this attribute are parsed.
+pub fn read_module_name(data: &[u8]) -> anyhow::Result<&str> {
+    let mut reader = ClassReader { data, position: 0 };
+    if reader.read_u32()? != 0xCAFE_BABE {
+        bail!("Invalid class file magic number");
+    }
+    reader.skip(4)?;
+
+    // Stores the tag and the offset of the data of each constant pool entry
+    let constant_pool_count = reader.read_u16()? as usize;
+    let mut constant_pool = vec![(0u8, 0usize); constant_pool_count];
+    let mut index = 1;
+    while index < constant_pool_count {
+        let tag = reader.read_u8()?;
+        constant_pool[index] = (tag, reader.position);
+        let size = match tag {
+            TAG_UTF8 => reader.read_u16()? as usize,
+            7 | 8 | 16 | TAG_MODULE | 20 => 2,
+            15 => 3,
+            3 | 4 | 9 | 10 | 11 | 12 | 17 | 18 => 4,
+            TAG_LONG | TAG_DOUBLE => 8,
+            _ => bail!("Invalid constant pool tag {}", tag),
+        };
+        reader.skip(size)?;
+        // Long and double entries take up two slots
+        index += if tag == TAG_LONG || tag == TAG_DOUBLE {
+            2
+        } else {
+            1
+        };
+    }
+
+    let entry = |index: u16, expected_tag: u8| -> anyhow::Result<usize> {
+        match constant_pool.get(index as usize) {
+            Some(&(tag, offset)) if tag == expected_tag => Ok(offset),
+            _ => Err(anyhow!("Invalid constant pool index {}", index)),
+        }
+    };
+    let utf8 = |index: u16| -> anyhow::Result<&str> {
+        let mut reader = ClassReader {
+            data,
+            position: entry(index, TAG_UTF8)?,
+        };
+        let length = reader.read_u16()? as usize;
+        std::str::from_utf8(reader.read_bytes(length)?).context("Invalid UTF-8 string")
+    };
+
+    // Access flags, this class and super class
+    reader.skip(6)?;
+    let interfaces_count = reader.read_u16()? as usize;
+    reader.skip(interfaces_count * 2)?;
+    // Fields and methods
+    for _ in 0..2 {
+        for _ in 0..reader.read_u16()? {
+            reader.skip(6)?;
+            reader.skip_attributes()?;
+        }
+    }
+
+    for _ in 0..reader.read_u16()? {
+        let name_index = reader.read_u16()?;
+        let length = reader.read_u32()? as usize;
+        if utf8(name_index)? != "Module" {
+            reader.skip(length)?;
+            continue;
+        }
+
+        let module_index = reader.read_u16()?;
+        let mut module_reader = ClassReader {
+            data,
+            position: entry(module_index, TAG_MODULE)?,
+        };
+        return utf8(module_reader.read_u16()?);
+    }
+
+    bail!("Missing Module attribute")
+}
+
+struct ClassReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ClassReader<'a> {
+    fn read_bytes(&mut self, length: usize) -> anyhow::Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.position..self.position + length)
+            .ok_or_else(|| anyhow!("Truncated class file"))?;
+        self.position += length;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, length: usize) -> anyhow::Result<()> {
+        self.read_bytes(length).map(|_| ())
+    }
+
+    fn skip_attributes(&mut self) -> anyhow::Result<()> {
+        for _ in 0..self.read_u16()? {
+            self.skip(2)?;
+            let length = self.read_u32()? as usize;
+            self.skip(length)?;
+        }
+        Ok(())
+    }
+
+    fn read_u8(&mut self) -> anyhow::Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> anyhow::Result<u16> {
+        Ok(u16::from_be_bytes(self.read_bytes(2)?.try_into()?))
+    }
+
+    fn read_u32(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::from_be_bytes(self.read_bytes(4)?.try_into()?))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::read_module_name;
+
+    /// Creates a `module-info.class` file with the given module name and a `SourceFile` attribute
+    /// before the `Module` attribute
+    pub(crate) fn module_info_bytes(module_name: &str) -> Vec<u8> {
+        let mut data = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0, 0, 53];
+        let utf8 = |data: &mut Vec<u8>, value: &str| {
+            data.push(1);
+            data.extend_from_slice(&(value.len() as u16).to_be_bytes());
+            data.extend_from_slice(value.as_bytes());
+        };
+
+        // 1: Utf8 "module-info", 2: Class #1, 3: Long, 5: Utf8 name, 6: Module #5,
+        // 7: Utf8 "Module", 8: Utf8 "SourceFile"
+        data.extend_from_slice(&9u16.to_be_bytes());
+        utf8(&mut data, "module-info");
+        data.extend_from_slice(&[7, 0, 1]);
+        data.extend_from_slice(&[5, 0, 0, 0, 0, 0, 0, 0, 0]);
+        utf8(&mut data, module_name);
+        data.extend_from_slice(&[19, 0, 5]);
+        utf8(&mut data, "Module");
+        utf8(&mut data, "SourceFile");
+
+        // Access flags, this class, super class, interfaces, fields and methods
+        data.extend_from_slice(&[0x80, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0]);
+        data.extend_from_slice(&2u16.to_be_bytes());
+        data.extend_from_slice(&[0, 8, 0, 0, 0, 2, 0, 1]);
+        // Only the module name index of the Module attribute is read
+        data.extend_from_slice(&[0, 7, 0, 0, 0, 2, 0, 6]);
+        data
+    }
+
+    #[test]
+    fn test_read_module_name() {
+        assert_eq!(
+            "java.base",
+            read_module_name(&module_info_bytes("java.base")).unwrap()
+        );
+
+        let data = module_info_bytes("java.base");
+        assert!(read_module_name(&data[..data.len() - 1]).is_err());
+        assert!(read_module_name(b"not a class").is_err());
+    }
+}
@@ -1,26 +1,41 @@
-use crate::builder::{BuildTimeInfo, ClassIndexBuilder, ClassInfo, FieldInfo, MethodInfo};
+use crate::annotation::RawAnnotation;
+use crate::builder::fingerprint::{FingerprintData, SourceFingerprint};
+use crate::builder::jimage::JImage;
+use crate::builder::module_info::read_module_name;
+use crate::builder::{
+    BuildTimeInfo, ClassIndexBuilder, ClassInfo, DuplicateClass, FieldInfo, MethodInfo,
+    RecordComponentInfo, SkippedSource,
+};
 use crate::class_index::ClassIndex;
+use crate::constant_value::RawConstantValue;
+use crate::reference::RawReference;
 use crate::rsplit_once;
 use crate::signature::{
     InnerClassType, RawClassSignature, RawEnclosingTypeInfo, RawMethodSignature, RawSignatureType,
 };
 use anyhow::{anyhow, bail, Context};
-use ascii::{AsAsciiStr, AsciiChar, AsciiStr, AsciiString, IntoAsciiString};
-use cafebabe::attributes::{AttributeData, AttributeInfo, InnerClassEntry};
-use cafebabe::constant_pool::NameAndType;
-use cafebabe::{parse_class_with_options, MethodAccessFlags, ParseOptions};
+use cafebabe::attributes::{AttributeData, AttributeInfo, InnerClassEntry, RecordComponentEntry};
+use cafebabe::bytecode::Opcode;
+use cafebabe::constant_pool::{LiteralConstant, Loadable, NameAndType};
+use cafebabe::{
+    parse_class_with_options, ClassFile, FieldAccessFlags, MethodAccessFlags, ParseOptions,
+};
 use compact_str::{CompactString, ToCompactString};
 use rayon::prelude::*;
+use rustc_hash::{FxHashMap, FxHashSet};
+use speedy::{Readable, Writable};
 use std::borrow::Cow;
+use std::fs;
 use std::fs::File;
-use std::io::{Cursor, Read};
+use std::io::{Cursor, Read, Seek};
 use std::ops::BitOr;
 use std::path::Path;
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::Instant;
 use zip::ZipArchive;
 
-fn do_multi_threaded<I, F, O>(queue: Vec<I>, func: &F) -> anyhow::Result<Vec<O>>
+pub(super) fn do_multi_threaded<I, F, O>(queue: Vec<I>, func: &F) -> anyhow::Result<Vec<O>>
 where
     O: Send,
     F: (Fn(I) -> anyhow::Result<O>) + Sync,
@@ -32,9 +47,139 @@ where
         .collect::<anyhow::Result<Vec<O>>>()?)
 }
 
-fn process_jar_worker(file_name: String) -> anyhow::Result<Vec<ClassInfo>> {
+/// Prefixes of directories inside of fat jars and wars which contain the actual class files
+const NESTED_CLASSES_PREFIXES: [&str; 2] = ["BOOT-INF/classes/", "WEB-INF/classes/"];
+
+/// Prefixes of directories inside of fat jars and wars which contain their dependencies. Jars in
+/// other directories are not read.
+const NESTED_JAR_PREFIXES: [&str; 2] = ["BOOT-INF/lib/", "WEB-INF/lib/"];
+
+/// Directory inside of multi-release jars which contains the version specific class files
+const VERSIONED_ENTRIES_PREFIX: &str = "META-INF/versions/";
+
+/// Decides which class is kept if multiple classes with the same name are found
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Readable, Writable)]
+pub enum DuplicateClassPolicy {
+    /// Keep the class from the source which was read first
+    First,
+    /// Keep the class from the source which was read last
+    Last,
+    /// Keep the class with the highest class file version. If multiple classes have the same
+    /// version, the one which was read first is kept.
+    HighestClassFileVersion,
+}
+
+impl DuplicateClassPolicy {
+    /// Selects the class to keep from the given non-empty list, which is in reading order
+    fn select(self, mut classes: Vec<ClassInfo>) -> ClassInfo {
+        match self {
+            DuplicateClassPolicy::First => classes.swap_remove(0),
+            DuplicateClassPolicy::Last => classes.pop().unwrap(),
+            DuplicateClassPolicy::HighestClassFileVersion => classes
+                .into_iter()
+                .rev()
+                .max_by_key(|c| c.class_file_version)
+                .unwrap(),
+        }
+    }
+}
+
+impl Default for DuplicateClassPolicy {
+    fn default() -> Self {
+        DuplicateClassPolicy::First
+    }
+}
+
+/// Decides which classes are read and which data is recorded for them. The options are stored in
+/// the created index and reused when it is updated.
+#[derive(Clone, Debug, PartialEq, Readable, Writable)]
+pub struct ReadOptions {
+    max_nested_jar_depth: u8,
+    target_release: Option<u16>,
+    duplicate_class_policy: DuplicateClassPolicy,
+    index_references: bool,
+    index_string_literals: bool,
+    local_variable_parameter_names: bool,
+}
+
+impl ReadOptions {
+    pub fn new() -> Self {
+        Self {
+            max_nested_jar_depth: 2,
+            target_release: None,
+            duplicate_class_policy: DuplicateClassPolicy::default(),
+            index_references: false,
+            index_string_literals: false,
+            local_variable_parameter_names: false,
+        }
+    }
+
+    /// Sets whether the code of all methods is parsed to record which classes, fields and methods
+    /// they reference. This is required for finding usages, but makes reading classes slower and
+    /// the index larger.
+    pub fn with_references(mut self, enabled: bool) -> Self {
+        self.index_references = enabled;
+        self
+    }
+
+    /// Sets whether the string literals used by each class are recorded. This is required for
+    /// finding classes by string literal, but makes reading classes slower and the index larger.
+    pub fn with_string_literals(mut self, enabled: bool) -> Self {
+        self.index_string_literals = enabled;
+        self
+    }
+
+    /// Sets whether parameter names are read from the local variable table of a method's code if
+    /// the method has no MethodParameters attribute. The table is only present if the class was
+    /// compiled with debug information, and obfuscated classes can contain misleading names.
+    pub fn with_local_variable_parameter_names(mut self, enabled: bool) -> Self {
+        self.local_variable_parameter_names = enabled;
+        self
+    }
+
+    /// Sets which class is kept if multiple sources contain a class with the same name. All
+    /// duplicates are reported in [BuildTimeInfo::duplicate_classes] regardless of this setting.
+    pub fn with_duplicate_class_policy(mut self, policy: DuplicateClassPolicy) -> Self {
+        self.duplicate_class_policy = policy;
+        self
+    }
+
+    /// Sets the Java release used to select classes from multi-release jars. The highest version
+    /// of a class which is not newer than this release is indexed. If no release is set, the
+    /// highest available version is used.
+    pub fn with_target_release(mut self, release: u16) -> Self {
+        self.target_release = Some(release);
+        self
+    }
+
+    /// Sets how deep jars contained in other jars are read. Only jars in the `BOOT-INF/lib/` and
+    /// `WEB-INF/lib/` directories of fat jars and wars are read. A depth of 0 disables reading
+    /// nested jars completely.
+    pub fn with_max_nested_jar_depth(mut self, depth: u8) -> Self {
+        self.max_nested_jar_depth = depth;
+        self
+    }
+
+    pub(super) fn duplicate_class_policy(&self) -> DuplicateClassPolicy {
+        self.duplicate_class_policy
+    }
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The classes read from a single source, the fingerprint of that source and the nested jars which
+/// could not be read
+type SourceReadResult = (Vec<ClassInfo>, SourceFingerprint, Vec<SkippedSource>);
+
+fn process_jar_worker(
+    file_name: String,
+    options: &ReadOptions,
+) -> anyhow::Result<SourceReadResult> {
     let mut file_buf = Vec::new();
-    let mut output = Vec::new();
     let file_path = Path::new(&file_name)
         .canonicalize()
         .with_context(|| format!("Failed to canonicalize path {}", file_name))?;
@@ -42,23 +187,169 @@ fn process_jar_worker(file_name: String) -> anyhow::Result<Vec<ClassInfo>> {
         return Err(anyhow!("File {} does not exist", file_name));
     }
 
-    file_buf.clear();
     let mut file =
-        File::open(file_path).with_context(|| format!("Failed to open file {}", file_name))?;
+        File::open(&file_path).with_context(|| format!("Failed to open file {}", file_name))?;
+    let metadata = file.metadata()?;
     file.read_to_end(&mut file_buf)?;
 
-    let mut archive = ZipArchive::new(Cursor::new(&file_buf))
+    // JDK runtime images store all modules in a single jimage file called 'modules'
+    let mut skipped_sources = Vec::new();
+    let class_infos = if file_path.file_name().map_or(false, |n| n == "modules") {
+        process_jimage(&file_buf, &file_name, options)?
+    } else if file_path.extension().map_or(false, |e| e == "jmod") {
+        process_jmod(&file_buf, &file_path, &file_name, options)?
+    } else {
+        process_zip(
+            &file_buf,
+            &file_name,
+            "",
+            None,
+            0,
+            options,
+            &mut skipped_sources,
+        )?
+    };
+
+    let fingerprint_data = FingerprintData::of_file(&metadata, &file_buf);
+    Ok((
+        class_infos,
+        SourceFingerprint::new(file_name, fingerprint_data),
+        skipped_sources,
+    ))
+}
+
+fn process_jmod(
+    file_buf: &[u8],
+    file_path: &Path,
+    file_name: &str,
+    options: &ReadOptions,
+) -> anyhow::Result<Vec<ClassInfo>> {
+    // Jmod files are zip files with an additional 4 byte header
+    if file_buf.len() < 4 || &file_buf[..2] != b"JM" {
+        bail!("{} is not a jmod file", file_name);
+    }
+
+    // The file name of a jmod usually matches its module name, but only module-info.class is
+    // authoritative
+    let module_name = read_jmod_module_name(&file_buf[4..]).ok().or_else(|| {
+        file_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(|s| s.to_compact_string())
+    });
+
+    process_zip(
+        &file_buf[4..],
+        file_name,
+        "classes/",
+        module_name,
+        0,
+        options,
+        // Jmod files do not contain nested jars
+        &mut Vec::new(),
+    )
+}
+
+fn read_jmod_module_name(zip_buf: &[u8]) -> anyhow::Result<CompactString> {
+    let mut archive = ZipArchive::new(Cursor::new(zip_buf))?;
+    let mut entry = archive.by_name("classes/module-info.class")?;
+    let mut data = Vec::with_capacity(entry.size() as usize);
+    entry.read_to_end(&mut data)?;
+    Ok(read_module_name(&data)?.to_compact_string())
+}
+
+fn process_zip(
+    file_buf: &[u8],
+    file_name: &str,
+    entry_prefix: &str,
+    module_name: Option<CompactString>,
+    depth: u8,
+    options: &ReadOptions,
+    skipped_sources: &mut Vec<SkippedSource>,
+) -> anyhow::Result<Vec<ClassInfo>> {
+    let mut output = Vec::new();
+    let mut archive = ZipArchive::new(Cursor::new(file_buf))
         .with_context(|| format!("Failed to read zip file {}", file_name))?;
+    let multi_release = is_multi_release_jar(&mut archive);
 
+    // Maps the name of each class entry to its release version and its index in the archive. Only
+    // the highest applicable version of each class is read.
+    let mut class_entries = FxHashMap::<String, (u16, usize)>::default();
     for i in 0..archive.len() {
         let mut entry = archive.by_index(i)?;
-        if entry.is_dir()
-            || !entry.name().ends_with(".class")
-            || entry.name() == "module-info.class"
-        {
+        if entry.is_dir() || !entry.name().starts_with(entry_prefix) {
+            continue;
+        }
+
+        // Fat jars and wars contain their dependencies as nested jars
+        if is_nested_jar(entry.name()) {
+            if depth >= options.max_nested_jar_depth {
+                continue;
+            }
+
+            // A corrupt nested jar only skips that jar instead of failing the whole outer jar
+            let nested_file_name = format!("{}!/{}", file_name, entry.name());
+            let mut data = Vec::with_capacity(entry.size() as usize);
+            let nested_class_infos = entry
+                .read_to_end(&mut data)
+                .with_context(|| format!("Failed to read {}", nested_file_name))
+                .and_then(|_| {
+                    process_zip(
+                        &data,
+                        &nested_file_name,
+                        "",
+                        module_name.clone(),
+                        depth + 1,
+                        options,
+                        skipped_sources,
+                    )
+                });
+            match nested_class_infos {
+                Ok(class_infos) => output.extend(class_infos),
+                Err(error) => skipped_sources.push(SkippedSource {
+                    name: nested_file_name,
+                    error: format!("{:#}", error),
+                }),
+            }
+            continue;
+        }
+
+        let entry_name = strip_nested_classes_prefix(&entry.name()[entry_prefix.len()..]);
+        let (release, entry_name) = match split_versioned_entry_name(entry_name) {
+            Some((release, _))
+                if !multi_release
+                    || options
+                        .target_release
+                        .map_or(false, |target| release > target) =>
+            {
+                continue
+            }
+            Some(versioned_entry) => versioned_entry,
+            None => (0, entry_name),
+        };
+        if !entry_name.ends_with(".class") || entry_name == "module-info.class" {
             continue;
         }
 
+        match class_entries.get_mut(entry_name) {
+            Some(existing) if existing.0 < release => *existing = (release, i),
+            Some(_) => {}
+            None => {
+                class_entries.insert(entry_name.to_owned(), (release, i));
+            }
+        }
+    }
+
+    let mut class_entry_indices = class_entries
+        .into_values()
+        .map(|(_, index)| index)
+        .collect::<Vec<_>>();
+    class_entry_indices.sort_unstable();
+
+    let source: Arc<str> = Arc::from(file_name);
+
+    for i in class_entry_indices {
+        let mut entry = archive.by_index(i)?;
         let mut data = Vec::with_capacity(entry.size() as usize);
         entry
             .read_to_end(&mut data)
@@ -67,30 +358,202 @@ fn process_jar_worker(file_name: String) -> anyhow::Result<Vec<ClassInfo>> {
         // NOTE: While processing the class immediately makes this a bit slower, because the
         // workload is split less evenly (e.g. a single jar file has way more classes than a
         // different one), we get the benefit of using way less memory while indexing.
-        output.push(match process_class(&data) {
+        let mut class_info = match process_class(&data, options) {
             Ok(x) => x,
             Err(_) => continue,
-        });
+        };
+        class_info.module_name = module_name.clone();
+        class_info.source = Some(source.clone());
+        output.push(class_info);
     }
 
     Ok(output)
 }
 
+/// Checks if the manifest of the given jar contains `Multi-Release: true`
+fn is_multi_release_jar<R: Read + Seek>(archive: &mut ZipArchive<R>) -> bool {
+    let mut manifest = String::new();
+    let read_result = archive
+        .by_name("META-INF/MANIFEST.MF")
+        .map(|mut file| file.read_to_string(&mut manifest));
+    if !matches!(read_result, Ok(Ok(_))) {
+        return false;
+    }
+
+    manifest.lines().any(|line| {
+        line.split_once(':').map_or(false, |(key, value)| {
+            key.trim().eq_ignore_ascii_case("Multi-Release")
+                && value.trim().eq_ignore_ascii_case("true")
+        })
+    })
+}
+
+/// Splits `META-INF/versions/N/a/b/C.class` into `N` and `a/b/C.class`
+fn split_versioned_entry_name(entry_name: &str) -> Option<(u16, &str)> {
+    let (release, name) = entry_name
+        .strip_prefix(VERSIONED_ENTRIES_PREFIX)?
+        .split_once('/')?;
+    Some((release.parse().ok()?, name))
+}
+
+fn is_nested_jar(entry_name: &str) -> bool {
+    entry_name.ends_with(".jar")
+        && NESTED_JAR_PREFIXES
+            .iter()
+            .any(|prefix| entry_name.starts_with(prefix))
+}
+
+/// Returns the name of the given zip entry relative to the class path root of the archive
+fn strip_nested_classes_prefix(entry_name: &str) -> &str {
+    NESTED_CLASSES_PREFIXES
+        .iter()
+        .find_map(|prefix| entry_name.strip_prefix(prefix))
+        .unwrap_or(entry_name)
+}
+
+fn process_jimage(
+    file_buf: &[u8],
+    file_name: &str,
+    options: &ReadOptions,
+) -> anyhow::Result<Vec<ClassInfo>> {
+    let image = JImage::parse(file_buf)
+        .with_context(|| format!("Failed to read jimage file {}", file_name))?;
+
+    let resources = image
+        .resources()
+        .collect::<anyhow::Result<Vec<_>>>()
+        .with_context(|| format!("Failed to read jimage file {}", file_name))?;
+
+    let source: Arc<str> = Arc::from(file_name);
+    Ok(resources
+        .into_par_iter()
+        .filter(|r| r.extension == "class" && r.base != "module-info" && !r.module.is_empty())
+        // Compressed resources are only created when using jlink with '--compress'
+        .filter_map(|r| {
+            let mut class_info = process_class(r.data?, options).ok()?;
+            class_info.module_name = Some(r.module.to_compact_string());
+            class_info.source = Some(source.clone());
+            Some(class_info)
+        })
+        .collect())
+}
+
+pub(super) fn process_path_worker(
+    path_name: String,
+    options: &ReadOptions,
+) -> anyhow::Result<SourceReadResult> {
+    let path = Path::new(&path_name);
+    if path.is_dir() {
+        let (class_infos, fingerprint_data) =
+            process_directory(path, path, &Arc::from(path_name.as_str()), options)
+                .with_context(|| format!("Failed to read directory {}", path_name))?;
+        Ok((
+            class_infos,
+            SourceFingerprint::new(path_name, fingerprint_data),
+            Vec::new(),
+        ))
+    } else {
+        process_jar_worker(path_name, options)
+    }
+}
+
+/// Walks the given directory in parallel and processes all class files found in it
+fn process_directory(
+    root: &Path,
+    directory: &Path,
+    source: &Arc<str>,
+    options: &ReadOptions,
+) -> anyhow::Result<(Vec<ClassInfo>, FingerprintData)> {
+    let entries = fs::read_dir(directory)?.collect::<Result<Vec<_>, _>>()?;
+
+    Ok(entries
+        .into_par_iter()
+        .map(|entry| {
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                return process_directory(root, &path, source, options);
+            }
+
+            if path.extension().map_or(true, |e| e != "class") {
+                return Ok((Vec::new(), FingerprintData::default()));
+            }
+
+            let data =
+                fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+            let relative_path = path.strip_prefix(root)?;
+            let fingerprint_data =
+                FingerprintData::of_class_file(relative_path, &entry.metadata()?, &data);
+            if relative_path == Path::new("module-info.class") {
+                return Ok((Vec::new(), fingerprint_data));
+            }
+
+            let class_infos = process_class(&data, options).map_or_else(
+                |_| Vec::new(),
+                |mut c| {
+                    c.source = Some(source.clone());
+                    vec![c]
+                },
+            );
+            Ok((class_infos, fingerprint_data))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?
+        .into_iter()
+        .fold(
+            (Vec::new(), FingerprintData::default()),
+            |(mut all_class_infos, all_data), (class_infos, data)| {
+                all_class_infos.extend(class_infos);
+                (all_class_infos, all_data.combine(data))
+            },
+        ))
+}
+
 pub fn create_class_index_from_jars(
     jar_names: Vec<String>,
+    options: ReadOptions,
+) -> anyhow::Result<(BuildTimeInfo, ClassIndex)> {
+    create_class_index_from_sources(jar_names, &options, &|name| {
+        process_jar_worker(name, &options)
+    })
+}
+
+/// Like [create_class_index_from_jars], but also accepts directories containing class files
+pub fn create_class_index_from_paths(
+    path_names: Vec<String>,
+    options: ReadOptions,
 ) -> anyhow::Result<(BuildTimeInfo, ClassIndex)> {
+    create_class_index_from_sources(path_names, &options, &|name| {
+        process_path_worker(name, &options)
+    })
+}
+
+fn create_class_index_from_sources<F>(
+    source_names: Vec<String>,
+    options: &ReadOptions,
+    worker: &F,
+) -> anyhow::Result<(BuildTimeInfo, ClassIndex)>
+where
+    F: (Fn(String) -> anyhow::Result<SourceReadResult>) + Sync,
+{
     let now = Instant::now();
-    let class_info_list = do_multi_threaded(jar_names, &process_jar_worker)?
-        .into_iter()
-        .flatten()
-        .collect();
+    let mut class_info_list = Vec::new();
+    let mut fingerprints = Vec::new();
+    let mut skipped_sources = Vec::new();
+    for (class_infos, fingerprint, skipped) in do_multi_threaded(source_names, worker)? {
+        class_info_list.extend(class_infos);
+        fingerprints.push(fingerprint);
+        skipped_sources.extend(skipped);
+    }
 
     let mut info = BuildTimeInfo {
         class_reading_time: now.elapsed().as_millis(),
+        skipped_sources,
         ..Default::default()
     };
 
-    let (other_info, class_index) = create_class_index_from_infos(class_info_list)?;
+    let (other_info, mut class_index) =
+        create_class_index_from_infos(class_info_list, options.duplicate_class_policy)?;
+    class_index.set_source_fingerprints(fingerprints);
+    class_index.set_read_options(options.clone());
     info.merge(other_info);
     Ok((info, class_index))
 }
@@ -113,13 +576,23 @@ macro_rules! get_attribute_data {
 }
 
 fn process_class_bytes_worker(bytes_queue: Vec<u8>) -> anyhow::Result<ClassInfo> {
-    process_class(&bytes_queue)
+    process_class(&bytes_queue, &ReadOptions::default())
 }
 
-fn process_class(bytes: &[u8]) -> anyhow::Result<ClassInfo> {
-    let class_file = parse_class_with_options(bytes, ParseOptions::default().parse_bytecode(false))
-        .map_err(|parse_error| anyhow!("{}", parse_error))
-        .with_context(|| format!("Failed to parse class file {:?}", bytes))?;
+fn process_class(bytes: &[u8], options: &ReadOptions) -> anyhow::Result<ClassInfo> {
+    let class_file = parse_class_with_options(
+        bytes,
+        ParseOptions::default()
+            .parse_bytecode(options.index_references || options.index_string_literals),
+    )
+    .map_err(|parse_error| anyhow!("{}", parse_error))
+    .with_context(|| format!("Failed to parse class file {:?}", bytes))?;
+
+    let string_literals = if options.index_string_literals {
+        convert_string_literals(&class_file)
+    } else {
+        Vec::new()
+    };
 
     let ConvertedInnerClassInfo {
         package_name,
@@ -153,22 +626,51 @@ fn process_class(bytes: &[u8]) -> anyhow::Result<ClassInfo> {
     Ok(ClassInfo {
         package_name,
         class_name: full_class_name,
+        module_name: None,
+        source: None,
         class_name_start_index,
         access_flags: class_file
             .access_flags
             .bits()
             .bitor(inner_class_access_flags),
+        class_file_version: class_file.major_version,
         signature: parsed_signature,
         enclosing_type,
         member_classes,
+        annotations: convert_annotations(&class_file.attributes),
+        permitted_subclasses: class_file
+            .attributes
+            .iter()
+            .find_map(|a| match &a.data {
+                AttributeData::PermittedSubclasses(vec) => {
+                    Some(vec.iter().map(|c| c.to_compact_string()).collect())
+                }
+                _ => None,
+            })
+            .unwrap_or_default(),
+        nest_host: class_file.attributes.iter().find_map(|a| match &a.data {
+            AttributeData::NestHost(host) => Some(host.to_compact_string()),
+            _ => None,
+        }),
+        nest_members: class_file
+            .attributes
+            .iter()
+            .find_map(|a| match &a.data {
+                AttributeData::NestMembers(vec) => {
+                    Some(vec.iter().map(|c| c.to_compact_string()).collect())
+                }
+                _ => None,
+            })
+            .unwrap_or_default(),
+        record_components: class_file.attributes.iter().find_map(|a| match &a.data {
+            AttributeData::Record(components) => Some(convert_record_components(components)),
+            _ => None,
+        }),
         fields: class_file
             .fields
             .into_iter()
             .filter_map(|f| {
-                let name = match f.name.as_ascii_str() {
-                    Ok(x) => x.to_compact_string(),
-                    Err(_) => return None,
-                };
+                let name = f.name.to_compact_string();
 
                 Some(get_attribute_data!(
                     &f.attributes,
@@ -183,6 +685,19 @@ fn process_class(bytes: &[u8]) -> anyhow::Result<ClassInfo> {
                             field_name: name,
                             descriptor: signature_type,
                             access_flags: f.access_flags,
+                            annotations: convert_annotations(&f.attributes),
+                            constant_value: f
+                                .access_flags
+                                .contains(FieldAccessFlags::STATIC)
+                                .then(|| {
+                                    f.attributes.iter().find_map(|a| match &a.data {
+                                        AttributeData::ConstantValue(constant) => {
+                                            RawConstantValue::from_class_file(constant)
+                                        }
+                                        _ => None,
+                                    })
+                                })
+                                .flatten(),
                         })
                 })
             })
@@ -191,10 +706,7 @@ fn process_class(bytes: &[u8]) -> anyhow::Result<ClassInfo> {
             .methods
             .into_iter()
             .filter_map(|m| {
-                let name = match m.name.as_ascii_str() {
-                    Ok(x) => x.to_compact_string(),
-                    Err(_) => return None,
-                };
+                let name = m.name.to_compact_string();
 
                 if m.access_flags.contains(MethodAccessFlags::SYNTHETIC) {
                     return None;
@@ -220,10 +732,24 @@ fn process_class(bytes: &[u8]) -> anyhow::Result<ClassInfo> {
                         method_name: name,
                         signature: signature_type,
                         access_flags: m.access_flags,
+                        annotations: convert_annotations(&m.attributes),
+                        parameter_annotations: convert_parameter_annotations(&m.attributes),
+                        parameter_names: convert_parameter_names(
+                            m.access_flags.contains(MethodAccessFlags::STATIC),
+                            &m.descriptor,
+                            &m.attributes,
+                            options.local_variable_parameter_names,
+                        ),
+                        references: if options.index_references {
+                            convert_references(&m.attributes)
+                        } else {
+                            Vec::new()
+                        },
                     })
                 })
             })
             .collect(),
+        string_literals,
     })
 }
 
@@ -233,25 +759,36 @@ pub fn create_class_index_from_bytes(
     let class_info_list: Vec<ClassInfo> =
         do_multi_threaded(class_bytes, &process_class_bytes_worker)?;
 
-    create_class_index_from_infos(class_info_list)
+    create_class_index_from_infos(class_info_list, DuplicateClassPolicy::default())
 }
 
-fn create_class_index_from_infos(
+pub(crate) fn create_class_index_from_infos(
     mut class_info_list: Vec<ClassInfo>,
+    duplicate_class_policy: DuplicateClassPolicy,
 ) -> anyhow::Result<(BuildTimeInfo, ClassIndex)> {
     let now = Instant::now();
 
-    //Removes duplicate classes
-    class_info_list.par_sort_unstable_by(|a, b| {
+    // Collect the sources before sorting to keep them in the order they were read in
+    let mut sources_set = FxHashSet::default();
+    let sources = class_info_list
+        .iter()
+        .filter_map(|class_info| class_info.source.as_deref())
+        .filter(|source| sources_set.insert(*source))
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+
+    // The sort is stable, so duplicate classes stay in the order they were read in
+    class_info_list.par_sort_by(|a, b| {
         a.class_name
             .cmp(&b.class_name)
             .then_with(|| a.package_name.cmp(&b.package_name))
     });
-    class_info_list
-        .dedup_by(|a, b| a.class_name.eq(&b.class_name) && a.package_name.eq(&b.package_name));
+    let (class_info_list, duplicate_classes) =
+        remove_duplicate_classes(class_info_list, duplicate_class_policy);
 
     let mut build_time_info = BuildTimeInfo {
         class_reading_time: now.elapsed().as_millis(),
+        duplicate_classes,
         ..Default::default()
     };
 
@@ -259,12 +796,301 @@ fn create_class_index_from_infos(
 
     let (other_info, class_index) = ClassIndexBuilder::default()
         .with_expected_method_count(method_count)
-        .build(class_info_list)?;
+        .with_sources(sources)
+        .build(class_info_list, &build_time_info.duplicate_classes)?;
 
     build_time_info.merge(other_info);
     Ok((build_time_info, class_index))
 }
 
+/// Removes all but one class of each group of classes with the same name from the given sorted
+/// list. Returns the remaining classes and a report for each removed group.
+fn remove_duplicate_classes(
+    class_info_list: Vec<ClassInfo>,
+    policy: DuplicateClassPolicy,
+) -> (Vec<ClassInfo>, Vec<DuplicateClass>) {
+    let is_same_class = |a: &ClassInfo, b: &ClassInfo| {
+        a.class_name == b.class_name && a.package_name == b.package_name
+    };
+
+    let mut output = Vec::with_capacity(class_info_list.len());
+    let mut duplicate_classes = Vec::new();
+    let mut iter = class_info_list.into_iter().peekable();
+    while let Some(class_info) = iter.next() {
+        if !iter
+            .peek()
+            .map_or(false, |next| is_same_class(&class_info, next))
+        {
+            output.push(class_info);
+            continue;
+        }
+
+        let mut group = vec![class_info];
+        while let Some(next) = iter.next_if(|next| is_same_class(&group[0], next)) {
+            group.push(next);
+        }
+
+        duplicate_classes.push(DuplicateClass::new(&group));
+        output.push(policy.select(group));
+    }
+
+    (output, duplicate_classes)
+}
+
+fn convert_record_components(components: &[RecordComponentEntry]) -> Vec<RecordComponentInfo> {
+    components
+        .iter()
+        .filter_map(|c| {
+            let signature = c
+                .attributes
+                .iter()
+                .find_map(|a| match &a.data {
+                    AttributeData::Signature(s) => Some(s),
+                    _ => None,
+                })
+                .unwrap_or(&c.descriptor);
+
+            RawSignatureType::from_str(signature)
+                .ok()
+                .map(|signature| RecordComponentInfo {
+                    name: c.name.to_compact_string(),
+                    signature,
+                })
+        })
+        .collect()
+}
+
+/// Collects the visible and invisible annotations from the given attributes
+fn convert_annotations(attributes: &[AttributeInfo]) -> Vec<RawAnnotation> {
+    attributes
+        .iter()
+        .flat_map(|a| match &a.data {
+            AttributeData::RuntimeVisibleAnnotations(annotations) => annotations
+                .iter()
+                .filter_map(|a| RawAnnotation::from_class_file(a, true))
+                .collect(),
+            AttributeData::RuntimeInvisibleAnnotations(annotations) => annotations
+                .iter()
+                .filter_map(|a| RawAnnotation::from_class_file(a, false))
+                .collect(),
+            _ => Vec::new(),
+        })
+        .collect()
+}
+
+/// Collects the visible and invisible annotations of each method parameter
+fn convert_parameter_annotations(attributes: &[AttributeInfo]) -> Vec<Vec<RawAnnotation>> {
+    let mut result: Vec<Vec<RawAnnotation>> = Vec::new();
+    for attribute in attributes {
+        let (parameters, visible) = match &attribute.data {
+            AttributeData::RuntimeVisibleParameterAnnotations(p) => (p, true),
+            AttributeData::RuntimeInvisibleParameterAnnotations(p) => (p, false),
+            _ => continue,
+        };
+
+        if result.len() < parameters.len() {
+            result.resize_with(parameters.len(), Vec::new);
+        }
+        for (index, parameter) in parameters.iter().enumerate() {
+            result[index].extend(
+                parameter
+                    .annotations
+                    .iter()
+                    .filter_map(|a| RawAnnotation::from_class_file(a, visible)),
+            );
+        }
+    }
+
+    result
+}
+
+/// Collects the parameter names from the MethodParameters attribute, or from the local variable
+/// table of the method's code if there is no such attribute and `use_local_variables` is set. The
+/// names belong to the parameters of the descriptor, which can include synthetic parameters which
+/// are not part of the signature.
+fn convert_parameter_names(
+    is_static: bool,
+    descriptor: &str,
+    attributes: &[AttributeInfo],
+    use_local_variables: bool,
+) -> Vec<Option<CompactString>> {
+    if let Some(parameters) = attributes.iter().find_map(|a| match &a.data {
+        AttributeData::MethodParameters(vec) => Some(vec),
+        _ => None,
+    }) {
+        return parameters
+            .iter()
+            .map(|p| p.name.as_ref().map(|name| name.to_compact_string()))
+            .collect();
+    }
+    if !use_local_variables {
+        return Vec::new();
+    }
+
+    let local_variables = attributes
+        .iter()
+        .find_map(|a| match &a.data {
+            AttributeData::Code(code) => Some(code),
+            _ => None,
+        })
+        .and_then(|code| {
+            code.attributes.iter().find_map(|a| match &a.data {
+                AttributeData::LocalVariableTable(vec) => Some(vec),
+                _ => None,
+            })
+        });
+    let local_variables = match local_variables {
+        Some(vec) => vec,
+        None => return Vec::new(),
+    };
+
+    // Parameters are the first local variables, after `this` for instance methods
+    let mut slot = if is_static { 0 } else { 1 };
+    let names = parameter_slot_sizes(descriptor)
+        .into_iter()
+        .map(|size| {
+            let name = local_variables
+                .iter()
+                .find(|v| v.index == slot && v.start_pc == 0)
+                .map(|v| v.name.to_compact_string());
+            slot += size;
+            name
+        })
+        .collect::<Vec<_>>();
+
+    if names.iter().all(Option::is_none) {
+        return Vec::new();
+    }
+    names
+}
+
+/// Collects the classes, fields and methods referenced by the code of a method. Empty if the
+/// bytecode was not parsed.
+fn convert_references(attributes: &[AttributeInfo]) -> Vec<RawReference> {
+    let bytecode = attributes.iter().find_map(|a| match &a.data {
+        AttributeData::Code(code) => code.bytecode.as_ref(),
+        _ => None,
+    });
+    let bytecode = match bytecode {
+        Some(bytecode) => bytecode,
+        None => return Vec::new(),
+    };
+
+    bytecode
+        .opcodes
+        .iter()
+        .filter_map(|(_, opcode)| match opcode {
+            Opcode::Invokeinterface(member, _)
+            | Opcode::Invokespecial(member)
+            | Opcode::Invokestatic(member)
+            | Opcode::Invokevirtual(member) => {
+                RawMethodSignature::from_data(&member.name_and_type.descriptor, &|| None)
+                    .ok()
+                    .map(|signature| RawReference::Method {
+                        class_name: member.class_name.to_compact_string(),
+                        method_name: member.name_and_type.name.to_compact_string(),
+                        signature,
+                    })
+            }
+            Opcode::Getfield(member) | Opcode::Getstatic(member) => Some(RawReference::FieldRead {
+                class_name: member.class_name.to_compact_string(),
+                field_name: member.name_and_type.name.to_compact_string(),
+            }),
+            Opcode::Putfield(member) | Opcode::Putstatic(member) => {
+                Some(RawReference::FieldWrite {
+                    class_name: member.class_name.to_compact_string(),
+                    field_name: member.name_and_type.name.to_compact_string(),
+                })
+            }
+            Opcode::New(class_name) => Some(RawReference::New(class_name.to_compact_string())),
+            Opcode::Checkcast(class_name) => {
+                element_class_name(class_name).map(RawReference::CheckCast)
+            }
+            Opcode::Instanceof(class_name) => {
+                element_class_name(class_name).map(RawReference::InstanceOf)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Collects the distinct strings loaded by the code of all methods, including synthetic ones, and
+/// the constant values of all fields. Empty if the bytecode was not parsed.
+fn convert_string_literals(class_file: &ClassFile) -> Vec<CompactString> {
+    let constant_values = class_file
+        .fields
+        .iter()
+        .flat_map(|f| f.attributes.iter())
+        .filter_map(|a| match &a.data {
+            AttributeData::ConstantValue(LiteralConstant::String(s)) => Some(s),
+            _ => None,
+        });
+    let loaded_strings = class_file
+        .methods
+        .iter()
+        .flat_map(|m| m.attributes.iter())
+        .filter_map(|a| match &a.data {
+            AttributeData::Code(code) => code.bytecode.as_ref(),
+            _ => None,
+        })
+        .flat_map(|bytecode| bytecode.opcodes.iter())
+        .filter_map(|(_, opcode)| match opcode {
+            Opcode::Ldc(Loadable::LiteralConstant(LiteralConstant::String(s)))
+            | Opcode::LdcW(Loadable::LiteralConstant(LiteralConstant::String(s))) => Some(s),
+            _ => None,
+        });
+
+    let mut literals = constant_values
+        .chain(loaded_strings)
+        .map(|s| s.to_compact_string())
+        .collect::<Vec<_>>();
+    literals.sort_unstable();
+    literals.dedup();
+    literals
+}
+
+/// Returns the element class of an array descriptor like `[[Ljava/lang/String;`, or the given name
+/// if it is not an array. Returns None for arrays of primitives.
+fn element_class_name(name: &str) -> Option<CompactString> {
+    if !name.starts_with('[') {
+        return Some(name.to_compact_string());
+    }
+
+    name.trim_start_matches('[')
+        .strip_prefix('L')
+        .and_then(|n| n.strip_suffix(';'))
+        .map(|n| n.to_compact_string())
+}
+
+/// Returns the number of local variable slots used by each parameter of a method descriptor
+fn parameter_slot_sizes(descriptor: &str) -> Vec<u16> {
+    let mut sizes = Vec::new();
+    let mut bytes = descriptor.bytes().skip(1);
+    while let Some(b) = bytes.next() {
+        match b {
+            b')' => break,
+            b'J' | b'D' => sizes.push(2),
+            b'[' => {
+                let mut element = b'[';
+                while element == b'[' {
+                    element = bytes.next().unwrap_or(b')');
+                }
+                if element == b'L' {
+                    bytes.by_ref().find(|b| *b == b';');
+                }
+                sizes.push(1);
+            }
+            b'L' => {
+                bytes.by_ref().find(|b| *b == b';');
+                sizes.push(1);
+            }
+            _ => sizes.push(1),
+        }
+    }
+
+    sizes
+}
+
 struct ConvertedInnerClassInfo {
     package_name: CompactString,
     full_class_name: CompactString,
@@ -279,8 +1105,8 @@ fn convert_enclosing_type_and_inner_classes(
     enclosing_method_data: Option<(&Cow<str>, &Option<NameAndType>)>,
     inner_class_data: Option<&Vec<InnerClassEntry>>,
 ) -> anyhow::Result<ConvertedInnerClassInfo> {
-    let this_name = this_name.as_ascii_str()?;
-    let (package_name, class_name) = rsplit_once(this_name, AsciiChar::Slash);
+    let this_name = this_name.as_ref();
+    let (package_name, class_name) = rsplit_once(this_name, '/');
     let mut class_name_start_index = 0;
     let mut access_flags = 0;
 
@@ -312,14 +1138,14 @@ fn convert_enclosing_type_and_inner_classes(
             if let Some((class_name, method_data)) = enclosing_method_data {
                 let (method_name, method_descriptor) = match method_data {
                     Some(NameAndType { name, descriptor }) => (
-                        Some(name.as_ascii_str()?.to_compact_string()),
+                        Some(name.to_compact_string()),
                         Some(RawMethodSignature::from_data(descriptor, &|| Option::None)?),
                     ),
                     None => (None, None),
                 };
 
                 enclosing_type_info = Some(RawEnclosingTypeInfo::new(
-                    Some(class_name.as_ascii_str()?.to_compact_string()),
+                    Some(class_name.to_compact_string()),
                     inner_class_type,
                     method_name,
                     method_descriptor,
@@ -350,10 +1176,8 @@ fn convert_enclosing_type_and_inner_classes(
                         || e.1.outer_class_info.as_ref().unwrap().as_ref() != this_name
                     {
                         None
-                    } else if let Ok(name) = e.1.inner_class_info.as_ascii_str() {
-                        Some(name.to_compact_string())
                     } else {
-                        None
+                        Some(e.1.inner_class_info.to_compact_string())
                     }
                 })
                 .collect(),
@@ -373,22 +1197,18 @@ fn convert_enclosing_type_and_inner_classes(
 /// Returns (0) the full outer class name including the package and (1) the index into the original
 /// class name from where the inner class name starts
 fn extract_outer_and_inner_name(
-    original_class_name: &AsciiStr,
+    original_class_name: &str,
     e: &InnerClassEntry,
 ) -> anyhow::Result<(CompactString, usize)> {
     e.inner_name
         .as_ref()
         .filter(|n| !n.is_empty())
         .filter(|_| e.outer_class_info.is_some())
-        .and_then(|n| {
-            if let Ok(name) = e.outer_class_info.as_ref().unwrap().as_ascii_str() {
-                Some((
-                    name.to_compact_string(),
-                    original_class_name.len() - n.len(),
-                ))
-            } else {
-                None
-            }
+        .map(|n| {
+            (
+                e.outer_class_info.as_ref().unwrap().to_compact_string(),
+                original_class_name.len() - n.len(),
+            )
         })
         .or_else(|| {
             //If we don't have an inner name, we usually have an anonymous class like
@@ -397,7 +1217,7 @@ fn extract_outer_and_inner_name(
                 match &e.outer_class_info {
                     //There might be an outer name which we can use to extract the inner name
                     Some(outer_name) => (
-                        outer_name.as_ascii_str()?.to_compact_string(),
+                        outer_name.to_compact_string(),
                         original_class_name.len()
                             - (e.inner_class_info.len() - (outer_name.len() + 1)),
                     ),
@@ -408,9 +1228,7 @@ fn extract_outer_and_inner_name(
                             .rfind('$')
                             .ok_or_else(|| anyhow!("No '$' found"))?;
                         (
-                            e.inner_class_info[..index]
-                                .as_ascii_str()?
-                                .to_compact_string(),
+                            CompactString::new(&e.inner_class_info[..index]),
                             original_class_name.len() - (e.inner_class_info.len() - (index + 1)),
                         )
                     }
@@ -437,19 +1255,11 @@ fn parse_class_signature(
             RawClassSignature::new(
                 super_class
                     .filter(|s| s != "java/lang/Object")
-                    .and_then(|s| {
-                        s.as_ascii_str()
-                            .ok()
-                            .map(|s| RawSignatureType::Object(s.to_compact_string()))
-                    }),
+                    .map(|s| RawSignatureType::Object(s.to_compact_string())),
                 Some(
                     interfaces
                         .into_iter()
-                        .filter_map(|s| {
-                            s.as_ascii_str()
-                                .ok()
-                                .map(|s| RawSignatureType::Object(s.to_compact_string()))
-                        })
+                        .map(|s| RawSignatureType::Object(s.to_compact_string()))
                         .collect::<Vec<_>>(),
                 )
                 .filter(|v| !v.is_empty()),
@@ -457,3 +1267,359 @@ fn parse_class_signature(
         },
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        convert_parameter_names, create_class_index_from_infos, parameter_slot_sizes, process_jmod,
+        process_path_worker, process_zip, DuplicateClassPolicy, ReadOptions,
+    };
+    use crate::builder::module_info::tests::module_info_bytes;
+    use crate::builder::ClassInfo;
+    use crate::test::{class_bytes, class_info, temp_dir, zip_bytes};
+    use cafebabe::attributes::{
+        AttributeData, AttributeInfo, CodeData, LocalVariableEntry, MethodParameterAccessFlags,
+        MethodParameterEntry,
+    };
+    use cafebabe::MethodAccessFlags;
+    use std::fs;
+    use std::path::Path;
+
+    fn class_names(class_infos: &[ClassInfo]) -> Vec<String> {
+        let mut names = class_infos
+            .iter()
+            .map(|c| format!("{}/{}", c.package_name, c.class_name))
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_nested_jars() {
+        let innermost = zip_bytes(&[("c/C.class", &class_bytes("c/C", 52, &[]))]);
+        let inner = zip_bytes(&[
+            ("b/B.class", &class_bytes("b/B", 52, &[])),
+            ("WEB-INF/lib/innermost.jar", &innermost),
+        ]);
+        let outer = zip_bytes(&[
+            ("a/A.class", &class_bytes("a/A", 52, &[])),
+            ("BOOT-INF/lib/inner.jar", &inner),
+            ("BOOT-INF/lib/broken.jar", b"not a jar"),
+            ("lib/other.jar", &innermost),
+        ]);
+
+        let mut skipped = Vec::new();
+        let class_infos = process_zip(
+            &outer,
+            "outer.jar",
+            "",
+            None,
+            0,
+            &ReadOptions::default(),
+            &mut skipped,
+        )
+        .unwrap();
+        assert_eq!(class_names(&class_infos), ["a/A", "b/B", "c/C"]);
+        let b = class_infos.iter().find(|c| c.class_name == "B").unwrap();
+        assert_eq!(
+            b.source.as_deref(),
+            Some("outer.jar!/BOOT-INF/lib/inner.jar")
+        );
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].name, "outer.jar!/BOOT-INF/lib/broken.jar");
+        assert!(skipped[0]
+            .error
+            .contains("outer.jar!/BOOT-INF/lib/broken.jar"));
+
+        let options = ReadOptions::default().with_max_nested_jar_depth(1);
+        let class_infos =
+            process_zip(&outer, "outer.jar", "", None, 0, &options, &mut Vec::new()).unwrap();
+        assert_eq!(class_names(&class_infos), ["a/A", "b/B"]);
+
+        let options = ReadOptions::default().with_max_nested_jar_depth(0);
+        let mut skipped = Vec::new();
+        let class_infos =
+            process_zip(&outer, "outer.jar", "", None, 0, &options, &mut skipped).unwrap();
+        assert_eq!(class_names(&class_infos), ["a/A"]);
+        assert!(skipped.is_empty());
+    }
+
+    #[test]
+    fn test_jmod_module_name() {
+        let module_name = |entries: &[(&str, &[u8])]| {
+            let mut data = b"JM\x01\x00".to_vec();
+            data.extend(zip_bytes(entries));
+            let class_infos = process_jmod(
+                &data,
+                Path::new("/jmods/file.name.jmod"),
+                "file.name.jmod",
+                &ReadOptions::default(),
+            )
+            .unwrap();
+            assert_eq!(class_names(&class_infos), ["a/A"]);
+            class_infos[0].module_name.clone().unwrap()
+        };
+
+        let class = class_bytes("a/A", 52, &[]);
+        let module_info = module_info_bytes("module.name");
+        assert_eq!(
+            "module.name",
+            module_name(&[
+                ("classes/a/A.class", &class),
+                ("classes/module-info.class", &module_info),
+            ])
+        );
+        // The file name is only used if module-info.class is missing or can't be read
+        assert_eq!(
+            "file.name",
+            module_name(&[
+                ("classes/a/A.class", &class),
+                ("classes/module-info.class", b"not a class"),
+            ])
+        );
+        assert_eq!("file.name", module_name(&[("classes/a/A.class", &class)]));
+    }
+
+    #[test]
+    fn test_duplicate_members_differ() {
+        let members_differ = |other: ClassInfo| {
+            let class_infos = vec![class_info("a/A", "Ljava/lang/Object;", "(I)V"), other];
+            let (info, _) =
+                create_class_index_from_infos(class_infos, DuplicateClassPolicy::First).unwrap();
+            info.duplicate_classes[0].members_differ
+        };
+
+        // Only the names, descriptors and access flags of members are compared
+        let mut other = class_info("a/A", "Ljava/lang/Object;", "(I)V");
+        other.methods[0].parameter_names = vec![Some("value".into())];
+        assert!(!members_differ(other));
+
+        let mut other = class_info("a/A", "Ljava/lang/Object;", "(I)V");
+        other.methods[0].access_flags = MethodAccessFlags::PRIVATE;
+        assert!(members_differ(other));
+        assert!(members_differ(class_info(
+            "a/A",
+            "Ljava/lang/Object;",
+            "(J)V"
+        )));
+    }
+
+    #[test]
+    fn test_multi_release_jar() {
+        let versions = [
+            ("a/A.class", class_bytes("a/A", 52, &["base"])),
+            (
+                "META-INF/versions/11/a/A.class",
+                class_bytes("a/A", 55, &["v11"]),
+            ),
+            (
+                "META-INF/versions/17/a/A.class",
+                class_bytes("a/A", 61, &["v17"]),
+            ),
+            (
+                "META-INF/versions/17/a/B.class",
+                class_bytes("a/B", 61, &["v17"]),
+            ),
+        ];
+        let jar = |manifest: &'static [u8]| {
+            let mut entries = vec![("META-INF/MANIFEST.MF", manifest)];
+            entries.extend(
+                versions
+                    .iter()
+                    .map(|(name, bytes)| (*name, bytes.as_slice())),
+            );
+            zip_bytes(&entries)
+        };
+        // Returns the name of each read class with the name of its only field
+        let read = |jar: &[u8], options: &ReadOptions| {
+            let mut class_infos =
+                process_zip(jar, "a.jar", "", None, 0, options, &mut Vec::new()).unwrap();
+            class_infos.sort_by(|a, b| a.class_name.cmp(&b.class_name));
+            class_infos
+                .iter()
+                .map(|c| format!("{}:{}", c.class_name, c.fields[0].field_name))
+                .collect::<Vec<_>>()
+        };
+
+        let multi_release = jar(b"Manifest-Version: 1.0\r\nMulti-Release: true\r\n");
+        assert_eq!(
+            read(&multi_release, &ReadOptions::default()),
+            ["A:v17", "B:v17"]
+        );
+        let options = ReadOptions::default().with_target_release(11);
+        assert_eq!(read(&multi_release, &options), ["A:v11"]);
+        let options = ReadOptions::default().with_target_release(16);
+        assert_eq!(read(&multi_release, &options), ["A:v11"]);
+        let options = ReadOptions::default().with_target_release(8);
+        assert_eq!(read(&multi_release, &options), ["A:base"]);
+
+        // Versioned entries are ignored if the manifest does not mark the jar as multi-release
+        let single_release = jar(b"Manifest-Version: 1.0\r\n");
+        assert_eq!(read(&single_release, &ReadOptions::default()), ["A:base"]);
+    }
+
+    #[test]
+    fn test_duplicate_class_policy() {
+        let read_jar = |name: &str, major_version: u16, field: &str| {
+            let jar = zip_bytes(&[("a/A.class", &class_bytes("a/A", major_version, &[field]))]);
+            process_zip(
+                &jar,
+                name,
+                "",
+                None,
+                0,
+                &ReadOptions::default(),
+                &mut Vec::new(),
+            )
+            .unwrap()
+        };
+        let kept_field = |policy: DuplicateClassPolicy| {
+            let class_infos = [
+                read_jar("first.jar", 52, "first"),
+                read_jar("newest.jar", 61, "newest"),
+                read_jar("also_newest.jar", 61, "also_newest"),
+                read_jar("last.jar", 55, "last"),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            let (info, class_index) = create_class_index_from_infos(class_infos, policy).unwrap();
+
+            // All versions are reported regardless of the policy
+            assert_eq!(info.duplicate_classes.len(), 1);
+            assert_eq!(info.duplicate_classes[0].name, "a/A");
+            assert_eq!(
+                info.duplicate_classes[0].sources,
+                ["first.jar", "newest.jar", "also_newest.jar", "last.jar"]
+                    .map(|s| Some(s.to_owned()))
+            );
+            assert!(info.duplicate_classes[0].members_differ);
+
+            let class = class_index.find_class("a", "A").unwrap();
+            class.fields()[0]
+                .field_name(class_index.constant_pool())
+                .to_owned()
+        };
+
+        assert_eq!(kept_field(DuplicateClassPolicy::First), "first");
+        assert_eq!(kept_field(DuplicateClassPolicy::Last), "last");
+        assert_eq!(
+            kept_field(DuplicateClassPolicy::HighestClassFileVersion),
+            "newest"
+        );
+    }
+
+    #[test]
+    fn test_read_directory() {
+        let dir = temp_dir("read_directory");
+        fs::create_dir_all(dir.join("a/b/c")).unwrap();
+        fs::write(dir.join("a/A.class"), class_bytes("a/A", 52, &[])).unwrap();
+        fs::write(dir.join("a/b/c/C.class"), class_bytes("a/b/c/C", 52, &[])).unwrap();
+        fs::write(dir.join("module-info.class"), b"not a class").unwrap();
+        fs::write(dir.join("a/Broken.class"), b"not a class").unwrap();
+        fs::write(dir.join("a/b/readme.txt"), b"text").unwrap();
+        let path = dir.to_str().unwrap().to_string();
+
+        let (class_infos, fingerprint, skipped) =
+            process_path_worker(path.clone(), &ReadOptions::default()).unwrap();
+        assert_eq!(class_names(&class_infos), ["a/A", "a/b/c/C"]);
+        assert!(class_infos
+            .iter()
+            .all(|c| c.source.as_deref() == Some(path.as_str())));
+        assert_eq!(fingerprint.path(), path);
+        assert!(fingerprint.is_up_to_date());
+        assert!(skipped.is_empty());
+
+        fs::write(dir.join("a/b/B.class"), class_bytes("a/b/B", 52, &[])).unwrap();
+        assert!(!fingerprint.is_up_to_date());
+        let (class_infos, _, _) = process_path_worker(path, &ReadOptions::default()).unwrap();
+        assert_eq!(class_names(&class_infos), ["a/A", "a/b/B", "a/b/c/C"]);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_parameter_slot_sizes() {
+        assert_eq!(parameter_slot_sizes("()V"), Vec::<u16>::new());
+        assert_eq!(parameter_slot_sizes("(IJZD)V"), [1, 2, 1, 2]);
+        assert_eq!(
+            parameter_slot_sizes("(Ljava/lang/String;J[J[[Ljava/lang/Long;D)V"),
+            [1, 2, 1, 1, 2]
+        );
+    }
+
+    #[test]
+    fn test_parameter_names() {
+        let attribute = |data: AttributeData<'static>| AttributeInfo {
+            name: "".into(),
+            data,
+        };
+        let local_variable = |name: &'static str, index: u16, start_pc: u16| LocalVariableEntry {
+            start_pc,
+            length: 10,
+            name: name.into(),
+            descriptor: "I".into(),
+            index,
+        };
+        let code = |local_variables: Vec<LocalVariableEntry<'static>>| {
+            attribute(AttributeData::Code(CodeData {
+                max_stack: 0,
+                max_locals: 0,
+                code: &[],
+                bytecode: None,
+                exception_table: Vec::new(),
+                attributes: vec![attribute(AttributeData::LocalVariableTable(
+                    local_variables,
+                ))],
+            }))
+        };
+        let names =
+            |names: &[Option<&str>]| names.iter().map(|n| n.map(Into::into)).collect::<Vec<_>>();
+
+        // MethodParameters is preferred over the local variable table
+        let attributes = [
+            attribute(AttributeData::MethodParameters(vec![
+                MethodParameterEntry {
+                    name: Some("a".into()),
+                    access_flags: MethodParameterAccessFlags::empty(),
+                },
+                MethodParameterEntry {
+                    name: None,
+                    access_flags: MethodParameterAccessFlags::SYNTHETIC,
+                },
+            ])),
+            code(vec![local_variable("x", 0, 0), local_variable("y", 1, 0)]),
+        ];
+        assert_eq!(
+            convert_parameter_names(true, "(II)V", &attributes, false),
+            names(&[Some("a"), None])
+        );
+
+        // Long and double parameters use two slots and instance methods start after `this`
+        let attributes = [code(vec![
+            local_variable("this", 0, 0),
+            local_variable("j", 1, 0),
+            local_variable("s", 3, 0),
+            local_variable("arr", 4, 0),
+            local_variable("d", 5, 0),
+            local_variable("local", 7, 2),
+        ])];
+        assert_eq!(
+            convert_parameter_names(false, "(JLjava/lang/String;[DD)V", &attributes, true),
+            names(&[Some("j"), Some("s"), Some("arr"), Some("d")])
+        );
+        assert_eq!(
+            convert_parameter_names(true, "(IJ)V", &attributes, true),
+            names(&[Some("this"), Some("j")])
+        );
+        // The local variable table is only used if enabled
+        assert!(
+            convert_parameter_names(false, "(JLjava/lang/String;[DD)V", &attributes, false)
+                .is_empty()
+        );
+
+        // Variables which do not start with the method are not parameters
+        let attributes = [code(vec![local_variable("local", 1, 2)])];
+        assert!(convert_parameter_names(false, "(I)V", &attributes, true).is_empty());
+        assert!(convert_parameter_names(false, "(I)V", &[], true).is_empty());
+    }
+}
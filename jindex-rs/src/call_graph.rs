@@ -0,0 +1,219 @@
+use crate::class_index::ClassIndex;
+use crate::class_index_members::{IndexedClass, IndexedMethod};
+use crate::reference::IndexedReference;
+use cafebabe::MethodAccessFlags;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::collections::hash_map::Entry;
+
+/// A method identified by the index of its declaring class and its index in the methods of that
+/// class
+type MethodKey = (u32, u32);
+
+/// The calls between all methods of a [ClassIndex], built from the references of each method.
+/// A call of a method which can be overridden is also treated as a call of all methods overriding
+/// it, which makes the graph an over-approximation of the possible calls.
+pub struct CallGraph<'a> {
+    class_index: &'a ClassIndex,
+    callees: FxHashMap<MethodKey, Vec<MethodKey>>,
+    callers: FxHashMap<MethodKey, Vec<MethodKey>>,
+}
+
+impl<'a> CallGraph<'a> {
+    /// Builds the call graph of the given index. The graph is empty if the index was created
+    /// without references. Fails if the members of a class could not be loaded.
+    pub fn new(class_index: &'a ClassIndex) -> anyhow::Result<Self> {
+        class_index.load_all_members()?;
+        let mut overriding_methods: FxHashMap<MethodKey, Vec<MethodKey>> = FxHashMap::default();
+        let mut callees: FxHashMap<MethodKey, Vec<MethodKey>> = FxHashMap::default();
+        let mut callers: FxHashMap<MethodKey, Vec<MethodKey>> = FxHashMap::default();
+        for class in class_index.classes() {
+            for (method_index, references) in class.method_references().iter().enumerate() {
+                let mut targets = Vec::new();
+                for reference in references {
+                    if let IndexedReference::Method {
+                        class_index: target_class,
+                        method_index: target_method,
+                    } = *reference
+                    {
+                        let target = (target_class, target_method);
+                        targets.push(target);
+                        let overriding = match overriding_methods.entry(target) {
+                            Entry::Occupied(entry) => entry.into_mut(),
+                            Entry::Vacant(entry) => {
+                                entry.insert(find_overriding_methods(class_index, target)?)
+                            }
+                        };
+                        targets.extend(overriding.iter());
+                    }
+                }
+                if targets.is_empty() {
+                    continue;
+                }
+
+                targets.sort_unstable();
+                targets.dedup();
+                let caller = (class.index(), method_index as u32);
+                for target in targets.iter() {
+                    callers.entry(*target).or_default().push(caller);
+                }
+                callees.insert(caller, targets);
+            }
+        }
+
+        Ok(Self {
+            class_index,
+            callees,
+            callers,
+        })
+    }
+
+    /// Returns the methods which the given method calls
+    pub fn callees_of(
+        &self,
+        class: &IndexedClass,
+        method: &IndexedMethod,
+    ) -> Vec<(&'a IndexedClass, &'a IndexedMethod)> {
+        self.resolve_all(method_key(class, method).and_then(|key| self.callees.get(&key)))
+    }
+
+    /// Returns the methods which call the given method
+    pub fn callers_of(
+        &self,
+        class: &IndexedClass,
+        method: &IndexedMethod,
+    ) -> Vec<(&'a IndexedClass, &'a IndexedMethod)> {
+        self.resolve_all(method_key(class, method).and_then(|key| self.callers.get(&key)))
+    }
+
+    /// Returns all methods which are transitively called by the given entry points, including the
+    /// entry points themselves. Methods of the index which are not returned are never called from
+    /// any entry point.
+    pub fn reachable_from(
+        &self,
+        entry_points: &[(&IndexedClass, &IndexedMethod)],
+    ) -> Vec<(&'a IndexedClass, &'a IndexedMethod)> {
+        let mut visited = FxHashSet::default();
+        let mut reachable = Vec::new();
+        let mut queue = entry_points
+            .iter()
+            .filter_map(|(class, method)| method_key(class, method))
+            .collect::<Vec<_>>();
+        while let Some(key) = queue.pop() {
+            if !visited.insert(key) {
+                continue;
+            }
+
+            reachable.push(key);
+            if let Some(callees) = self.callees.get(&key) {
+                queue.extend(callees.iter().filter(|callee| !visited.contains(*callee)));
+            }
+        }
+
+        self.resolve_all(Some(&reachable))
+    }
+
+    fn resolve_all(
+        &self,
+        keys: Option<&Vec<MethodKey>>,
+    ) -> Vec<(&'a IndexedClass, &'a IndexedMethod)> {
+        keys.map_or_else(Vec::new, |keys| {
+            keys.iter()
+                .map(|(class_index, method_index)| {
+                    let class = self.class_index.class_at_index(*class_index);
+                    (class, &class.methods()[*method_index as usize])
+                })
+                .collect()
+        })
+    }
+}
+
+fn method_key(class: &IndexedClass, method: &IndexedMethod) -> Option<MethodKey> {
+    class
+        .methods()
+        .iter()
+        .position(|m| std::ptr::eq(m, method))
+        .map(|index| (class.index(), index as u32))
+}
+
+/// Finds all methods in sub types of the class of the given method which override it. Static
+/// methods, private methods and constructors can't be overridden.
+fn find_overriding_methods(
+    class_index: &ClassIndex,
+    (class, method_index): MethodKey,
+) -> anyhow::Result<Vec<MethodKey>> {
+    let method = match class_index
+        .class_at_index(class)
+        .methods()
+        .get(method_index as usize)
+    {
+        Some(method) => method,
+        None => return Ok(Vec::new()),
+    };
+    if method.access_flags() & (MethodAccessFlags::STATIC | MethodAccessFlags::PRIVATE).bits() != 0
+        || method.method_name(class_index.constant_pool()) == "<init>"
+    {
+        return Ok(Vec::new());
+    }
+
+    Ok(class_index
+        .find_implementations_of_method(class, method)?
+        .into_iter()
+        .filter_map(|(class, method)| method_key(class, method))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CallGraph;
+    use crate::builder::workers::{create_class_index_from_infos, DuplicateClassPolicy};
+    use crate::class_index_members::{IndexedClass, IndexedMethod};
+    use crate::reference::RawReference;
+    use crate::signature::RawMethodSignature;
+    use crate::test::class_info;
+
+    #[test]
+    fn test_call_graph() {
+        let mut caller = class_info("a/Caller", "Ljava/lang/Object;", "()V");
+        caller.methods[0].references = vec![RawReference::Method {
+            class_name: "a/Base".into(),
+            method_name: "get".into(),
+            signature: RawMethodSignature::from_data("()V", &|| None).unwrap(),
+        }];
+        let (_, class_index) = create_class_index_from_infos(
+            vec![
+                class_info("a/Base", "Ljava/lang/Object;", "()V"),
+                caller,
+                class_info("a/Sub", "La/Base;", "()V"),
+                class_info("a/Unused", "Ljava/lang/Object;", "()V"),
+            ],
+            DuplicateClassPolicy::First,
+        )
+        .unwrap();
+        let call_graph = CallGraph::new(&class_index).unwrap();
+
+        let class = |name| class_index.find_class("a", name).unwrap();
+        let class_names = |methods: Vec<(&IndexedClass, &IndexedMethod)>| {
+            let mut names = methods
+                .into_iter()
+                .map(|(class, _)| class.class_name(class_index.constant_pool()))
+                .collect::<Vec<_>>();
+            names.sort_unstable();
+            names
+        };
+        let caller = class("Caller");
+        // The virtual call also reaches the override in Sub
+        assert_eq!(
+            class_names(call_graph.callees_of(caller, &caller.methods()[0])),
+            vec!["Base", "Sub"]
+        );
+        let sub = class("Sub");
+        assert_eq!(
+            class_names(call_graph.callers_of(sub, &sub.methods()[0])),
+            vec!["Caller"]
+        );
+        assert_eq!(
+            class_names(call_graph.reachable_from(&[(caller, &caller.methods()[0])])),
+            vec!["Base", "Caller", "Sub"]
+        );
+    }
+}
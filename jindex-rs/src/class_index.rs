@@ -1,20 +1,43 @@
 use std::hash::{Hash, Hasher};
 use std::ops::Range;
 
-use ascii::{AsAsciiStr, AsciiChar, AsciiStr};
+use anyhow::Context;
+use cafebabe::{ClassAccessFlags, MethodAccessFlags};
+use once_cell::sync::OnceCell;
+use rayon::prelude::*;
 use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::all_direct_super_types;
-use crate::class_index_members::{IndexedClass, IndexedMethod};
-use crate::constant_pool::{ClassIndexConstantPool, MatchMode, SearchMode, SearchOptions};
+use crate::annotation::IndexedAnnotation;
+use crate::builder::fingerprint::SourceFingerprint;
+use crate::builder::workers::ReadOptions;
+use crate::class_index_members::{IndexedClass, IndexedField, IndexedMethod};
+use crate::constant_pool::{
+    ClassIndexConstantPool, MatchMode, SearchMode, SearchOptions, SearchResult,
+};
+use crate::constant_value::RawConstantValue;
+use crate::name_pattern::NamePattern;
 use crate::package_index::{IndexedPackage, PackageIndex};
+use crate::reference::IndexedReference;
 use crate::rsplit_once;
+use crate::validation::validate_super_types;
 
 pub struct ClassIndex {
     constant_pool: ClassIndexConstantPool,
     class_prefix_range_map: FxHashMap<u8, Range<u32>>,
     package_index: PackageIndex,
     classes: Vec<IndexedClass>,
+    sources: Vec<String>,
+    /// Maps the index of each class which was found in multiple sources to the indices of those
+    /// sources, sorted by class index
+    duplicate_class_sources: Vec<(u32, Vec<u32>)>,
+    /// The state of all paths this index was created from at the time they were read. Stored
+    /// separately from the rest of the index.
+    source_fingerprints: Vec<SourceFingerprint>,
+    /// The options used for reading the classes of this index
+    read_options: ReadOptions,
+    /// Set once the members of all classes were loaded successfully
+    all_members_loaded: OnceCell<()>,
 }
 
 impl ClassIndex {
@@ -22,26 +45,22 @@ impl ClassIndex {
         constant_pool: ClassIndexConstantPool,
         package_index: PackageIndex,
         classes: Vec<IndexedClass>,
+        sources: Vec<String>,
+        duplicate_class_sources: Vec<(u32, Vec<u32>)>,
     ) -> Self {
         //Construct prefix range map
         let mut prefix_count_map: FxHashMap<u8, u32> = FxHashMap::default();
 
         for class in classes.iter() {
             let count = prefix_count_map
-                .entry(
-                    class
-                        .class_name(&constant_pool)
-                        .get_ascii(0)
-                        .unwrap()
-                        .as_byte(),
-                )
+                .entry(class.class_name(&constant_pool).as_bytes()[0])
                 .or_insert(0);
             *count += 1;
         }
 
         let mut range_map: FxHashMap<u8, Range<u32>> = FxHashMap::default();
         let mut total = 0u32;
-        for i in 0..=127u8 {
+        for i in 0..=u8::MAX {
             let prefix_count = prefix_count_map.get(&i);
             if prefix_count.is_none() {
                 continue;
@@ -57,36 +76,67 @@ impl ClassIndex {
             constant_pool,
             classes,
             package_index,
+            sources,
+            duplicate_class_sources,
+            source_fingerprints: Vec::new(),
+            read_options: ReadOptions::default(),
+            all_members_loaded: OnceCell::new(),
             class_prefix_range_map: range_map,
         }
     }
 
-    pub fn find_classes(&self, name: &AsciiStr, options: SearchOptions) -> Vec<&IndexedClass> {
+    pub fn find_classes(&self, name: &str, options: SearchOptions) -> Vec<&IndexedClass> {
+        self.find_classes_with_scores(name, options)
+            .into_iter()
+            .map(|result| result.value)
+            .collect()
+    }
+
+    /// Like [ClassIndex::find_classes], but also returns the score of each class. The classes
+    /// are sorted by their score.
+    pub fn find_classes_with_scores(
+        &self,
+        name: &str,
+        options: SearchOptions,
+    ) -> Vec<SearchResult<&IndexedClass>> {
         if name.is_empty() {
             return Vec::default();
         }
 
-        let mut iters = Vec::with_capacity(2);
+        let mut iters = Vec::with_capacity(3);
+        let first_byte = name.as_bytes()[0];
         match options.search_mode {
             SearchMode::Prefix => match options.match_mode {
+                MatchMode::IgnoreCase if first_byte.is_ascii() => {
+                    iters.push(self.class_iter_for_char(first_byte.to_ascii_lowercase()));
+                    iters.push(self.class_iter_for_char(first_byte.to_ascii_uppercase()));
+                    //Some non ASCII chars have an ASCII char as their other case
+                    let non_ascii_start = self.classes.partition_point(|c| {
+                        c.class_name(&self.constant_pool).as_bytes()[0].is_ascii()
+                    });
+                    iters.push(&self.classes[non_ascii_start..]);
+                }
                 MatchMode::IgnoreCase => {
-                    iters.push(self.class_iter_for_char(
-                        name.get_ascii(0).unwrap().to_ascii_lowercase().as_byte(),
-                    ));
-                    iters.push(self.class_iter_for_char(
-                        name.get_ascii(0).unwrap().to_ascii_uppercase().as_byte(),
-                    ));
+                    //The other case of a non ASCII char can start with any byte
+                    iters.push(&self.classes[..]);
                 }
                 MatchMode::MatchCase | MatchMode::MatchCaseFirstCharOnly => {
-                    iters.push(self.class_iter_for_char(name.get_ascii(0).unwrap().as_byte()));
+                    iters.push(self.class_iter_for_char(first_byte));
                 }
             },
-            SearchMode::Contains => {
-                //We have to search all classes in contains mode
+            SearchMode::Contains | SearchMode::CamelCase | SearchMode::Fuzzy => {
+                //We have to search all classes in these modes
                 iters.push(&self.classes[..]);
             }
         }
 
+        //The best camel case and fuzzy matches can be anywhere, therefore the limit is applied
+        //after sorting
+        let search_limit = match options.search_mode {
+            SearchMode::CamelCase | SearchMode::Fuzzy => usize::MAX,
+            _ => options.limit,
+        };
+
         let mut result: Vec<(usize, &IndexedClass)> = Vec::new();
 
         for x in iters {
@@ -102,12 +152,16 @@ impl ClassIndex {
                     index += 1;
                     result
                 })
-                .take(options.limit.saturating_sub(result.len()))
+                .take(search_limit.saturating_sub(result.len()))
                 .for_each(|el| result.push(el))
         }
 
         result.sort_by_key(|el| el.0);
-        result.into_iter().map(|el| el.1).collect()
+        result
+            .into_iter()
+            .take(options.limit)
+            .map(|(score, value)| SearchResult { score, value })
+            .collect()
     }
 
     ///TODO:
@@ -118,16 +172,12 @@ impl ClassIndex {
     /// package class_indices to make this whole find_class even faster For example, when
     /// searching for 'java/lang/S', we perform a binary search on a slice with 12k elements.
     /// Instead we could find java/lang extremely fast and then binary search ~200 classes.
-    pub fn find_class(
-        &self,
-        package_name: &AsciiStr,
-        class_name: &AsciiStr,
-    ) -> Option<&IndexedClass> {
+    pub fn find_class(&self, package_name: &str, class_name: &str) -> Option<&IndexedClass> {
         if class_name.is_empty() {
             return Option::None;
         }
 
-        let class_iter = self.class_iter_for_char(class_name.get_ascii(0).unwrap().as_byte());
+        let class_iter = self.class_iter_for_char(class_name.as_bytes()[0]);
 
         let index = class_iter.binary_search_by(|a| {
             a.class_name(&self.constant_pool)
@@ -149,13 +199,26 @@ impl ClassIndex {
         None
     }
 
-    pub fn find_packages(&self, name: &AsciiStr) -> Vec<&IndexedPackage> {
+    pub fn find_packages(&self, name: &str) -> Vec<&IndexedPackage> {
+        self.find_packages_with_scores(name, SearchOptions::default())
+            .into_iter()
+            .map(|result| result.value)
+            .collect()
+    }
+
+    /// Searches the sub packages of the package before the last '/' of `name` for packages
+    /// matching the rest of `name`. The packages are sorted by their score.
+    pub fn find_packages_with_scores(
+        &self,
+        name: &str,
+        options: SearchOptions,
+    ) -> Vec<SearchResult<&IndexedPackage>> {
         if name.is_empty() {
             return Vec::default();
         }
 
         let pool = self.constant_pool();
-        let split_index = rsplit_once(name, AsciiChar::Slash);
+        let split_index = rsplit_once(name, '/');
 
         let base_package = if split_index.0.is_empty() {
             Some(self.package_index.package_at(0))
@@ -168,21 +231,72 @@ impl ClassIndex {
                 let mut results = Vec::new();
                 for sub_index in p.sub_packages_indices() {
                     let sub_package = self.package_index.package_at(*sub_index);
-                    if pool
+                    if let Some(score) = pool
                         .string_view_at(sub_package.package_name_index())
-                        .starts_with(pool, split_index.1, MatchMode::IgnoreCase)
+                        .search(pool, split_index.1, options)
                     {
-                        results.push(sub_package);
+                        results.push(SearchResult {
+                            score,
+                            value: sub_package,
+                        });
                     }
                 }
 
+                results.sort_by_key(|result| result.score);
+                results.truncate(options.limit);
                 results
             }
             None => Vec::default(),
         }
     }
 
-    pub fn find_package(&self, name: &AsciiStr) -> Option<&IndexedPackage> {
+    /// Returns the classes whose fully qualified name matches the given pattern. Packages are
+    /// only searched if names starting with their name can match the pattern.
+    pub fn find_classes_matching(
+        &self,
+        pattern: &NamePattern,
+        limit: usize,
+    ) -> anyhow::Result<Vec<&IndexedClass>> {
+        let mut cache = pattern.create_cache();
+        let mut result = Vec::new();
+        let mut queue = vec![(0, pattern.start_state(&mut cache)?)];
+        while let Some((package_index, state)) = queue.pop() {
+            let package = self.package_index.package_at(package_index);
+            for class_index in package.sub_classes_indices().iter() {
+                if result.len() >= limit {
+                    return Ok(result);
+                }
+
+                let class = self.class_at_index(*class_index);
+                let class_state =
+                    pattern.feed(&mut cache, state, class.class_name(&self.constant_pool))?;
+                if pattern.is_match(&mut cache, class_state)? {
+                    result.push(class);
+                }
+            }
+
+            for sub_index in package.sub_packages_indices() {
+                let package_name = self
+                    .package_index
+                    .package_at(*sub_index)
+                    .package_name(&self.constant_pool);
+                // Classes without a package are in a package with an empty name
+                let sub_state = if package_name.is_empty() {
+                    state
+                } else {
+                    let sub_state = pattern.feed(&mut cache, state, package_name)?;
+                    pattern.feed(&mut cache, sub_state, "/")?
+                };
+                if !sub_state.is_dead() {
+                    queue.push((*sub_index, sub_state));
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    pub fn find_package(&self, name: &str) -> Option<&IndexedPackage> {
         for sub_index in self.package_index.package_at(0).sub_packages_indices() {
             let result = self.find_package_starting_at(name, *sub_index);
             if result.is_some() {
@@ -195,14 +309,11 @@ impl ClassIndex {
 
     fn find_package_starting_at(
         &self,
-        name: &AsciiStr,
+        name: &str,
         start_package_index: u32,
     ) -> Option<(u32, &IndexedPackage)> {
         let package = self.package_index.package_at(start_package_index);
-        let split_index = name
-            .chars()
-            .position(|ch| ch == AsciiChar::Slash)
-            .unwrap_or(name.len());
+        let split_index = name.find('/').unwrap_or(name.len());
         let part = &name[0..split_index];
 
         if package.package_name(&self.constant_pool) != part {
@@ -225,11 +336,8 @@ impl ClassIndex {
         }
     }
 
-    pub fn find_methods(
-        &self,
-        name: &AsciiStr,
-        limit: usize,
-    ) -> anyhow::Result<Vec<&IndexedMethod>> {
+    pub fn find_methods(&self, name: &str, limit: usize) -> anyhow::Result<Vec<&IndexedMethod>> {
+        self.load_all_members()?;
         let res = self
             .classes
             .iter()
@@ -244,13 +352,41 @@ impl ClassIndex {
         Ok(res)
     }
 
+    /// Searches the names of all methods. The methods are sorted by their score.
+    pub fn find_methods_with_scores(
+        &self,
+        name: &str,
+        options: SearchOptions,
+    ) -> anyhow::Result<Vec<SearchResult<(&IndexedClass, &IndexedMethod)>>> {
+        self.load_all_members()?;
+        let mut results = self
+            .classes
+            .iter()
+            .flat_map(|class| class.methods().iter().map(move |method| (class, method)))
+            .filter_map(|(class, method)| {
+                self.constant_pool()
+                    .string_view_at(method.method_name_index())
+                    .search(self.constant_pool(), name, options)
+                    .map(|score| SearchResult {
+                        score,
+                        value: (class, method),
+                    })
+            })
+            .collect::<Vec<_>>();
+        results.sort_by_key(|result| result.score);
+        results.truncate(options.limit);
+        Ok(results)
+    }
+
     pub fn find_implementations_of_class(
         &self,
         index: u32,
         direct_sub_types_only: bool,
-    ) -> Vec<&IndexedClass> {
+    ) -> anyhow::Result<Vec<&IndexedClass>> {
+        self.load_all_members()?;
         let mut queue = Vec::new();
-        self.classes
+        Ok(self
+            .classes
             .iter()
             .filter(|class| {
                 if direct_sub_types_only {
@@ -285,15 +421,16 @@ impl ClassIndex {
                     false
                 }
             })
-            .collect()
+            .collect())
     }
 
     pub fn find_implementations_of_method<'b>(
         &'b self,
         defining_class_index: u32,
         target_method: &'b IndexedMethod,
-    ) -> Vec<(&IndexedClass, &IndexedMethod)> {
-        self.find_implementations_of_class(defining_class_index, false)
+    ) -> anyhow::Result<Vec<(&IndexedClass, &IndexedMethod)>> {
+        Ok(self
+            .find_implementations_of_class(defining_class_index, false)?
             .iter()
             .flat_map(|class| {
                 class
@@ -302,7 +439,7 @@ impl ClassIndex {
                     .filter(|m| m.overrides(target_method))
                     .map(|m| (*class, m))
             })
-            .collect()
+            .collect())
     }
 
     pub fn find_base_methods_of_method(
@@ -335,10 +472,302 @@ impl ClassIndex {
             .collect()
     }
 
+    /// Finds all classes, fields, methods and method parameters which are annotated with the
+    /// given annotation class
+    pub fn find_annotated(&self, annotation_class: u32) -> anyhow::Result<Vec<AnnotatedElement>> {
+        self.load_all_members()?;
+        let is_annotated = |annotations: &[IndexedAnnotation]| {
+            annotations
+                .iter()
+                .any(|a| a.annotation_class() == Some(annotation_class))
+        };
+
+        let mut result = Vec::new();
+        for class in self.classes.iter() {
+            if is_annotated(class.annotations()) {
+                result.push(AnnotatedElement::Class(class));
+            }
+            for field in class.fields().iter() {
+                if is_annotated(field.annotations()) {
+                    result.push(AnnotatedElement::Field(class, field));
+                }
+            }
+            for method in class.methods().iter() {
+                if is_annotated(method.annotations()) {
+                    result.push(AnnotatedElement::Method(class, method));
+                }
+                for (index, annotations) in method.parameter_annotations().iter().enumerate() {
+                    if is_annotated(annotations) {
+                        result.push(AnnotatedElement::Parameter(class, method, index));
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Finds all fields whose constant value has the same type and value as the given one
+    pub fn find_fields_with_constant(
+        &self,
+        value: &RawConstantValue,
+    ) -> anyhow::Result<Vec<(&IndexedClass, &IndexedField)>> {
+        self.load_all_members()?;
+        Ok(self
+            .classes
+            .iter()
+            .flat_map(|class| class.fields().iter().map(move |field| (class, field)))
+            .filter(|(_, field)| {
+                field
+                    .constant_value()
+                    .map_or(false, |v| value.matches(&self.constant_pool, v))
+            })
+            .collect())
+    }
+
+    /// Finds all classes which use a string literal matching the given query, either in their code
+    /// or as the constant value of a field. Always empty if the index was created without string
+    /// literals.
+    pub fn find_classes_by_string_literal(
+        &self,
+        query: &str,
+        options: SearchOptions,
+    ) -> anyhow::Result<Vec<&IndexedClass>> {
+        self.load_all_members()?;
+        // Many classes use the same strings, therefore every string is only searched once
+        let mut matches: FxHashMap<u32, bool> = FxHashMap::default();
+        Ok(self
+            .classes
+            .iter()
+            .filter(|class| {
+                class.string_literals().iter().any(|literal| {
+                    *matches.entry(*literal).or_insert_with(|| {
+                        self.constant_pool
+                            .string_view_at(*literal)
+                            .search(&self.constant_pool, query, options)
+                            .is_some()
+                    })
+                })
+            })
+            .take(options.limit)
+            .collect())
+    }
+
+    /// Finds all methods whose code invokes the given method. Calls through a subclass which
+    /// inherits the method are included, calls of methods which override it are not. Always empty
+    /// if the index was created without references.
+    pub fn find_usages_of_method<'b>(
+        &'b self,
+        defining_class_index: u32,
+        target_method: &'b IndexedMethod,
+    ) -> anyhow::Result<Vec<(&'b IndexedClass, &'b IndexedMethod)>> {
+        self.load_all_members()?;
+        let target_index = match self
+            .class_at_index(defining_class_index)
+            .methods()
+            .iter()
+            .position(|m| std::ptr::eq(m, target_method))
+        {
+            Some(index) => index as u32,
+            None => return Ok(Vec::new()),
+        };
+
+        self.find_usages(|reference| {
+            matches!(reference, IndexedReference::Method { class_index, method_index }
+                if *class_index == defining_class_index && *method_index == target_index)
+        })
+    }
+
+    /// Finds all methods whose code reads or writes the given field. Always empty if the index was
+    /// created without references.
+    pub fn find_usages_of_field<'b>(
+        &'b self,
+        defining_class_index: u32,
+        target_field: &'b IndexedField,
+    ) -> anyhow::Result<Vec<(&'b IndexedClass, &'b IndexedMethod)>> {
+        self.load_all_members()?;
+        let target_index = match self
+            .class_at_index(defining_class_index)
+            .fields()
+            .iter()
+            .position(|f| std::ptr::eq(f, target_field))
+        {
+            Some(index) => index as u32,
+            None => return Ok(Vec::new()),
+        };
+
+        self.find_usages(|reference| match reference {
+            IndexedReference::FieldRead {
+                class_index,
+                field_index,
+            }
+            | IndexedReference::FieldWrite {
+                class_index,
+                field_index,
+            } => *class_index == defining_class_index && *field_index == target_index,
+            _ => false,
+        })
+    }
+
+    /// Finds all methods whose code instantiates the given class, casts to it, checks for it with
+    /// `instanceof` or accesses one of its members. Always empty if the index was created without
+    /// references.
+    pub fn find_usages_of_class(
+        &self,
+        class_index: u32,
+    ) -> anyhow::Result<Vec<(&IndexedClass, &IndexedMethod)>> {
+        self.find_usages(|reference| reference.class_index() == class_index)
+    }
+
+    fn find_usages(
+        &self,
+        is_usage: impl Fn(&IndexedReference) -> bool,
+    ) -> anyhow::Result<Vec<(&IndexedClass, &IndexedMethod)>> {
+        self.load_all_members()?;
+        Ok(self
+            .classes
+            .iter()
+            .flat_map(|class| {
+                class
+                    .methods()
+                    .iter()
+                    .zip(class.method_references())
+                    .filter(|(_, references)| references.iter().any(&is_usage))
+                    .map(move |(method, _)| (class, method))
+            })
+            .collect())
+    }
+
+    /// Returns the host of the nest the given class belongs to. Classes without a `NestHost`
+    /// attribute, e.g. from class files older than Java 11, belong to the nest of their outermost
+    /// enclosing class.
+    pub fn nest_host_of<'b>(&'b self, class: &'b IndexedClass) -> &'b IndexedClass {
+        if let Some(host) = class.nest_host() {
+            return self.class_at_index(host);
+        }
+
+        let mut current = class;
+        while let Some(enclosing_class) = current.enclosing_class(self) {
+            current = enclosing_class;
+        }
+        current
+    }
+
+    /// Returns true if code in `from_class` can access the target class, or the given member of
+    /// the target class. Protected members are accessible from all subclasses, the type of the
+    /// receiver is not checked.
+    pub fn is_accessible(
+        &self,
+        from_class: &IndexedClass,
+        target_class: &IndexedClass,
+        target_member: Option<ClassMember>,
+    ) -> bool {
+        if !self.is_class_accessible(from_class, target_class) {
+            return false;
+        }
+
+        match target_member {
+            Some(member) => self.is_access_allowed(from_class, target_class, member.access_flags()),
+            None => true,
+        }
+    }
+
+    fn is_class_accessible(&self, from_class: &IndexedClass, target_class: &IndexedClass) -> bool {
+        match target_class.enclosing_class(self) {
+            // Nested classes also carry the modifiers of their declaration
+            Some(enclosing_class) => {
+                self.is_class_accessible(from_class, enclosing_class)
+                    && self.is_access_allowed(
+                        from_class,
+                        enclosing_class,
+                        target_class.access_flags(),
+                    )
+            }
+            None => {
+                target_class.access_flags() & ClassAccessFlags::PUBLIC.bits() != 0
+                    || from_class.package_index() == target_class.package_index()
+            }
+        }
+    }
+
+    /// Checks the access to something with the given access flags declared in `declaring_class`
+    fn is_access_allowed(
+        &self,
+        from_class: &IndexedClass,
+        declaring_class: &IndexedClass,
+        access_flags: u16,
+    ) -> bool {
+        let is_protected = access_flags & MethodAccessFlags::PROTECTED.bits() != 0;
+        if access_flags & MethodAccessFlags::PRIVATE.bits() != 0 {
+            return std::ptr::eq(
+                self.nest_host_of(from_class),
+                self.nest_host_of(declaring_class),
+            );
+        }
+        // Protected nested classes are public in their own class file
+        if access_flags & MethodAccessFlags::PUBLIC.bits() != 0 && !is_protected {
+            return true;
+        }
+        if from_class.package_index() == declaring_class.package_index() {
+            return true;
+        }
+        if !is_protected {
+            return false;
+        }
+
+        // Nested classes of a subclass can access protected members as well
+        let mut current = Some(from_class);
+        while let Some(class) = current {
+            if self.is_subclass_of(class, declaring_class.index()) {
+                return true;
+            }
+            current = class.enclosing_class(self);
+        }
+        false
+    }
+
+    /// Checks if `class` extends the class at the index `super_class` directly or indirectly.
+    /// Interfaces are not checked, because they cannot declare protected members.
+    fn is_subclass_of(&self, class: &IndexedClass, super_class: u32) -> bool {
+        let mut current = class;
+        // The super classes of a malformed index could form a cycle
+        for _ in 0..self.classes.len() {
+            match current
+                .signature()
+                .super_class()
+                .and_then(|s| s.extract_base_object_type())
+            {
+                Some(index) if index == super_class => return true,
+                Some(index) => current = self.class_at_index(index),
+                None => return false,
+            }
+        }
+        false
+    }
+
     pub fn classes(&self) -> &Vec<IndexedClass> {
         &self.classes
     }
 
+    /// Loads the members of all classes and checks that the super types of no class form a cycle.
+    /// Searches over the members of the whole index call this first and fail with its error,
+    /// because the members of a class which could not be loaded are otherwise treated as empty.
+    pub fn load_all_members(&self) -> anyhow::Result<()> {
+        self.all_members_loaded
+            .get_or_try_init(|| {
+                for class in self.classes.iter() {
+                    class.load_members().with_context(|| {
+                        format!(
+                            "Failed to load members of {}",
+                            class.class_name_with_package(&self.package_index, &self.constant_pool)
+                        )
+                    })?;
+                }
+                validate_super_types(&self.classes)
+            })
+            .map(|_| ())
+    }
+
     pub fn package_index(&self) -> &PackageIndex {
         &self.package_index
     }
@@ -351,6 +780,63 @@ impl ClassIndex {
         self.classes().get(index as usize).unwrap()
     }
 
+    /// Paths of all jars, directories and runtime images the classes in this index were read from
+    pub fn sources(&self) -> &Vec<String> {
+        &self.sources
+    }
+
+    pub fn source_at_index(&self, index: u32) -> &str {
+        self.sources.get(index as usize).unwrap()
+    }
+
+    pub fn source_fingerprints(&self) -> &Vec<SourceFingerprint> {
+        &self.source_fingerprints
+    }
+
+    pub(crate) fn set_source_fingerprints(&mut self, fingerprints: Vec<SourceFingerprint>) {
+        self.source_fingerprints = fingerprints;
+    }
+
+    /// The options this index was created with. Indexes created from class bytes and indexes
+    /// loaded from files saved by older versions use the default options.
+    pub fn read_options(&self) -> &ReadOptions {
+        &self.read_options
+    }
+
+    pub(crate) fn set_read_options(&mut self, options: ReadOptions) {
+        self.read_options = options;
+    }
+
+    /// Checks if this index was created from exactly the given paths and none of them have changed
+    /// since then. The order of the paths is ignored, because updated indexes contain added paths
+    /// last.
+    pub fn is_up_to_date(&self, paths: &[String]) -> bool {
+        let indexed_paths = self
+            .source_fingerprints
+            .iter()
+            .map(SourceFingerprint::path)
+            .collect::<FxHashSet<_>>();
+        let paths = paths.iter().map(String::as_str).collect::<FxHashSet<_>>();
+        indexed_paths == paths
+            && self
+                .source_fingerprints
+                .par_iter()
+                .all(|fingerprint| fingerprint.is_up_to_date())
+    }
+
+    pub fn duplicate_class_sources(&self) -> &Vec<(u32, Vec<u32>)> {
+        &self.duplicate_class_sources
+    }
+
+    /// Returns the indices of all sources which contain a class with the same name as the class
+    /// at the given index, or None if the class was only found once
+    pub fn duplicate_class_sources_of(&self, class_index: u32) -> Option<&Vec<u32>> {
+        self.duplicate_class_sources
+            .binary_search_by_key(&class_index, |(index, _)| *index)
+            .ok()
+            .map(|i| &self.duplicate_class_sources[i].1)
+    }
+
     fn class_iter_for_char(&self, char: u8) -> &[IndexedClass] {
         self.class_prefix_range_map.get(&char).map_or_else(
             || &self.classes[0..0],
@@ -359,6 +845,31 @@ impl ClassIndex {
     }
 }
 
+/// A field or method of a class
+#[derive(Clone, Copy)]
+pub enum ClassMember<'a> {
+    Field(&'a IndexedField),
+    Method(&'a IndexedMethod),
+}
+
+impl ClassMember<'_> {
+    pub fn access_flags(&self) -> u16 {
+        match self {
+            ClassMember::Field(field) => field.access_flags(),
+            ClassMember::Method(method) => method.access_flags(),
+        }
+    }
+}
+
+/// An element which carries an annotation, see [ClassIndex::find_annotated]
+pub enum AnnotatedElement<'a> {
+    Class(&'a IndexedClass),
+    Field(&'a IndexedClass, &'a IndexedField),
+    Method(&'a IndexedClass, &'a IndexedMethod),
+    /// A method parameter and its index
+    Parameter(&'a IndexedClass, &'a IndexedMethod, usize),
+}
+
 pub struct MethodWithClass<'a> {
     pub class: &'a IndexedClass,
     pub method: &'a IndexedMethod,
@@ -380,3 +891,172 @@ impl Hash for MethodWithClass<'_> {
         self.method.method_name_index().hash(state);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ClassMember;
+    use crate::builder::incremental::to_class_info;
+    use crate::builder::workers::{create_class_index_from_infos, DuplicateClassPolicy};
+    use crate::builder::RecordComponentInfo;
+    use crate::constant_pool::{MatchMode, SearchMode, SearchOptions};
+    use crate::signature::{InnerClassType, RawEnclosingTypeInfo, RawSignatureType};
+    use crate::test::class_info;
+    use cafebabe::{ClassAccessFlags, MethodAccessFlags};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_permitted_subclasses_and_record_components() {
+        let mut shape = class_info("a/Shape", "Ljava/lang/Object;", "()V");
+        shape.permitted_subclasses = vec!["a/Circle".into(), "b/Missing".into()];
+        let mut circle = class_info("a/Circle", "Ljava/lang/Record;", "()V");
+        circle.record_components = Some(vec![RecordComponentInfo {
+            name: "radius".into(),
+            signature: RawSignatureType::from_str("D").unwrap(),
+        }]);
+        let (_, class_index) =
+            create_class_index_from_infos(vec![shape, circle], DuplicateClassPolicy::First)
+                .unwrap();
+
+        let shape = class_index.find_class("a", "Shape").unwrap();
+        let circle = class_index.find_class("a", "Circle").unwrap();
+        assert!(shape.is_sealed());
+        assert_eq!(shape.permitted_subclasses(), &[circle.index()]);
+        assert!(shape.record_components().is_none());
+
+        let class_info = to_class_info(circle, &class_index, None);
+        let components = class_info.record_components.unwrap();
+        assert_eq!(components[0].name, "radius");
+    }
+
+    #[test]
+    fn test_is_accessible() {
+        let mut base = class_info("a/Base", "Ljava/lang/Object;", "()V");
+        base.access_flags = ClassAccessFlags::PUBLIC.bits();
+        base.methods[0].access_flags = MethodAccessFlags::PROTECTED;
+        let mut hidden = class_info("a/Base$Hidden", "Ljava/lang/Object;", "()V");
+        hidden.access_flags = ClassAccessFlags::PUBLIC.bits() | MethodAccessFlags::PRIVATE.bits();
+        hidden.enclosing_type = Some(RawEnclosingTypeInfo::new(
+            Some("a/Base".into()),
+            InnerClassType::Member,
+            None,
+            None,
+        ));
+        let mut sub_inner = class_info("c/SubSub$Inner", "Ljava/lang/Object;", "()V");
+        sub_inner.enclosing_type = Some(RawEnclosingTypeInfo::new(
+            Some("c/SubSub".into()),
+            InnerClassType::Member,
+            None,
+            None,
+        ));
+        let (_, class_index) = create_class_index_from_infos(
+            vec![
+                base,
+                hidden,
+                class_info("a/Other", "Ljava/lang/Object;", "()V"),
+                class_info("b/Sub", "La/Base;", "()V"),
+                class_info("c/SubSub", "Lb/Sub;", "()V"),
+                sub_inner,
+                class_info("c/Unrelated", "Ljava/lang/Object;", "()V"),
+            ],
+            DuplicateClassPolicy::First,
+        )
+        .unwrap();
+
+        let class = |package, name| class_index.find_class(package, name).unwrap();
+        let base = class("a", "Base");
+        let member = Some(ClassMember::Method(&base.methods()[0]));
+        assert!(class_index.is_accessible(class("a", "Other"), base, member));
+        assert!(class_index.is_accessible(class("b", "Sub"), base, member));
+        assert!(class_index.is_accessible(class("c", "SubSub"), base, member));
+        assert!(class_index.is_accessible(class("c", "SubSub$Inner"), base, member));
+        assert!(!class_index.is_accessible(class("c", "Unrelated"), base, member));
+        assert!(!class_index.is_accessible(class("c", "Unrelated"), class("a", "Other"), None));
+
+        let hidden = class("a", "Base$Hidden");
+        assert!(class_index.is_accessible(base, hidden, None));
+        assert!(!class_index.is_accessible(class("a", "Other"), hidden, None));
+    }
+
+    #[test]
+    fn test_string_literals() {
+        let mut config = class_info("a/Config", "Ljava/lang/Object;", "()V");
+        config.string_literals = vec!["Missing config key: %s".into(), "timeout".into()];
+        let mut client = class_info("a/Client", "Ljava/lang/Object;", "()V");
+        client.string_literals = vec!["timeout".into()];
+        let (_, class_index) =
+            create_class_index_from_infos(vec![config, client], DuplicateClassPolicy::First)
+                .unwrap();
+
+        let find = |query, search_mode, limit| {
+            let options = SearchOptions {
+                limit,
+                search_mode,
+                match_mode: MatchMode::IgnoreCase,
+            };
+            class_index
+                .find_classes_by_string_literal(query, options)
+                .unwrap()
+                .into_iter()
+                .map(|class| class.class_name(class_index.constant_pool()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            find("CONFIG KEY", SearchMode::Contains, usize::MAX),
+            vec!["Config"]
+        );
+        assert_eq!(
+            find("time", SearchMode::Prefix, usize::MAX),
+            vec!["Client", "Config"]
+        );
+        assert_eq!(find("time", SearchMode::Prefix, 1).len(), 1);
+        assert!(find("key", SearchMode::Prefix, usize::MAX).is_empty());
+
+        let config = class_index.find_class("a", "Config").unwrap();
+        let class_info = to_class_info(config, &class_index, None);
+        assert_eq!(class_info.string_literals.len(), 2);
+    }
+
+    #[test]
+    fn test_scored_search() {
+        let (_, class_index) = create_class_index_from_infos(
+            vec![
+                class_info("a/ArrayListSpliterator", "Ljava/lang/Object;", "()V"),
+                class_info("a/ArrayList", "Ljava/lang/Object;", "()V"),
+                class_info("a/LinkedList", "Ljava/lang/Object;", "()V"),
+            ],
+            DuplicateClassPolicy::First,
+        )
+        .unwrap();
+        let fuzzy = SearchOptions {
+            search_mode: SearchMode::Fuzzy,
+            ..Default::default()
+        };
+
+        let results = class_index
+            .find_classes_with_scores("ArrayLsit", fuzzy)
+            .into_iter()
+            .map(|result| {
+                (
+                    result.value.class_name(class_index.constant_pool()),
+                    result.score,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(results, vec![("ArrayList", 2), ("ArrayListSpliterator", 3)]);
+        assert_eq!(
+            class_index
+                .find_methods_with_scores("gte", fuzzy)
+                .unwrap()
+                .len(),
+            3
+        );
+        assert_eq!(
+            class_index
+                .find_packages_with_scores("a", fuzzy)
+                .into_iter()
+                .map(|result| result.score)
+                .collect::<Vec<_>>(),
+            vec![0]
+        );
+    }
+}
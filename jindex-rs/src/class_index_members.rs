@@ -1,26 +1,47 @@
+use crate::annotation::IndexedAnnotation;
 use crate::class_index::ClassIndex;
 use crate::constant_pool::ClassIndexConstantPool;
+use crate::constant_value::IndexedConstantValue;
+use crate::io::{ClassMembers, MappedMembers};
 use crate::package_index::PackageIndex;
+use crate::reference::IndexedReference;
 use crate::signature::{
     IndexedClassSignature, IndexedEnclosingTypeInfo, IndexedMethodSignature, IndexedSignatureType,
 };
-use ascii::{AsAsciiStr, AsciiStr, AsciiString};
+use crate::validation::IndexBounds;
 use atomic_refcell::{AtomicRef, AtomicRefCell};
-use cafebabe::MethodAccessFlags;
-use once_cell::unsync::OnceCell;
+use cafebabe::{FieldAccessFlags, MethodAccessFlags};
+use once_cell::sync::OnceCell;
 use speedy::{Readable, Writable};
+use std::sync::Arc;
 
 pub struct IndexedClass {
     index: OnceCell<u32>,
     package_index: u32,
     name_index: u32,
-    name_start_index: u8,
+    name_start_index: u16,
     access_flags: u16,
+    class_file_version: u16,
+    module_name_index: Option<u32>,
+    source_index: Option<u32>,
     signature: OnceCell<IndexedClassSignature>,
     enclosing_type_info: OnceCell<IndexedEnclosingTypeInfo>,
     member_classes: AtomicRefCell<Vec<u32>>,
     fields: OnceCell<Vec<IndexedField>>,
     methods: OnceCell<Vec<IndexedMethod>>,
+    annotations: OnceCell<Vec<IndexedAnnotation>>,
+    permitted_subclasses: OnceCell<Vec<u32>>,
+    record_components: OnceCell<Option<Vec<IndexedRecordComponent>>>,
+    nest_host: OnceCell<Option<u32>>,
+    nest_members: OnceCell<Vec<u32>>,
+    method_references: OnceCell<Vec<Vec<IndexedReference>>>,
+    string_literals: OnceCell<Vec<u32>>,
+    /// The signature, enclosing type info, fields and methods of a loaded class are only read
+    /// from the index file when one of them is accessed for the first time
+    mapped_members: Option<MappedMembers>,
+    /// Set once the mapped members were read, contains the reason why they could not be loaded.
+    /// Multiple threads can access the members of the same class, but only one of them reads them.
+    member_load_result: OnceCell<Option<anyhow::Error>>,
 }
 
 #[macro_export]
@@ -37,8 +58,11 @@ impl IndexedClass {
     pub(crate) fn new(
         package_index: u32,
         class_name_index: u32,
-        class_name_start_index: u8,
+        class_name_start_index: u16,
         access_flags: u16,
+        class_file_version: u16,
+        module_name_index: Option<u32>,
+        source_index: Option<u32>,
     ) -> Self {
         Self {
             index: OnceCell::new(),
@@ -46,31 +70,54 @@ impl IndexedClass {
             name_index: class_name_index,
             name_start_index: class_name_start_index,
             access_flags,
+            class_file_version,
+            module_name_index,
+            source_index,
             signature: OnceCell::new(),
             enclosing_type_info: OnceCell::new(),
             member_classes: AtomicRefCell::default(),
             fields: OnceCell::new(),
             methods: OnceCell::new(),
+            annotations: OnceCell::new(),
+            permitted_subclasses: OnceCell::new(),
+            record_components: OnceCell::new(),
+            nest_host: OnceCell::new(),
+            nest_members: OnceCell::new(),
+            method_references: OnceCell::new(),
+            string_literals: OnceCell::new(),
+            mapped_members: None,
+            member_load_result: OnceCell::new(),
         }
     }
 
-    pub fn class_name<'b>(&self, constant_pool: &'b ClassIndexConstantPool) -> &'b AsciiStr {
+    pub fn class_name<'b>(&self, constant_pool: &'b ClassIndexConstantPool) -> &'b str {
         constant_pool
             .string_view_at(self.name_index)
-            .into_ascii_str(constant_pool)
+            .into_str(constant_pool)
     }
 
-    pub fn simple_class_name<'b>(&self, constant_pool: &'b ClassIndexConstantPool) -> &'b AsciiStr {
+    pub fn simple_class_name<'b>(&self, constant_pool: &'b ClassIndexConstantPool) -> &'b str {
         &constant_pool
             .string_view_at(self.name_index)
-            .into_ascii_str(constant_pool)[self.name_start_index as usize..]
+            .into_str(constant_pool)[self.name_start_index as usize..]
+    }
+
+    pub fn module_name<'b>(&self, constant_pool: &'b ClassIndexConstantPool) -> Option<&'b str> {
+        self.module_name_index
+            .map(|i| constant_pool.string_view_at(i).into_str(constant_pool))
+    }
+
+    /// The path of the jar, directory or runtime image this class was read from. For jars nested
+    /// in other jars, this looks like `outer.jar!/inner.jar`.
+    pub fn source<'a>(&self, class_index: &'a ClassIndex) -> Option<&'a str> {
+        self.source_index.map(|i| class_index.source_at_index(i))
     }
 
     pub fn class_name_with_package(
         &self,
         package_index: &PackageIndex,
         constant_pool: &ClassIndexConstantPool,
-    ) -> AsciiString {
+    ) -> String {
         self.name_with_package(self.class_name(constant_pool), package_index, constant_pool)
     }
     
@@ -78,24 +125,24 @@ impl IndexedClass {
         &self,
         package_index: &PackageIndex,
         constant_pool: &ClassIndexConstantPool,
-    ) -> AsciiString {
+    ) -> String {
         self.name_with_package(self.simple_class_name(constant_pool), package_index, constant_pool)
     }
 
     fn name_with_package(
         &self,
-        name: &AsciiStr,
+        name: &str,
         package_index: &PackageIndex,
         constant_pool: &ClassIndexConstantPool,
-    ) -> AsciiString {
+    ) -> String {
         let package_name = package_index
             .package_at(self.package_index)
             .package_name_with_parents(package_index, constant_pool);
 
         if package_name.is_empty() {
-            name.to_ascii_string()
+            name.to_string()
         } else {
-            package_name + unsafe { "/".as_ascii_str_unchecked() } + name
+            package_name + "/" + name
         }
     }
 
@@ -119,7 +166,7 @@ impl IndexedClass {
         *self.index.get().unwrap()
     }
 
-    pub(crate) fn set_index(&mut self, index: u32) {
+    pub(crate) fn set_index(&self, index: u32) {
         self.index.set(index).unwrap();
     }
 
@@ -135,16 +182,16 @@ impl IndexedClass {
         self.name_index
     }
 
-    pub fn class_name_start_index(&self) -> u8 {
+    pub fn class_name_start_index(&self) -> u16 {
         self.name_start_index
     }
 
     pub fn field_count(&self) -> u16 {
-        self.fields.get().unwrap().len() as u16
+        self.fields().len() as u16
     }
 
     pub fn method_count(&self) -> u16 {
-        self.methods.get().unwrap().len() as u16
+        self.methods().len() as u16
     }
 
     pub fn package_index(&self) -> u32 {
@@ -152,14 +199,17 @@ impl IndexedClass {
     }
 
     pub fn signature(&self) -> &IndexedClassSignature {
+        self.load_mapped_members();
         self.signature.get().unwrap()
     }
 
     pub fn enclosing_type_info(&self) -> Option<&IndexedEnclosingTypeInfo> {
+        self.load_mapped_members();
         self.enclosing_type_info.get()
     }
 
     pub fn fields(&self) -> &Vec<IndexedField> {
+        self.load_mapped_members();
         self.fields.get().unwrap()
     }
 
@@ -168,9 +218,156 @@ impl IndexedClass {
     }
 
     pub fn methods(&self) -> &Vec<IndexedMethod> {
+        self.load_mapped_members();
         self.methods.get().unwrap()
     }
 
+    pub fn annotations(&self) -> &[IndexedAnnotation] {
+        self.load_mapped_members();
+        self.annotations.get().map_or(&[], |a| a)
+    }
+
+    pub(crate) fn set_annotations(&self, annotations: Vec<IndexedAnnotation>) {
+        self.annotations.set(annotations).unwrap();
+    }
+
+    /// Returns the classes which are permitted to extend this sealed class. Permitted subclasses
+    /// which are not part of the index are not included.
+    pub fn permitted_subclasses(&self) -> &[u32] {
+        self.load_mapped_members();
+        self.permitted_subclasses.get().map_or(&[], |p| p)
+    }
+
+    pub(crate) fn set_permitted_subclasses(&self, classes: Vec<u32>) {
+        self.permitted_subclasses.set(classes).unwrap();
+    }
+
+    /// Returns true if this class has a `PermittedSubclasses` attribute
+    pub fn is_sealed(&self) -> bool {
+        !self.permitted_subclasses().is_empty()
+    }
+
+    /// Returns the components of this record in declaration order, or None if this class is not a
+    /// record
+    pub fn record_components(&self) -> Option<&Vec<IndexedRecordComponent>> {
+        self.load_mapped_members();
+        self.record_components.get().and_then(Option::as_ref)
+    }
+
+    pub(crate) fn set_record_components(&self, components: Option<Vec<IndexedRecordComponent>>) {
+        self.record_components.set(components).unwrap();
+    }
+
+    /// Returns the class from the `NestHost` attribute, or None if the attribute is missing or the
+    /// class is not part of the index. See [ClassIndex::nest_host_of] for the effective nest host.
+    pub fn nest_host(&self) -> Option<u32> {
+        self.load_mapped_members();
+        self.nest_host.get().copied().flatten()
+    }
+
+    pub(crate) fn set_nest_host(&self, nest_host: Option<u32>) {
+        self.nest_host.set(nest_host).unwrap();
+    }
+
+    /// Returns the classes from the `NestMembers` attribute which are part of the index
+    pub fn nest_members(&self) -> &[u32] {
+        self.load_mapped_members();
+        self.nest_members.get().map_or(&[], |m| m)
+    }
+
+    pub(crate) fn set_nest_members(&self, nest_members: Vec<u32>) {
+        self.nest_members.set(nest_members).unwrap();
+    }
+
+    /// Returns the references found in the code of each method, in the same order as
+    /// [IndexedClass::methods]. Empty if the index was created without references or no method
+    /// of this class contains a reference.
+    pub fn method_references(&self) -> &[Vec<IndexedReference>] {
+        self.load_mapped_members();
+        self.method_references.get().map_or(&[], |r| r)
+    }
+
+    pub(crate) fn set_method_references(&self, references: Vec<Vec<IndexedReference>>) {
+        self.method_references.set(references).unwrap();
+    }
+
+    /// Returns the constant pool indices of the distinct strings used by the code and the constant
+    /// fields of this class. Empty if the index was created without string literals.
+    pub fn string_literals(&self) -> &[u32] {
+        self.load_mapped_members();
+        self.string_literals.get().map_or(&[], |s| s)
+    }
+
+    pub(crate) fn set_string_literals(&self, literals: Vec<u32>) {
+        self.string_literals.set(literals).unwrap();
+    }
+
+    /// Returns the enum constants of this class in declaration order
+    pub fn enum_constants(&self) -> impl Iterator<Item = &IndexedField> {
+        self.fields()
+            .iter()
+            .filter(|f| f.access_flags() & FieldAccessFlags::ENUM.bits() != 0)
+    }
+
+    pub(crate) fn set_mapped_members(&mut self, members: MappedMembers) {
+        self.mapped_members = Some(members);
+    }
+
+    /// Allows validating the mapped members once they are loaded
+    pub(crate) fn set_member_bounds(&mut self, bounds: Arc<IndexBounds>) {
+        if let Some(members) = &mut self.mapped_members {
+            members.set_bounds(bounds);
+        }
+    }
+
+    /// Returns true if the members of this class were loaded lazily and have not been accessed yet
+    pub(crate) fn has_unloaded_members(&self) -> bool {
+        self.mapped_members.is_some() && self.member_load_result.get().is_none()
+    }
+
+    /// Loads the members of this class if they were not loaded yet. Fails if the members could
+    /// not be read from the index file, in which case all member accessors return empty values.
+    /// Queries of [ClassIndex] which use the members of other classes return this error instead.
+    pub fn load_members(&self) -> anyhow::Result<()> {
+        match self.load_mapped_members() {
+            Some(error) => Err(anyhow::anyhow!("{:#}", error)),
+            None => Ok(()),
+        }
+    }
+
+    fn load_mapped_members(&self) -> Option<&anyhow::Error> {
+        let mapped_members = self.mapped_members.as_ref()?;
+        self.member_load_result
+            .get_or_init(|| match mapped_members.read() {
+                Ok(members) => {
+                    self.set_members(members);
+                    None
+                }
+                Err(error) => {
+                    // Accessors can't fail, so the class is treated as if it had no members
+                    self.set_members(ClassMembers::empty());
+                    Some(error)
+                }
+            })
+            .as_ref()
+    }
+
+    fn set_members(&self, members: ClassMembers) {
+        self.set_signature(members.signature);
+        if let Some(info) = members.enclosing_type_info {
+            self.set_enclosing_type_info(info);
+        }
+        self.fields.set(members.fields).unwrap();
+        self.methods.set(members.methods).unwrap();
+        self.set_annotations(members.annotations);
+        self.set_permitted_subclasses(members.permitted_subclasses);
+        self.set_record_components(members.record_components);
+        self.set_nest_host(members.nest_host);
+        self.set_nest_members(members.nest_members);
+        self.set_method_references(members.method_references);
+        self.set_string_literals(members.string_literals);
+    }
+
     pub(crate) fn set_methods(
         &self,
         methods: Vec<IndexedMethod>,
@@ -185,13 +382,29 @@ impl IndexedClass {
     pub fn access_flags(&self) -> u16 {
         self.access_flags
     }
+
+    /// The major version of the class file this class was read from, e.g. 52 for Java 8
+    pub fn class_file_version(&self) -> u16 {
+        self.class_file_version
+    }
+
+    pub fn module_name_index(&self) -> Option<u32> {
+        self.module_name_index
+    }
+
+    pub fn source_index(&self) -> Option<u32> {
+        self.source_index
+    }
 }
 
-#[derive(Readable, Writable, Debug)]
+#[derive(Writable, Debug)]
 pub struct IndexedField {
     name_index: u32,
     access_flags: u16,
     field_signature: IndexedSignatureType,
+    annotations: Option<Box<Vec<IndexedAnnotation>>>,
+    /// The value of the ConstantValue attribute of a static field
+    constant_value: Option<Box<IndexedConstantValue>>,
 }
 
 impl IndexedField {
@@ -199,18 +412,26 @@ impl IndexedField {
         name_index: u32,
         access_flags: u16,
         field_signature: IndexedSignatureType,
+        annotations: Vec<IndexedAnnotation>,
+        constant_value: Option<IndexedConstantValue>,
     ) -> Self {
         Self {
             name_index,
             access_flags,
             field_signature,
+            annotations: Some(annotations).filter(|v| !v.is_empty()).map(Box::new),
+            constant_value: constant_value.map(Box::new),
         }
     }
 
-    pub fn field_name<'b>(&self, constant_pool: &'b ClassIndexConstantPool) -> &'b AsciiStr {
+    pub fn field_name<'b>(&self, constant_pool: &'b ClassIndexConstantPool) -> &'b str {
         constant_pool
             .string_view_at(self.name_index)
-            .into_ascii_str(constant_pool)
+            .into_str(constant_pool)
+    }
+
+    pub fn field_name_index(&self) -> u32 {
+        self.name_index
     }
 
     pub fn access_flags(&self) -> u16 {
@@ -220,13 +441,29 @@ impl IndexedField {
     pub fn field_signature(&self) -> &IndexedSignatureType {
         &self.field_signature
     }
+
+    pub fn annotations(&self) -> &[IndexedAnnotation] {
+        self.annotations.as_deref().map_or(&[], |a| a)
+    }
+
+    /// Returns the constant value of this field, if it is a `static final` field with a constant
+    /// initializer
+    pub fn constant_value(&self) -> Option<&IndexedConstantValue> {
+        self.constant_value.as_deref()
+    }
 }
 
-#[derive(Readable, Writable, Debug)]
+#[derive(Writable, Debug)]
 pub struct IndexedMethod {
     name_index: u32,
     access_flags: u16,
     method_signature: IndexedMethodSignature,
+    annotations: Option<Box<Vec<IndexedAnnotation>>>,
+    /// The annotations of each parameter, only present if at least one parameter is annotated
+    parameter_annotations: Option<Box<Vec<Vec<IndexedAnnotation>>>>,
+    /// The name of each parameter of the descriptor, or the empty string if it is unknown. Only
+    /// present if at least one name is known.
+    parameter_names: Option<Box<Vec<u32>>>,
 }
 
 impl IndexedMethod {
@@ -234,18 +471,28 @@ impl IndexedMethod {
         name_index: u32,
         access_flags: u16,
         method_signature: IndexedMethodSignature,
+        annotations: Vec<IndexedAnnotation>,
+        parameter_annotations: Vec<Vec<IndexedAnnotation>>,
+        parameter_names: Vec<u32>,
     ) -> Self {
         Self {
             name_index,
             access_flags,
             method_signature,
+            annotations: Some(annotations).filter(|v| !v.is_empty()).map(Box::new),
+            parameter_annotations: Some(parameter_annotations)
+                .filter(|v| v.iter().any(|a| !a.is_empty()))
+                .map(Box::new),
+            parameter_names: Some(parameter_names)
+                .filter(|v| !v.is_empty())
+                .map(Box::new),
         }
     }
 
-    pub fn method_name<'b>(&self, constant_pool: &'b ClassIndexConstantPool) -> &'b AsciiStr {
+    pub fn method_name<'b>(&self, constant_pool: &'b ClassIndexConstantPool) -> &'b str {
         constant_pool
             .string_view_at(self.name_index)
-            .into_ascii_str(constant_pool)
+            .into_str(constant_pool)
     }
 
     pub fn overrides(&self, base_method: &IndexedMethod) -> bool {
@@ -255,20 +502,9 @@ impl IndexedMethod {
         }
 
         self.name_index == base_method.name_index
-            && self.method_signature.parameter_count()
-                == base_method.method_signature.parameter_count()
             && self
                 .method_signature
-                .parameters()
-                .map(|a| {
-                    a.iter()
-                        // Safety: We know that the parameter count is equal, so we know that the
-                        // other method must have parameters
-                        .zip(base_method.method_signature.parameters().unwrap().iter())
-                        .all(|(a, b)| a.eq_erased(b))
-                })
-                // If we don't have any parameters, the count is 0, so we automatically match
-                .unwrap_or(true)
+                .eq_erased_parameters(&base_method.method_signature)
     }
 
     pub fn method_name_index(&self) -> u32 {
@@ -282,4 +518,60 @@ impl IndexedMethod {
     pub fn method_signature(&self) -> &IndexedMethodSignature {
         &self.method_signature
     }
+
+    pub fn annotations(&self) -> &[IndexedAnnotation] {
+        self.annotations.as_deref().map_or(&[], |a| a)
+    }
+
+    /// Returns the annotations of each parameter. Empty if no parameter is annotated.
+    pub fn parameter_annotations(&self) -> &[Vec<IndexedAnnotation>] {
+        self.parameter_annotations.as_deref().map_or(&[], |a| a)
+    }
+
+    /// Returns the constant pool index of the name of each parameter of the descriptor. Unknown
+    /// names are the empty string. Empty if no name is known.
+    pub fn parameter_name_indices(&self) -> &[u32] {
+        self.parameter_names.as_deref().map_or(&[], |n| n)
+    }
+
+    /// Returns the name of the parameter at the given index of the descriptor, if it is known
+    pub fn parameter_name<'b>(
+        &self,
+        constant_pool: &'b ClassIndexConstantPool,
+        index: usize,
+    ) -> Option<&'b str> {
+        self.parameter_name_indices()
+            .get(index)
+            .map(|i| constant_pool.string_view_at(*i).into_str(constant_pool))
+            .filter(|name| !name.is_empty())
+    }
+}
+
+#[derive(Readable, Writable, Debug)]
+pub struct IndexedRecordComponent {
+    name_index: u32,
+    signature: IndexedSignatureType,
+}
+
+impl IndexedRecordComponent {
+    pub(crate) fn new(name_index: u32, signature: IndexedSignatureType) -> Self {
+        Self {
+            name_index,
+            signature,
+        }
+    }
+
+    pub fn name<'b>(&self, constant_pool: &'b ClassIndexConstantPool) -> &'b str {
+        constant_pool
+            .string_view_at(self.name_index)
+            .into_str(constant_pool)
+    }
+
+    pub fn name_index(&self) -> u32 {
+        self.name_index
+    }
+
+    pub fn signature(&self) -> &IndexedSignatureType {
+        &self.signature
+    }
 }
@@ -1,168 +1,263 @@
-use anyhow::{anyhow, Result};
-use ascii::{AsciiChar, AsciiStr};
-use speedy::{Readable, Writable};
+use crate::io::IndexBytes;
+use anyhow::{anyhow, bail, Result};
+use once_cell::sync::OnceCell;
+use std::ops::Deref;
 
-#[derive(Readable, Writable)]
+/// Strings of at least this length are prefixed with this byte, followed by their length as a
+/// little endian u32
+const LONG_STRING_MARKER: u8 = u8::MAX;
+
+#[derive(Clone)]
 pub struct ClassIndexConstantPool {
-    string_data: Vec<u8>, //Holds Ascii Strings prefixed with their length
+    string_data: StringData, //Holds UTF-8 Strings prefixed with their length
+    /// Pools saved by older versions prefix every string with a single length byte, which makes
+    /// strings with a length of 255 ambiguous. Only set if the pool contains such a string.
+    legacy_lengths: bool,
+    /// A bit for every byte of the pool which is set if a valid string starts there. Only
+    /// computed when strings of an untrusted pool are checked.
+    string_starts: OnceCell<Vec<u64>>,
+}
+
+/// The strings of a loaded pool are usually not copied out of the index file
+#[derive(Clone)]
+enum StringData {
+    Owned(Vec<u8>),
+    Loaded(IndexBytes),
+}
+
+impl Deref for StringData {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            StringData::Owned(data) => data,
+            StringData::Loaded(data) => data,
+        }
+    }
 }
 
 impl ClassIndexConstantPool {
     pub(crate) fn new(capacity: u32) -> Self {
         Self {
-            string_data: Vec::with_capacity(capacity as usize),
+            string_data: StringData::Owned(Vec::with_capacity(capacity as usize)),
+            legacy_lengths: false,
+            string_starts: OnceCell::new(),
         }
     }
 
+    /// Creates a pool from saved bytes. `legacy_lengths` has to be set if the bytes were created
+    /// by [ClassIndexConstantPool::from_legacy_bytes] and contain a string with a length of 255.
+    pub(crate) fn from_bytes(data: IndexBytes, legacy_lengths: bool) -> Self {
+        Self {
+            string_data: StringData::Loaded(data),
+            legacy_lengths,
+            string_starts: OnceCell::new(),
+        }
+    }
+
+    /// Creates a pool from the bytes of a pool saved by an older version, where every string
+    /// was prefixed with a single length byte
+    pub(crate) fn from_legacy_bytes(data: IndexBytes) -> Self {
+        let mut index = 0;
+        let mut legacy_lengths = false;
+        while let Some(length) = data.get(index) {
+            if *length == LONG_STRING_MARKER {
+                legacy_lengths = true;
+                break;
+            }
+            index += 1 + *length as usize;
+        }
+
+        Self::from_bytes(data, legacy_lengths)
+    }
+
+    /// Returns `true` if this pool uses the string layout of older versions for strings with a
+    /// length of 255
+    pub(crate) fn has_legacy_lengths(&self) -> bool {
+        self.legacy_lengths
+    }
+
+    pub(crate) fn bytes(&self) -> &[u8] {
+        &self.string_data
+    }
+
     pub(crate) fn add_string(&mut self, str: &[u8]) -> Result<u32> {
-        let index = self.string_data.len();
-        let length = str.len();
-        if length > u8::MAX as usize {
-            return Err(anyhow!(
+        let string_data = match &mut self.string_data {
+            StringData::Owned(data) => data,
+            StringData::Loaded(_) => bail!("Cannot add strings to a loaded constant pool"),
+        };
+        let index = u32::try_from(string_data.len())
+            .map_err(|_| anyhow!("The constant pool exceeds the maximum size of {}", u32::MAX))?;
+        let length = u32::try_from(str.len()).map_err(|_| {
+            anyhow!(
                 "The string {} exceeds the maximum size of {}",
-                AsciiStr::from_ascii(str).unwrap(),
-                u8::MAX
-            ));
+                String::from_utf8_lossy(str),
+                u32::MAX
+            )
+        })?;
+
+        if length < LONG_STRING_MARKER as u32 {
+            string_data.try_reserve(1 + str.len())?;
+            string_data.push(length as u8);
+        } else {
+            string_data.try_reserve(5 + str.len())?;
+            string_data.push(LONG_STRING_MARKER);
+            string_data.extend_from_slice(&length.to_le_bytes());
+        }
+        string_data.extend_from_slice(str);
+        self.string_starts = OnceCell::new();
+
+        Ok(index)
+    }
+
+    /// Returns the start and length of the string at the given index, or `None` if its length
+    /// prefix is out of bounds
+    fn string_bounds(&self, index: u32) -> Option<(usize, usize)> {
+        let index = index as usize;
+        let length = *self.string_data.get(index)?;
+        if length != LONG_STRING_MARKER || self.legacy_lengths {
+            return Some((index + 1, length as usize));
+        }
+
+        let length = self.string_data.get(index + 1..index + 5)?;
+        Some((
+            index + 5,
+            u32::from_le_bytes(length.try_into().unwrap()) as usize,
+        ))
+    }
+
+    /// Checks that a valid string starts at the given index. Strings in pools which were not
+    /// created by [ClassIndexConstantPool::add_string] have to be checked before accessing
+    /// them. An index pointing into the middle of a string is rejected as well, even if the bytes
+    /// there happen to look like a string.
+    pub(crate) fn check_string_at(&self, index: u32) -> Result<()> {
+        let index = index as usize;
+        let is_start = self
+            .string_starts()
+            .get(index / 64)
+            .map_or(false, |bits| bits & (1 << (index % 64)) != 0);
+        if !is_start {
+            bail!("No valid string starts at constant pool index {}", index);
         }
 
-        self.string_data.try_reserve(1 + str.len())?;
-        self.string_data.push(length as u8);
-        self.string_data.extend_from_slice(str);
+        Ok(())
+    }
 
-        Ok(index as u32)
+    /// Walks over all strings of the pool once. The walk stops at the first string which exceeds
+    /// the pool, strings which are not valid UTF-8 are skipped.
+    fn string_starts(&self) -> &[u64] {
+        self.string_starts.get_or_init(|| {
+            let mut starts = vec![0u64; self.string_data.len() / 64 + 1];
+            let mut index = 0;
+            while let Some((start, length)) = self.string_bounds(index as u32) {
+                let bytes = match self.string_data.get(start..start + length) {
+                    Some(bytes) => bytes,
+                    None => break,
+                };
+                if std::str::from_utf8(bytes).is_ok() {
+                    starts[index / 64] |= 1 << (index % 64);
+                }
+                index = start + length;
+            }
+            starts
+        })
     }
 
     pub fn string_view_at(&self, index: u32) -> ConstantPoolStringView {
+        let (start, length) = self.string_bounds(index).unwrap();
         ConstantPoolStringView {
-            index,
-            end: 1 + /* Add the length */ self.string_data.get(index as usize).unwrap(),
+            start: start as u32,
+            length: length as u32,
         }
     }
 }
 
-#[derive(Debug, Eq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct ConstantPoolStringView {
-    index: u32,
-    end: u8,
-}
-
-impl PartialEq for ConstantPoolStringView {
-    fn eq(&self, other: &Self) -> bool {
-        self.index == other.index && self.end == other.end
-    }
+    /// The index of the first byte after the length prefix
+    start: u32,
+    length: u32,
 }
 
 impl ConstantPoolStringView {
-    pub fn into_ascii_str(self, constant_pool: &ClassIndexConstantPool) -> &AsciiStr {
+    pub fn into_str(self, constant_pool: &ClassIndexConstantPool) -> &str {
         unsafe {
-            AsciiStr::from_ascii_unchecked(
-                &constant_pool.string_data[(self.index + 1) as usize..][..(self.end - 1) as usize],
+            std::str::from_utf8_unchecked(
+                &constant_pool.string_data[self.start as usize..][..self.length as usize],
             )
         }
     }
 
     pub fn is_empty(&self) -> bool {
-        self.end as u32 == self.index + 1
+        self.length == 0
     }
 
-    pub fn byte_at(&self, constant_pool: &ClassIndexConstantPool, index: u8) -> u8 {
+    pub fn byte_at(&self, constant_pool: &ClassIndexConstantPool, index: u32) -> u8 {
         *constant_pool
             .string_data
-            .get(self.index as usize + 1 + index as usize)
+            .get(self.start as usize + index as usize)
             .unwrap()
     }
 
     pub fn starts_with(
         &self,
         constant_pool: &ClassIndexConstantPool,
-        other: &AsciiStr,
+        other: &str,
         match_mode: MatchMode,
     ) -> bool {
         self.starts_with_at(constant_pool, other, 0, match_mode)
     }
 
+    /// Checks if the part of this string starting at the byte index `start_index` starts with
+    /// `other`
     pub fn starts_with_at(
         &self,
         constant_pool: &ClassIndexConstantPool,
-        other: &AsciiStr,
-        start_index: u8,
+        other: &str,
+        start_index: u32,
         match_mode: MatchMode,
     ) -> bool {
-        //Checks if the operation is possible
-        if (start_index as usize + other.len()) > self.len() as usize {
-            return false;
-        }
-        //Every string starts with empty string
-        if other.is_empty() {
-            return true;
-        }
-
-        let mut start = start_index;
-        let offset = start;
-        let end = start_index + other.len() as u8;
-        let ignore_case = match match_mode {
-            MatchMode::MatchCaseFirstCharOnly => {
-                //If the first char is not the same, then it is not the same
-                if self.byte_at(constant_pool, start_index) != other[0] {
-                    return false;
-                }
-
-                //We don't want to check the first char again
-                start += 1;
-                true
-            }
-            MatchMode::MatchCase => false,
-            MatchMode::IgnoreCase => true,
-        };
-
-        for i in start..end {
-            let current_byte = self.byte_at(constant_pool, i);
-            let current_char = other[(i - offset) as usize];
-            if current_byte != current_char
-                && (!ignore_case || current_byte != switch_ascii_char_case(current_char))
-            {
-                return false;
-            }
+        match self.into_str(constant_pool).get(start_index as usize..) {
+            Some(str) => matches_prefix(str, other, match_mode),
+            None => false,
         }
-
-        true
     }
 
-    /// Searches for the given `query` using the given `options` and returns the
-    /// matched position of there is one.
+    /// Searches for the given `query` using the given `options` and returns the rank of the match
+    /// if there is one, where lower ranks are better matches. For [SearchMode::Prefix] and
+    /// [SearchMode::Contains], the rank is the matched byte position.
     pub fn search(
         &self,
         constant_pool: &ClassIndexConstantPool,
-        query: &AsciiStr,
+        query: &str,
         options: SearchOptions,
     ) -> Option<usize> {
+        let str = self.into_str(constant_pool);
         match options.search_mode {
             SearchMode::Prefix => {
-                if self.starts_with(constant_pool, query, options.match_mode) {
+                if matches_prefix(str, query, options.match_mode) {
                     Some(0)
                 } else {
                     None
                 }
             }
             SearchMode::Contains => {
-                //We need to do this check because we cast the query length to a u8
-                if query.len() > self.len() as usize {
-                    return None;
-                }
-
-                for i in 0..=(self.len() - query.len() as u8) {
-                    if self.starts_with_at(constant_pool, query, i, options.match_mode) {
-                        return Some(i as usize);
-                    }
+                if let MatchMode::MatchCase = options.match_mode {
+                    return str.find(query);
                 }
 
-                None
+                str.char_indices()
+                    .map(|(i, _)| i)
+                    .chain(std::iter::once(str.len()))
+                    .find(|i| matches_prefix(&str[*i..], query, options.match_mode))
             }
+            SearchMode::CamelCase => matches_camel_case(str, query, options.match_mode),
+            SearchMode::Fuzzy => matches_fuzzy(str, query, options.match_mode),
         }
     }
 
-    pub fn len(&self) -> u8 {
-        self.end - 1
+    pub fn len(&self) -> u32 {
+        self.length
     }
 }
 
@@ -170,6 +265,12 @@ impl ConstantPoolStringView {
 pub enum SearchMode {
     Prefix,
     Contains,
+    /// Every upper case char of the query starts a part which has to match the start of a word,
+    /// e.g. `HMap` matches `HashMap`. The parts have to occur in order, but words may be skipped.
+    CamelCase,
+    /// The query has to match the start of the string with a few typos, e.g. `ArrayLsit` matches
+    /// `ArrayList`. See [max_fuzzy_edits] for how many edits are tolerated.
+    Fuzzy,
 }
 
 #[derive(Clone, Copy)]
@@ -186,6 +287,13 @@ pub struct SearchOptions {
     pub match_mode: MatchMode,
 }
 
+/// A value found by a search and the rank of the match, where lower scores are better matches
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SearchResult<T> {
+    pub score: usize,
+    pub value: T,
+}
+
 impl Default for SearchOptions {
     fn default() -> Self {
         SearchOptions {
@@ -196,10 +304,304 @@ impl Default for SearchOptions {
     }
 }
 
-fn switch_ascii_char_case(char: AsciiChar) -> AsciiChar {
-    if char.is_uppercase() {
-        char.to_ascii_lowercase()
-    } else {
-        char.to_ascii_uppercase()
+/// Checks if `str` starts with `prefix`. Chars are compared by their simple case mapping if the
+/// case should be ignored.
+fn matches_prefix(str: &str, prefix: &str, match_mode: MatchMode) -> bool {
+    let mut chars = str.chars();
+    let mut prefix_chars = prefix.chars();
+    match match_mode {
+        MatchMode::MatchCase => return str.starts_with(prefix),
+        MatchMode::MatchCaseFirstCharOnly => {
+            //If the first char is not the same, then it is not the same
+            if let Some(first_char) = prefix_chars.next() {
+                if chars.next() != Some(first_char) {
+                    return false;
+                }
+            }
+        }
+        MatchMode::IgnoreCase => {}
+    }
+
+    prefix_chars.all(|p| chars.next().map_or(false, |c| chars_eq_ignore_case(c, p)))
+}
+
+/// Matches the parts of `query` against the starts of the words in `str`, see
+/// [SearchMode::CamelCase]. Returns a rank which is lower the fewer words are left unmatched, with
+/// matches at the start of `str` being ranked better.
+fn matches_camel_case(str: &str, query: &str, match_mode: MatchMode) -> Option<usize> {
+    let word_starts = camel_case_word_starts(str);
+    let mut parts = Vec::new();
+    let mut previous: Option<char> = None;
+    for (i, c) in query.char_indices() {
+        let is_start = match previous {
+            None => true,
+            Some(previous) => {
+                c.is_uppercase() || (c.is_ascii_digit() && !previous.is_ascii_digit())
+            }
+        };
+        if is_start {
+            parts.push(i);
+        }
+        previous = Some(c);
+    }
+
+    let mut matched_words = 0;
+    let mut matches_first_word = false;
+    let mut position = 0;
+    for (part_index, part_start) in parts.iter().enumerate() {
+        let part = &query[*part_start..parts.get(part_index + 1).map_or(query.len(), |i| *i)];
+        let matches_at = |start: usize| {
+            str.get(start..)
+                .map_or(false, |rest| matches_prefix(rest, part, match_mode))
+        };
+        // A part may continue the previous one, e.g. `URLC` matches `URLConnection`. Otherwise,
+        // the first matching word leaves the most words for the next parts.
+        let start = if matches_at(position) {
+            position
+        } else {
+            *word_starts
+                .iter()
+                .find(|start| **start >= position && matches_at(**start))?
+        };
+        matches_first_word |= start == 0;
+
+        position = start + part.len();
+        matched_words += word_starts
+            .iter()
+            .filter(|word_start| (start..position).contains(*word_start))
+            .count();
+    }
+
+    let unmatched_words = word_starts.len() - matched_words;
+    Some(unmatched_words * 2 + usize::from(!matches_first_word))
+}
+
+/// Returns the number of edits which [SearchMode::Fuzzy] tolerates for a query with the given
+/// number of chars. Short queries would match almost anything otherwise.
+pub fn max_fuzzy_edits(query_length: usize) -> usize {
+    match query_length {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    }
+}
+
+/// Computes the smallest edit distance between `query` and a prefix of `str`, where inserting,
+/// deleting or replacing a char and swapping two adjacent chars each count as one edit. Returns a
+/// rank which is lower the fewer edits are needed, with matches of the whole `str` being ranked
+/// better, or `None` if more than [max_fuzzy_edits] edits are needed.
+fn matches_fuzzy(str: &str, query: &str, match_mode: MatchMode) -> Option<usize> {
+    let query = query.chars().collect::<Vec<_>>();
+    let max_edits = max_fuzzy_edits(query.len());
+    let chars_eq = |query_index: usize, c: char| match match_mode {
+        MatchMode::MatchCase => query[query_index] == c,
+        MatchMode::MatchCaseFirstCharOnly if query_index == 0 => query[query_index] == c,
+        _ => chars_eq_ignore_case(query[query_index], c),
+    };
+
+    // Each row holds the distances between all prefixes of the query and the prefix of `str`
+    // which ends at the current char
+    let mut second_previous_row = Vec::new();
+    let mut previous_row = (0..=query.len()).collect::<Vec<_>>();
+    let mut min_distance = query.len();
+    let mut full_distance = query.len();
+    let mut previous_char = None;
+    for (i, c) in str.chars().enumerate() {
+        let mut row = Vec::with_capacity(query.len() + 1);
+        row.push(i + 1);
+        for j in 1..=query.len() {
+            let cost = usize::from(!chars_eq(j - 1, c));
+            let mut distance = (previous_row[j] + 1)
+                .min(row[j - 1] + 1)
+                .min(previous_row[j - 1] + cost);
+            if j > 1 && previous_char.map_or(false, |p| chars_eq(j - 1, p)) && chars_eq(j - 2, c) {
+                distance = distance.min(second_previous_row[j - 2] + 1);
+            }
+            row.push(distance);
+        }
+
+        full_distance = row[query.len()];
+        min_distance = min_distance.min(full_distance);
+        // Longer prefixes can't get closer to the query anymore
+        if row.iter().all(|distance| *distance > max_edits) {
+            full_distance = usize::MAX;
+            break;
+        }
+
+        second_previous_row = std::mem::replace(&mut previous_row, row);
+        previous_char = Some(c);
+    }
+
+    if min_distance > max_edits {
+        return None;
+    }
+
+    Some(min_distance * 2 + usize::from(full_distance != min_distance))
+}
+
+/// Returns the byte positions at which the words of a camel case name start. Words also start
+/// after `_` and `$`, and at digits.
+fn camel_case_word_starts(str: &str) -> Vec<usize> {
+    let mut starts = Vec::new();
+    let mut chars = str.char_indices().peekable();
+    let mut previous: Option<char> = None;
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|(_, c)| *c);
+        let is_start = match previous {
+            None => true,
+            Some(previous) if c.is_uppercase() => {
+                !previous.is_uppercase() || next.map_or(false, |n| n.is_lowercase())
+            }
+            Some(previous) if c.is_ascii_digit() => !previous.is_ascii_digit(),
+            Some(previous) => previous == '_' || previous == '$',
+        };
+        if is_start && c != '_' && c != '$' {
+            starts.push(i);
+        }
+        previous = Some(c);
+    }
+
+    starts
+}
+
+pub(crate) fn chars_eq_ignore_case(a: char, b: char) -> bool {
+    if a == b {
+        return true;
+    }
+    if a.is_ascii() && b.is_ascii() {
+        return a.eq_ignore_ascii_case(&b);
+    }
+
+    // Some non ASCII chars, like the Kelvin sign, map to ASCII chars
+    a.to_lowercase().eq(b.to_lowercase()) || a.to_uppercase().eq(b.to_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClassIndexConstantPool, MatchMode, SearchOptions};
+    use crate::io::IndexBytes;
+
+    #[test]
+    fn test_long_strings() {
+        let long_name = "a".repeat(300) + "Name";
+        let mut constant_pool = ClassIndexConstantPool::new(16);
+        let short_index = constant_pool.add_string(b"Short").unwrap();
+        let long_index = constant_pool.add_string(long_name.as_bytes()).unwrap();
+        let boundary_index = constant_pool.add_string(&[b'b'; 255]).unwrap();
+
+        let view = constant_pool.string_view_at(long_index);
+        assert_eq!(view.len(), 304);
+        assert_eq!(view.into_str(&constant_pool), long_name.as_str());
+        assert_eq!(
+            constant_pool.string_view_at(long_index).search(
+                &constant_pool,
+                "name",
+                SearchOptions {
+                    search_mode: super::SearchMode::Contains,
+                    ..Default::default()
+                }
+            ),
+            Some(300)
+        );
+        assert_eq!(constant_pool.string_view_at(boundary_index).len(), 255);
+        assert_eq!(constant_pool.string_view_at(short_index).len(), 5);
+        assert!(constant_pool.check_string_at(boundary_index).is_ok());
+        assert!(constant_pool.check_string_at(long_index).is_ok());
+        assert!(constant_pool.check_string_at(long_index + 1).is_err());
+
+        // The bytes after the length prefix look like a string of length 1
+        let nested_index = constant_pool.add_string(b"\x01a").unwrap();
+        assert!(constant_pool.check_string_at(nested_index).is_ok());
+        assert!(constant_pool.check_string_at(nested_index + 1).is_err());
+        let loaded = ClassIndexConstantPool::from_bytes(
+            IndexBytes::owned(constant_pool.bytes().to_vec()),
+            false,
+        );
+        assert!(loaded.check_string_at(nested_index).is_ok());
+        assert!(loaded.check_string_at(nested_index + 1).is_err());
+        assert!(loaded.check_string_at(loaded.bytes().len() as u32).is_err());
+
+        let legacy = ClassIndexConstantPool::from_legacy_bytes(IndexBytes::owned(
+            [&[255][..], &[b'a'; 255]].concat(),
+        ));
+        assert!(legacy.has_legacy_lengths());
+        assert_eq!(legacy.string_view_at(0).len(), 255);
+        assert!(legacy.check_string_at(0).is_ok());
+        assert!(legacy.check_string_at(1).is_err());
+    }
+
+    #[test]
+    fn test_unicode_search() {
+        let mut constant_pool = ClassIndexConstantPool::new(16);
+        let index = constant_pool
+            .add_string("ÄpfelTest$`sorts Überschriften`".as_bytes())
+            .unwrap();
+        let view = constant_pool.string_view_at(index);
+        let contains = SearchOptions {
+            search_mode: super::SearchMode::Contains,
+            ..Default::default()
+        };
+
+        assert!(view.starts_with(&constant_pool, "äPFEL", MatchMode::IgnoreCase));
+        assert!(!view.starts_with(&constant_pool, "äpfel", MatchMode::MatchCaseFirstCharOnly));
+        assert!(view.starts_with(&constant_pool, "ÄPFEL", MatchMode::MatchCaseFirstCharOnly));
+        assert_eq!(
+            view.search(&constant_pool, "überschriften", contains),
+            Some(18)
+        );
+        assert_eq!(view.search(&constant_pool, "test$`", contains), Some(6));
+        // The Kelvin sign is the upper case of an ASCII char
+        assert!(super::chars_eq_ignore_case('\u{212A}', 'k'));
+    }
+
+    #[test]
+    fn test_camel_case_search() {
+        let mut constant_pool = ClassIndexConstantPool::new(64);
+        let mut search = |str: &str, query| {
+            let index = constant_pool.add_string(str.as_bytes()).unwrap();
+            constant_pool.string_view_at(index).search(
+                &constant_pool,
+                query,
+                SearchOptions {
+                    search_mode: super::SearchMode::CamelCase,
+                    match_mode: MatchMode::MatchCase,
+                    ..Default::default()
+                },
+            )
+        };
+
+        assert_eq!(search("HashMap", "HMap"), Some(0));
+        assert_eq!(search("ConcurrentHashMap", "CHM"), Some(0));
+        assert_eq!(search("ConcurrentHashMap", "HMap"), Some(3));
+        assert_eq!(search("HashMapEntry", "HMap"), Some(2));
+        assert_eq!(search("URLConnection", "URLC"), Some(0));
+        assert_eq!(search("Map$Entry", "ME"), Some(0));
+        assert_eq!(search("HashMap", "MH"), None);
+        assert_eq!(search("HashMap", "HMp"), None);
+    }
+
+    #[test]
+    fn test_fuzzy_search() {
+        let mut constant_pool = ClassIndexConstantPool::new(64);
+        let mut search = |str: &str, query| {
+            let index = constant_pool.add_string(str.as_bytes()).unwrap();
+            constant_pool.string_view_at(index).search(
+                &constant_pool,
+                query,
+                SearchOptions {
+                    search_mode: super::SearchMode::Fuzzy,
+                    ..Default::default()
+                },
+            )
+        };
+
+        assert_eq!(search("ArrayList", "arraylist"), Some(0));
+        assert_eq!(search("ArrayList", "ArrayLsit"), Some(2));
+        assert_eq!(search("ArrayListSpliterator", "ArrayLsit"), Some(3));
+        assert_eq!(search("ArrayList", "ArayLsit"), Some(4));
+        assert_eq!(search("ArrayList", "AraLsit"), None);
+        assert_eq!(search("List", "Lst"), Some(2));
+        assert_eq!(search("Map", "Mpa"), Some(2));
+        assert_eq!(search("Map", "Xa"), None);
     }
 }
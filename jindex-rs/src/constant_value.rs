@@ -0,0 +1,160 @@
+use crate::builder::{get_index_from_pool, ClassToIndexMap};
+use crate::class_index::ClassIndex;
+use crate::constant_pool::ClassIndexConstantPool;
+use crate::signature::indexed_signature::{ToIndexedType, ToRawType};
+use crate::validation::{IndexBounds, Validate};
+use cafebabe::constant_pool::LiteralConstant;
+use compact_str::{CompactString, ToCompactString};
+use rustc_hash::FxHashMap;
+use std::borrow::Cow;
+
+/// The value of a `static final` field with a constant initializer
+#[derive(Debug, PartialEq)]
+pub enum ConstantValue<T> {
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(T),
+}
+
+pub type RawConstantValue = ConstantValue<CompactString>;
+pub type IndexedConstantValue = ConstantValue<u32>;
+
+impl RawConstantValue {
+    /// Converts the value of a ConstantValue attribute. Returns None for strings which are not
+    /// valid modified UTF-8.
+    pub(crate) fn from_class_file(constant: &LiteralConstant) -> Option<Self> {
+        Some(match constant {
+            LiteralConstant::Integer(v) => ConstantValue::Int(*v),
+            LiteralConstant::Long(v) => ConstantValue::Long(*v),
+            LiteralConstant::Float(v) => ConstantValue::Float(*v),
+            LiteralConstant::Double(v) => ConstantValue::Double(*v),
+            LiteralConstant::String(v) => ConstantValue::String(v.to_compact_string()),
+            LiteralConstant::StringBytes(_) => return None,
+        })
+    }
+
+    /// Returns true if the given value has the same type and value. Floating point values are
+    /// compared by their bits, which allows finding NaN constants.
+    pub fn matches(
+        &self,
+        constant_pool: &ClassIndexConstantPool,
+        value: &IndexedConstantValue,
+    ) -> bool {
+        match (self, value) {
+            (ConstantValue::Int(a), ConstantValue::Int(b)) => a == b,
+            (ConstantValue::Long(a), ConstantValue::Long(b)) => a == b,
+            (ConstantValue::Float(a), ConstantValue::Float(b)) => a.to_bits() == b.to_bits(),
+            (ConstantValue::Double(a), ConstantValue::Double(b)) => a.to_bits() == b.to_bits(),
+            (ConstantValue::String(a), ConstantValue::String(b)) => {
+                constant_pool.string_view_at(*b).into_str(constant_pool) == a.as_str()
+            }
+            _ => false,
+        }
+    }
+}
+
+impl ToIndexedType for RawConstantValue {
+    type Out = IndexedConstantValue;
+
+    fn to_indexed_type<'a>(
+        &'a self,
+        constant_pool: &mut ClassIndexConstantPool,
+        constant_pool_map: &mut FxHashMap<Cow<'a, str>, u32>,
+        _class_to_index_map: &ClassToIndexMap,
+    ) -> anyhow::Result<Self::Out> {
+        Ok(match self {
+            ConstantValue::Int(v) => ConstantValue::Int(*v),
+            ConstantValue::Long(v) => ConstantValue::Long(*v),
+            ConstantValue::Float(v) => ConstantValue::Float(*v),
+            ConstantValue::Double(v) => ConstantValue::Double(*v),
+            ConstantValue::String(v) => {
+                ConstantValue::String(get_index_from_pool(v, constant_pool_map, constant_pool)?)
+            }
+        })
+    }
+}
+
+impl ToRawType for IndexedConstantValue {
+    type Out = RawConstantValue;
+
+    fn to_raw_type(&self, class_index: &ClassIndex) -> Self::Out {
+        match self {
+            ConstantValue::Int(v) => ConstantValue::Int(*v),
+            ConstantValue::Long(v) => ConstantValue::Long(*v),
+            ConstantValue::Float(v) => ConstantValue::Float(*v),
+            ConstantValue::Double(v) => ConstantValue::Double(*v),
+            ConstantValue::String(v) => {
+                let constant_pool = class_index.constant_pool();
+                ConstantValue::String(
+                    constant_pool
+                        .string_view_at(*v)
+                        .into_str(constant_pool)
+                        .to_compact_string(),
+                )
+            }
+        }
+    }
+}
+
+impl Validate for IndexedConstantValue {
+    fn validate(&self, bounds: &IndexBounds) -> anyhow::Result<()> {
+        match self {
+            ConstantValue::String(v) => bounds.check_string(*v),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConstantValue;
+    use crate::builder::incremental::to_class_info;
+    use crate::builder::workers::{create_class_index_from_infos, DuplicateClassPolicy};
+    use crate::builder::FieldInfo;
+    use crate::signature::RawSignatureType;
+    use crate::test::class_info;
+    use cafebabe::FieldAccessFlags;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_constant_values() {
+        let mut constants = class_info("a/Constants", "Ljava/lang/Object;", "()V");
+        for (name, value) in [
+            ("INT", ConstantValue::Int(42)),
+            ("LONG", ConstantValue::Long(42)),
+            ("KEY", ConstantValue::String("key".into())),
+        ] {
+            constants.fields.push(FieldInfo {
+                field_name: name.into(),
+                descriptor: RawSignatureType::from_str("I").unwrap(),
+                access_flags: FieldAccessFlags::STATIC | FieldAccessFlags::FINAL,
+                annotations: Vec::new(),
+                constant_value: Some(value),
+            });
+        }
+        let (_, class_index) =
+            create_class_index_from_infos(vec![constants], DuplicateClassPolicy::First).unwrap();
+
+        let constant_pool = class_index.constant_pool();
+        let find = |value| {
+            class_index
+                .find_fields_with_constant(&value)
+                .unwrap()
+                .into_iter()
+                .map(|(_, field)| field.field_name(constant_pool))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(find(ConstantValue::Int(42)), vec!["INT"]);
+        assert_eq!(find(ConstantValue::Long(42)), vec!["LONG"]);
+        assert_eq!(find(ConstantValue::String("key".into())), vec!["KEY"]);
+        assert!(find(ConstantValue::Int(7)).is_empty());
+
+        let class_info = to_class_info(&class_index.classes()[0], &class_index, None);
+        assert_eq!(
+            class_info.fields[2].constant_value,
+            Some(ConstantValue::String("key".into()))
+        );
+    }
+}
@@ -1,29 +1,433 @@
+use std::borrow::Cow;
+use std::fs;
 use std::fs::OpenOptions;
-use std::io::{Read, Write};
+use std::io::{Cursor, Read, Write};
+use std::ops::{Deref, Range};
+use std::sync::Arc;
 use std::time::Instant;
 
+use crate::annotation::{AnnotationValue, IndexedAnnotation, IndexedAnnotationValue};
+use crate::builder::workers::ReadOptions;
 use crate::builder::BuildTimeInfo;
 use crate::class_index::ClassIndex;
-use crate::class_index_members::IndexedClass;
-use crate::package_index::IndexedPackage;
-use anyhow::Context as AnyhowContext;
-use speedy::{Context, Readable, Reader, Writable, Writer};
+use crate::class_index_members::{
+    IndexedClass, IndexedField, IndexedMethod, IndexedRecordComponent,
+};
+use crate::constant_pool::ClassIndexConstantPool;
+use crate::constant_value::{ConstantValue, IndexedConstantValue};
+use crate::package_index::{IndexedPackage, PackageIndex};
+use crate::reference::IndexedReference;
+use crate::validation::{validate_class_index, IndexBounds, Validate};
+use anyhow::{anyhow, bail, Context as AnyhowContext};
+use memmap2::Mmap;
+use speedy::{Context, Endianness, Readable, Reader, Writable, Writer};
 use zip::write::FileOptions;
-use zip::{ZipArchive, ZipWriter};
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
 
-use crate::signature::{IndexedEnclosingTypeInfo, IndexedMethodSignature, IndexedSignatureType};
+use crate::signature::{
+    IndexedClassSignature, IndexedEnclosingTypeInfo, IndexedMethodSignature, IndexedSignatureType,
+};
 
-pub fn load_class_index_from_file(path: String) -> anyhow::Result<(BuildTimeInfo, ClassIndex)> {
-    let now = Instant::now();
-    let mut archive = ZipArchive::new(OpenOptions::new().read(true).open(path)?)?;
+/// Name of the zip entry which describes the format of the other entries
+const HEADER_ENTRY_NAME: &str = "header";
+/// Name of the zip entry which stores the serialized [ClassIndex]
+const INDEX_ENTRY_NAME: &str = "index";
+/// Name of the zip entry which stores the strings of the constant pool
+const CONSTANT_POOL_ENTRY_NAME: &str = "constant_pool";
+/// Name of the zip entry which stores the signatures, fields and methods of all classes
+const MEMBERS_ENTRY_NAME: &str = "members";
+/// Name of the zip entry which stores the fingerprints of all sources next to the index
+const SOURCES_ENTRY_NAME: &str = "sources";
+
+/// "JIDX" in ASCII
+const INDEX_FILE_MAGIC: u32 = 0x4A49_4458;
+/// The version of the format used when saving. Has to be incremented every time the serialized
+/// representation of the index changes.
+const FORMAT_VERSION: u32 = 1;
+/// Files without a header were saved by jindex-rs 0.0.x. These are migrated when loading.
+const LEGACY_FORMAT_VERSION: u32 = 0;
+
+#[derive(Readable, Writable, Debug)]
+struct IndexFileHeader {
+    magic: u32,
+    format_version: u32,
+    /// The version of jindex-rs which saved the file, only used for error messages
+    library_version: String,
+    /// The CRC32 checksum of the serialized index
+    index_checksum: u32,
+    /// The CRC32 checksum of the constant pool
+    constant_pool_checksum: u32,
+    /// The codec used for all other entries
+    codec: IndexCodec,
+}
+
+impl IndexFileHeader {
+    fn current(serialized_index: &[u8], constant_pool: &[u8], codec: IndexCodec) -> Self {
+        Self {
+            magic: INDEX_FILE_MAGIC,
+            format_version: FORMAT_VERSION,
+            library_version: env!("CARGO_PKG_VERSION").to_string(),
+            index_checksum: crc32fast::hash(serialized_index),
+            constant_pool_checksum: crc32fast::hash(constant_pool),
+            codec,
+        }
+    }
+
+    /// Returns an error if the file cannot be loaded by this version of jindex-rs
+    fn check_compatibility(&self) -> anyhow::Result<()> {
+        if self.magic != INDEX_FILE_MAGIC {
+            bail!(
+                "Not a class index file, invalid magic number {:#X}",
+                self.magic
+            );
+        }
+        if self.format_version != FORMAT_VERSION {
+            bail!(
+                "Incompatible index version {} written by jindex-rs {}, expected version {}",
+                self.format_version,
+                self.library_version,
+                FORMAT_VERSION
+            );
+        }
+        self.codec.check_supported()
+    }
+}
+
+/// How the entries of a saved index are compressed
+#[derive(Readable, Writable, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IndexCodec {
+    /// No compression. Only files saved without compression can be memory mapped, which makes
+    /// loading them the fastest.
+    Stored,
+    /// Deflate with a level from 0 to 9, or the default level of 6
+    Deflate(Option<i32>),
+    /// Zstandard with a level from -7 to 22, or the default level of 3. Requires the `zstd`
+    /// feature.
+    Zstd(Option<i32>),
+    /// LZ4 block compression. Requires the `lz4` feature.
+    Lz4,
+}
+
+impl Default for IndexCodec {
+    fn default() -> Self {
+        IndexCodec::Deflate(None)
+    }
+}
+
+impl IndexCodec {
+    fn check_supported(self) -> anyhow::Result<()> {
+        match self {
+            IndexCodec::Zstd(_) if !cfg!(feature = "zstd") => {
+                bail!("Zstandard compression requires the zstd feature")
+            }
+            IndexCodec::Lz4 if !cfg!(feature = "lz4") => {
+                bail!("LZ4 compression requires the lz4 feature")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn file_options(self) -> FileOptions {
+        let options = FileOptions::default();
+        match self {
+            IndexCodec::Stored | IndexCodec::Lz4 => {
+                options.compression_method(CompressionMethod::Stored)
+            }
+            IndexCodec::Deflate(level) => options
+                .compression_method(CompressionMethod::Deflated)
+                .compression_level(level),
+            #[cfg(feature = "zstd")]
+            IndexCodec::Zstd(level) => options
+                .compression_method(CompressionMethod::Zstd)
+                .compression_level(level),
+            #[cfg(not(feature = "zstd"))]
+            IndexCodec::Zstd(_) => unreachable!(),
+        }
+    }
+
+    /// Compresses data which is not compressed by the zip file itself
+    fn encode<'a>(self, data: &'a [u8]) -> Cow<'a, [u8]> {
+        match self {
+            #[cfg(feature = "lz4")]
+            IndexCodec::Lz4 => Cow::Owned(lz4_flex::compress_prepend_size(data)),
+            _ => Cow::Borrowed(data),
+        }
+    }
+
+    fn decode(self, data: IndexBytes) -> anyhow::Result<IndexBytes> {
+        match self {
+            #[cfg(feature = "lz4")]
+            IndexCodec::Lz4 => Ok(IndexBytes::owned(
+                lz4_flex::decompress_size_prepended(&data)
+                    .with_context(|| "Failed to decompress LZ4 data")?,
+            )),
+            _ => Ok(data),
+        }
+    }
+}
+
+/// Bytes of a loaded index file. Entries which are stored without compression are memory mapped,
+/// which means they are only read from the disk when they are accessed.
+#[derive(Clone)]
+pub struct IndexBytes {
+    source: Arc<IndexBytesSource>,
+    range: Range<usize>,
+}
+
+enum IndexBytesSource {
+    Mapped(Mmap),
+    Owned(Vec<u8>),
+}
+
+impl IndexBytes {
+    pub(crate) fn owned(data: Vec<u8>) -> Self {
+        let range = 0..data.len();
+        Self {
+            source: Arc::new(IndexBytesSource::Owned(data)),
+            range,
+        }
+    }
+
+    /// Returns a part of these bytes, or None if the range is out of bounds
+    fn slice(&self, range: Range<usize>) -> Option<Self> {
+        if range.start > range.end || range.end > self.len() {
+            return None;
+        }
+
+        Some(Self {
+            source: self.source.clone(),
+            range: self.range.start + range.start..self.range.start + range.end,
+        })
+    }
+}
+
+impl Deref for IndexBytesSource {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            IndexBytesSource::Mapped(map) => map,
+            IndexBytesSource::Owned(data) => data,
+        }
+    }
+}
+
+impl Deref for IndexBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.source[self.range.clone()]
+    }
+}
+
+/// The part of the members entry which stores the members of a single class
+pub(crate) struct MappedMembers {
+    data: IndexBytes,
+    checksum: u32,
+    /// Set once the rest of the index was validated
+    bounds: Option<Arc<IndexBounds>>,
+}
+
+/// Everything of a class which is only loaded once it is accessed
+pub(crate) struct ClassMembers {
+    pub(crate) signature: IndexedClassSignature,
+    pub(crate) enclosing_type_info: Option<IndexedEnclosingTypeInfo>,
+    pub(crate) fields: Vec<IndexedField>,
+    pub(crate) methods: Vec<IndexedMethod>,
+    pub(crate) annotations: Vec<IndexedAnnotation>,
+    pub(crate) permitted_subclasses: Vec<u32>,
+    pub(crate) record_components: Option<Vec<IndexedRecordComponent>>,
+    pub(crate) nest_host: Option<u32>,
+    pub(crate) nest_members: Vec<u32>,
+    pub(crate) method_references: Vec<Vec<IndexedReference>>,
+    pub(crate) string_literals: Vec<u32>,
+}
+
+impl MappedMembers {
+    pub(crate) fn set_bounds(&mut self, bounds: Arc<IndexBounds>) {
+        self.bounds = Some(bounds);
+    }
+
+    /// Deserializes the members and checks that their references are in bounds
+    pub(crate) fn read(&self) -> anyhow::Result<ClassMembers> {
+        if crc32fast::hash(&self.data) != self.checksum {
+            bail!("Class members checksum mismatch");
+        }
+
+        let context = IndexReadContext {
+            format_version: FORMAT_VERSION,
+            constant_pool: None,
+            members: None,
+        };
+        let members = ClassMembers::read_from_buffer_with_ctx(context, &self.data)
+            .context("Failed to read class members")?;
+        if let Some(bounds) = &self.bounds {
+            members.validate(bounds).context("Invalid class members")?;
+        }
+        Ok(members)
+    }
+}
+
+impl ClassMembers {
+    /// The members of a class whose members could not be read
+    pub(crate) fn empty() -> Self {
+        ClassMembers {
+            signature: IndexedClassSignature::new(None, None, None),
+            enclosing_type_info: None,
+            fields: Vec::new(),
+            methods: Vec::new(),
+            annotations: Vec::new(),
+            permitted_subclasses: Vec::new(),
+            record_components: None,
+            nest_host: None,
+            nest_members: Vec::new(),
+            method_references: Vec::new(),
+            string_literals: Vec::new(),
+        }
+    }
+}
+
+impl<'a> Readable<'a, IndexReadContext> for ClassMembers {
+    fn read_from<R: Reader<'a, IndexReadContext>>(reader: &mut R) -> Result<Self, speedy::Error> {
+        Ok(ClassMembers {
+            signature: reader.read_value()?,
+            enclosing_type_info: reader.read_value()?,
+            fields: reader.read_value()?,
+            methods: reader.read_value()?,
+            annotations: reader.read_value()?,
+            permitted_subclasses: reader.read_value()?,
+            record_components: reader.read_value()?,
+            nest_host: reader.read_value()?,
+            nest_members: reader.read_value()?,
+            method_references: reader.read_value()?,
+            string_literals: reader.read_value()?,
+        })
+    }
+}
+
+/// The context used when deserializing a [ClassIndex]. Allows reading files saved without a
+/// header.
+#[derive(Clone)]
+pub struct IndexReadContext {
+    format_version: u32,
+    constant_pool: Option<IndexBytes>,
+    members: Option<IndexBytes>,
+}
+
+impl Context for IndexReadContext {
+    type Error = speedy::Error;
+
+    fn endianness(&self) -> Endianness {
+        Endianness::LittleEndian
+    }
+}
+
+/// Returns the decompressed contents of an entry. Entries stored without compression are not
+/// copied.
+fn read_entry(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    file_bytes: &IndexBytes,
+    name: &str,
+    codec: IndexCodec,
+) -> anyhow::Result<IndexBytes> {
     let mut file = archive
-        .by_index(0)
-        .with_context(|| "File with index 0 not found")?;
+        .by_name(name)
+        .with_context(|| format!("File {} not found", name))?;
     let file_size = file.size();
 
+    if file.compression() == CompressionMethod::Stored {
+        let start = file.data_start() as usize;
+        let data = file_bytes
+            .slice(start..start + file_size as usize)
+            .ok_or_else(|| anyhow!("File {} exceeds the zip file", name))?;
+        return codec.decode(data);
+    }
+
     let mut output_buf = Vec::with_capacity(file_size as usize);
     file.read_to_end(&mut output_buf)
-        .with_context(|| "Failed to read first file")?;
+        .with_context(|| format!("Failed to read file {}", name))?;
+    codec.decode(IndexBytes::owned(output_buf))
+}
+
+fn verify_checksum(name: &str, data: &[u8], expected: u32) -> anyhow::Result<()> {
+    let checksum = crc32fast::hash(data);
+    if checksum != expected {
+        bail!(
+            "{} checksum mismatch, expected {:#010X} but got {:#010X}. The file is corrupted.",
+            name,
+            expected,
+            checksum
+        );
+    }
+
+    Ok(())
+}
+
+/// Loads an index saved by [save_class_index_to_file]. The file is memory mapped, so it must not
+/// be modified while the returned index is in use.
+pub fn load_class_index_from_file(path: String) -> anyhow::Result<(BuildTimeInfo, ClassIndex)> {
+    let now = Instant::now();
+    let file = OpenOptions::new().read(true).open(path)?;
+    // Safety: Modifying the file while it is mapped is not supported
+    let map = unsafe { Mmap::map(&file) }.with_context(|| "Failed to map file")?;
+    let file_bytes = IndexBytes {
+        range: 0..map.len(),
+        source: Arc::new(IndexBytesSource::Mapped(map)),
+    };
+    let mut archive = ZipArchive::new(Cursor::new(&file_bytes[..]))?;
+
+    let header = if archive.file_names().any(|name| name == HEADER_ENTRY_NAME) {
+        Some(
+            IndexFileHeader::read_from_buffer(&read_entry(
+                &mut archive,
+                &file_bytes,
+                HEADER_ENTRY_NAME,
+                IndexCodec::Stored,
+            )?)
+            .with_context(|| "Failed to deserialize header")?,
+        )
+    } else {
+        None
+    };
+    let (format_version, codec) = match &header {
+        Some(header) => {
+            header.check_compatibility()?;
+            (FORMAT_VERSION, header.codec)
+        }
+        None => (LEGACY_FORMAT_VERSION, IndexCodec::Deflate(None)),
+    };
+
+    let output_buf = read_entry(&mut archive, &file_bytes, INDEX_ENTRY_NAME, codec)?;
+    let mut context = IndexReadContext {
+        format_version,
+        constant_pool: None,
+        members: None,
+    };
+    // Files without a header store everything in the index entry and contain no fingerprints
+    let mut fingerprints_buf = None;
+    if let Some(header) = &header {
+        verify_checksum("Index", &output_buf, header.index_checksum)?;
+
+        let constant_pool = read_entry(&mut archive, &file_bytes, CONSTANT_POOL_ENTRY_NAME, codec)?;
+        verify_checksum(
+            "Constant pool",
+            &constant_pool,
+            header.constant_pool_checksum,
+        )?;
+        context.constant_pool = Some(constant_pool);
+        context.members = Some(read_entry(
+            &mut archive,
+            &file_bytes,
+            MEMBERS_ENTRY_NAME,
+            codec,
+        )?);
+        fingerprints_buf = Some(read_entry(
+            &mut archive,
+            &file_bytes,
+            SOURCES_ENTRY_NAME,
+            codec,
+        )?);
+    }
 
     let mut info = BuildTimeInfo {
         class_reading_time: now.elapsed().as_millis(),
@@ -31,49 +435,195 @@ pub fn load_class_index_from_file(path: String) -> anyhow::Result<(BuildTimeInfo
     };
 
     let now = Instant::now();
-    let result = ClassIndex::read_from_buffer(&output_buf)
+    let mut result = ClassIndex::read_from_buffer_with_ctx(context, &output_buf)
         .with_context(|| "Failed to deserialize ClassIndex")?;
+    if let Some(fingerprints_buf) = fingerprints_buf {
+        result.set_source_fingerprints(
+            Vec::read_from_buffer(&fingerprints_buf)
+                .with_context(|| "Failed to deserialize source fingerprints")?,
+        );
+    }
     info.deserialization_time = now.elapsed().as_millis();
 
     Ok((info, result))
 }
 
-pub fn save_class_index_to_file(class_index: &ClassIndex, path: String) -> anyhow::Result<()> {
-    let mut file = ZipWriter::new(OpenOptions::new().write(true).create(true).open(path)?);
+/// Saves the index to a zip file. With [IndexCodec::Stored], loading the file does not require
+/// copying most of it into memory.
+///
+/// An existing file is replaced. A loaded index keeps its file mapped until it is dropped, which
+/// is fine on Unix, but on Windows a mapped file cannot be replaced and saving fails.
+pub fn save_class_index_to_file(
+    class_index: &ClassIndex,
+    path: String,
+    codec: IndexCodec,
+) -> anyhow::Result<()> {
+    codec.check_supported()?;
+
+    let mut members_buf = Vec::new();
+    let mut member_ranges = Vec::with_capacity(class_index.classes().len());
+    for class in class_index.classes() {
+        let start = members_buf.len();
+        (
+            class.signature(),
+            class.enclosing_type_info(),
+            class.fields(),
+            class.methods(),
+        )
+            .write_to_stream(&mut members_buf)
+            .and_then(|_| class.annotations().write_to_stream(&mut members_buf))
+            .and_then(|_| {
+                class
+                    .permitted_subclasses()
+                    .write_to_stream(&mut members_buf)
+            })
+            .and_then(|_| class.record_components().write_to_stream(&mut members_buf))
+            .and_then(|_| class.nest_host().write_to_stream(&mut members_buf))
+            .and_then(|_| class.nest_members().write_to_stream(&mut members_buf))
+            .and_then(|_| class.method_references().write_to_stream(&mut members_buf))
+            .and_then(|_| class.string_literals().write_to_stream(&mut members_buf))
+            .with_context(|| "Member serialization failed")?;
+        member_ranges.push((
+            start as u32,
+            (members_buf.len() - start) as u32,
+            crc32fast::hash(&members_buf[start..]),
+        ));
+    }
 
-    let serialized_buf = class_index
+    let serialized_buf = SavedClassIndex {
+        class_index,
+        member_ranges: &member_ranges,
+    }
+    .write_to_vec()
+    .with_context(|| "ClassIndex serialization failed")?;
+    let serialized_fingerprints = class_index
+        .source_fingerprints()
+        .write_to_vec()
+        .with_context(|| "Source fingerprint serialization failed")?;
+    let constant_pool = class_index.constant_pool().bytes();
+    let serialized_header = IndexFileHeader::current(&serialized_buf, constant_pool, codec)
         .write_to_vec()
-        .with_context(|| "ClassIndex serialization failed")?;
+        .with_context(|| "Header serialization failed")?;
 
-    file.start_file("index", FileOptions::default())
+    // The existing file might still be mapped by a loaded index, therefore it is replaced instead
+    // of being overwritten
+    let temp_path = format!("{}.tmp", path);
+    let mut file = ZipWriter::new(
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&temp_path)?,
+    );
+    // The header has to be readable before the codec is known
+    file.start_file(HEADER_ENTRY_NAME, IndexCodec::Stored.file_options())
         .with_context(|| "Failed to start file")?;
-    file.write_all(&serialized_buf)
+    file.write_all(&serialized_header)
         .with_context(|| "Unable to write file contents")?;
+    for (name, data) in [
+        (INDEX_ENTRY_NAME, &serialized_buf[..]),
+        (CONSTANT_POOL_ENTRY_NAME, constant_pool),
+        (MEMBERS_ENTRY_NAME, &members_buf[..]),
+        (SOURCES_ENTRY_NAME, &serialized_fingerprints[..]),
+    ] {
+        file.start_file(name, codec.file_options())
+            .with_context(|| "Failed to start file")?;
+        file.write_all(&codec.encode(data))
+            .with_context(|| "Unable to write file contents")?;
+    }
     file.finish().with_context(|| "Failed to finish zip file")?;
+    if let Err(error) = fs::rename(&temp_path, &path) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(error).with_context(|| {
+            if cfg!(windows) {
+                "Failed to replace existing file, it might still be used by a loaded index"
+            } else {
+                "Failed to replace existing file"
+            }
+        });
+    }
     Ok(())
 }
 
-impl<'a, C> Readable<'a, C> for ClassIndex
-where
-    C: Context,
-{
-    fn read_from<R: Reader<'a, C>>(reader: &mut R) -> Result<Self, C::Error> {
-        Ok(ClassIndex::new(
-            reader.read_value()?,
-            reader.read_value()?,
-            reader.read_value()?,
-        ))
+impl<'a> Readable<'a, IndexReadContext> for ClassIndex {
+    fn read_from<R: Reader<'a, IndexReadContext>>(reader: &mut R) -> Result<Self, speedy::Error> {
+        let constant_pool = match reader.context().constant_pool.clone() {
+            Some(constant_pool) => constant_pool,
+            None => IndexBytes::owned(reader.read_value()?),
+        };
+        let package_index: PackageIndex = reader.read_value()?;
+        let mut classes: Vec<IndexedClass> = reader.read_value()?;
+        let (sources, duplicate_class_sources, read_options, constant_pool) =
+            if reader.context().format_version == LEGACY_FORMAT_VERSION {
+                // Every string of the constant pool is prefixed with a single length byte
+                (
+                    Vec::new(),
+                    Vec::new(),
+                    ReadOptions::default(),
+                    ClassIndexConstantPool::from_legacy_bytes(constant_pool),
+                )
+            } else {
+                (
+                    reader.read_value()?,
+                    reader.read_value()?,
+                    reader.read_value()?,
+                    ClassIndexConstantPool::from_bytes(constant_pool, reader.read_value()?),
+                )
+            };
+
+        // Creating the index already accesses the class names
+        let bounds = Arc::new(IndexBounds::new(
+            &constant_pool,
+            package_index.packages().len(),
+            classes.len(),
+            sources.len(),
+        ));
+        validate_class_index(&bounds, &package_index, &classes, &duplicate_class_sources)
+            .map_err(|e| speedy::Error::custom(format!("{:#}", e)))?;
+        for class in classes.iter_mut() {
+            class.set_member_bounds(bounds.clone());
+        }
+
+        let mut class_index = ClassIndex::new(
+            constant_pool,
+            package_index,
+            classes,
+            sources,
+            duplicate_class_sources,
+        );
+        class_index.set_read_options(read_options);
+        Ok(class_index)
     }
 }
 
-impl<C> Writable<C> for ClassIndex
+/// Writes the index entry of the current format. The constant pool and members are written to
+/// their own entries, `member_ranges` contains the offset, length and checksum of the members of
+/// each class in the members entry.
+struct SavedClassIndex<'a> {
+    class_index: &'a ClassIndex,
+    member_ranges: &'a [(u32, u32, u32)],
+}
+
+impl<'a, C> Writable<C> for SavedClassIndex<'a>
 where
     C: Context,
 {
     fn write_to<T: ?Sized + Writer<C>>(&self, writer: &mut T) -> Result<(), C::Error> {
-        self.constant_pool().write_to(writer)?;
-        self.package_index().write_to(writer)?;
-        self.classes().write_to(writer)?;
+        self.class_index.package_index().write_to(writer)?;
+        writer.write_u32(self.class_index.classes().len() as u32)?;
+        for (class, member_range) in self.class_index.classes().iter().zip(self.member_ranges) {
+            class.write_to(writer)?;
+            member_range.write_to(writer)?;
+        }
+        self.class_index.sources().write_to(writer)?;
+        self.class_index
+            .duplicate_class_sources()
+            .write_to(writer)?;
+        self.class_index.read_options().write_to(writer)?;
+        self.class_index
+            .constant_pool()
+            .has_legacy_lengths()
+            .write_to(writer)?;
         Ok(())
     }
 }
@@ -107,28 +657,72 @@ where
     }
 }
 
-impl<'a, C> Readable<'a, C> for IndexedClass
-where
-    C: Context,
-{
-    fn read_from<R: Reader<'a, C>>(reader: &mut R) -> Result<Self, C::Error> {
-        let mut class = IndexedClass::new(
-            reader.read_u32()?,
-            reader.read_u32()?,
-            reader.read_u8()?,
-            reader.read_u16()?,
-        );
+impl<'a> Readable<'a, IndexReadContext> for IndexedClass {
+    fn read_from<R: Reader<'a, IndexReadContext>>(reader: &mut R) -> Result<Self, speedy::Error> {
+        let legacy = reader.context().format_version == LEGACY_FORMAT_VERSION;
+        let package_index = reader.read_u32()?;
+        let class_name_index = reader.read_u32()?;
+        let class_name_start_index = if legacy {
+            reader.read_u8()? as u16
+        } else {
+            reader.read_u16()?
+        };
+        let access_flags = reader.read_u16()?;
+        let mut class = if legacy {
+            // The class file version, module and source were not stored
+            IndexedClass::new(
+                package_index,
+                class_name_index,
+                class_name_start_index,
+                access_flags,
+                0,
+                None,
+                None,
+            )
+        } else {
+            IndexedClass::new(
+                package_index,
+                class_name_index,
+                class_name_start_index,
+                access_flags,
+                reader.read_u16()?,
+                reader.read_value()?,
+                reader.read_value()?,
+            )
+        };
         class.set_index(reader.read_value()?);
-        class.set_signature(reader.read_value()?);
-        if let Some(info) = reader.read_value::<Option<IndexedEnclosingTypeInfo>>()? {
-            class.set_enclosing_type_info(info);
+
+        if legacy {
+            // All members are stored in the index entry
+            class.set_signature(reader.read_value()?);
+            if let Some(info) = reader.read_value::<Option<IndexedEnclosingTypeInfo>>()? {
+                class.set_enclosing_type_info(info);
+            }
+            reader
+                .read_value::<Vec<u32>>()?
+                .into_iter()
+                .for_each(|c| class.add_member_class(c));
+            class.set_fields(Vec::read_from(reader)?).unwrap();
+            class.set_methods(Vec::read_from(reader)?).unwrap();
+            return Ok(class);
         }
+
         reader
             .read_value::<Vec<u32>>()?
             .into_iter()
             .for_each(|c| class.add_member_class(c));
-        class.set_fields(Vec::read_from(reader)?).unwrap();
-        class.set_methods(Vec::read_from(reader)?).unwrap();
+        let (offset, length, checksum): (u32, u32, u32) = reader.read_value()?;
+        let data = reader
+            .context()
+            .members
+            .as_ref()
+            .and_then(|members| members.slice(offset as usize..offset as usize + length as usize))
+            .ok_or_else(|| speedy::Error::custom("Class members out of bounds"))?;
+        class.set_mapped_members(MappedMembers {
+            data,
+            checksum,
+            bounds: None,
+        });
         Ok(class)
     }
 }
@@ -142,12 +736,11 @@ where
         self.class_name_index().write_to(writer)?;
         self.class_name_start_index().write_to(writer)?;
         self.access_flags().write_to(writer)?;
+        self.class_file_version().write_to(writer)?;
+        self.module_name_index().write_to(writer)?;
+        self.source_index().write_to(writer)?;
         self.index().write_to(writer)?;
-        self.signature().write_to(writer)?;
-        self.enclosing_type_info().write_to(writer)?;
         self.member_classes().write_to(writer)?;
-        self.fields().write_to(writer)?;
-        self.methods().write_to(writer)?;
         Ok(())
     }
 }
@@ -158,7 +751,7 @@ where
 {
     fn read_from<R: Reader<'a, C>>(reader: &mut R) -> Result<Self, C::Error> {
         Ok(match reader.read_u8()? {
-            0 => IndexedSignatureType::Unresolved,
+            0 => IndexedSignatureType::Unresolved(None),
             1 => IndexedSignatureType::Primitive(match reader.read_u8()? {
                 0 => jni::signature::Primitive::Boolean,
                 1 => jni::signature::Primitive::Byte,
@@ -169,7 +762,7 @@ where
                 6 => jni::signature::Primitive::Long,
                 7 => jni::signature::Primitive::Short,
                 8 => jni::signature::Primitive::Void,
-                _ => unreachable!(),
+                tag => return Err(invalid_tag_error("primitive", tag)),
             }),
             2 => IndexedSignatureType::Generic(reader.read_u32()?),
             3 => IndexedSignatureType::Object(reader.read_u32()?),
@@ -182,18 +775,27 @@ where
             6 => IndexedSignatureType::ObjectTypeBounds(Box::new(<_>::read_from(reader)?)),
             7 => IndexedSignatureType::ObjectInnerClass(Box::new(<_>::read_from(reader)?)),
             8 => IndexedSignatureType::Array(Box::new(<_>::read_from(reader)?)),
-            _ => unreachable!(),
+            9 => IndexedSignatureType::Unresolved(Some(reader.read_u32()?)),
+            tag => return Err(invalid_tag_error("signature type", tag)),
         })
     }
 }
 
+fn invalid_tag_error<E: From<speedy::Error>>(kind: &str, tag: u8) -> E {
+    speedy::Error::custom(format!("Invalid {} tag {}", kind, tag)).into()
+}
+
 impl<C> Writable<C> for IndexedSignatureType
 where
     C: Context,
 {
     fn write_to<T: ?Sized + Writer<C>>(&self, writer: &mut T) -> Result<(), C::Error> {
         match self {
-            IndexedSignatureType::Unresolved => writer.write_u8(0)?,
+            IndexedSignatureType::Unresolved(None) => writer.write_u8(0)?,
+            IndexedSignatureType::Unresolved(Some(i)) => {
+                writer.write_u8(9)?;
+                writer.write_u32(*i)?;
+            }
             IndexedSignatureType::Primitive(p) => {
                 writer.write_u8(1)?;
                 writer.write_u8(match p {
@@ -294,3 +896,557 @@ where
         Ok(())
     }
 }
+
+impl<'a> Readable<'a, IndexReadContext> for IndexedField {
+    fn read_from<R: Reader<'a, IndexReadContext>>(reader: &mut R) -> Result<Self, speedy::Error> {
+        Ok(IndexedField::new(
+            reader.read_value()?,
+            reader.read_value()?,
+            reader.read_value()?,
+            read_added_vec(reader)?,
+            read_added(reader)?,
+        ))
+    }
+}
+
+impl<'a> Readable<'a, IndexReadContext> for IndexedMethod {
+    fn read_from<R: Reader<'a, IndexReadContext>>(reader: &mut R) -> Result<Self, speedy::Error> {
+        Ok(IndexedMethod::new(
+            reader.read_value()?,
+            reader.read_value()?,
+            reader.read_value()?,
+            read_added_vec(reader)?,
+            read_added_vec(reader)?,
+            read_added_vec(reader)?,
+        ))
+    }
+}
+
+/// Reads a value which is not present in files saved without a header
+fn read_added<'a, T, R>(reader: &mut R) -> Result<T, speedy::Error>
+where
+    T: Readable<'a, IndexReadContext> + Default,
+    R: Reader<'a, IndexReadContext>,
+{
+    if reader.context().format_version == LEGACY_FORMAT_VERSION {
+        return Ok(T::default());
+    }
+
+    reader.read_value()
+}
+
+/// Reads an optional list which is not present in files saved without a header
+fn read_added_vec<'a, T, R>(reader: &mut R) -> Result<Vec<T>, speedy::Error>
+where
+    T: Readable<'a, IndexReadContext>,
+    R: Reader<'a, IndexReadContext>,
+{
+    Ok(read_added::<Option<Vec<T>>, _>(reader)?.unwrap_or_default())
+}
+
+impl<'a, C> Readable<'a, C> for IndexedAnnotation
+where
+    C: Context,
+{
+    fn read_from<R: Reader<'a, C>>(reader: &mut R) -> Result<Self, C::Error> {
+        Ok(IndexedAnnotation::new(
+            reader.read_value()?,
+            reader.read_value()?,
+            reader.read_value()?,
+        ))
+    }
+}
+
+impl<C> Writable<C> for IndexedAnnotation
+where
+    C: Context,
+{
+    fn write_to<T: ?Sized + Writer<C>>(&self, writer: &mut T) -> Result<(), C::Error> {
+        self.annotation_type().write_to(writer)?;
+        self.is_visible().write_to(writer)?;
+        self.elements().write_to(writer)?;
+        Ok(())
+    }
+}
+
+impl<'a, C> Readable<'a, C> for IndexedConstantValue
+where
+    C: Context,
+{
+    fn read_from<R: Reader<'a, C>>(reader: &mut R) -> Result<Self, C::Error> {
+        Ok(match reader.read_u8()? {
+            0 => ConstantValue::Int(reader.read_value()?),
+            1 => ConstantValue::Long(reader.read_value()?),
+            2 => ConstantValue::Float(reader.read_value()?),
+            3 => ConstantValue::Double(reader.read_value()?),
+            4 => ConstantValue::String(reader.read_value()?),
+            tag => return Err(invalid_tag_error("constant value", tag)),
+        })
+    }
+}
+
+impl<C> Writable<C> for IndexedConstantValue
+where
+    C: Context,
+{
+    fn write_to<T: ?Sized + Writer<C>>(&self, writer: &mut T) -> Result<(), C::Error> {
+        match self {
+            ConstantValue::Int(v) => {
+                writer.write_u8(0)?;
+                v.write_to(writer)?;
+            }
+            ConstantValue::Long(v) => {
+                writer.write_u8(1)?;
+                v.write_to(writer)?;
+            }
+            ConstantValue::Float(v) => {
+                writer.write_u8(2)?;
+                v.write_to(writer)?;
+            }
+            ConstantValue::Double(v) => {
+                writer.write_u8(3)?;
+                v.write_to(writer)?;
+            }
+            ConstantValue::String(v) => {
+                writer.write_u8(4)?;
+                v.write_to(writer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, C> Readable<'a, C> for IndexedAnnotationValue
+where
+    C: Context,
+{
+    fn read_from<R: Reader<'a, C>>(reader: &mut R) -> Result<Self, C::Error> {
+        Ok(match reader.read_u8()? {
+            0 => AnnotationValue::Boolean(reader.read_value()?),
+            1 => AnnotationValue::Byte(reader.read_value()?),
+            2 => AnnotationValue::Char(reader.read_value()?),
+            3 => AnnotationValue::Short(reader.read_value()?),
+            4 => AnnotationValue::Int(reader.read_value()?),
+            5 => AnnotationValue::Long(reader.read_value()?),
+            6 => AnnotationValue::Float(reader.read_value()?),
+            7 => AnnotationValue::Double(reader.read_value()?),
+            8 => AnnotationValue::String(reader.read_value()?),
+            9 => AnnotationValue::Enum(Box::new(reader.read_value()?)),
+            10 => AnnotationValue::Class(Box::new(reader.read_value()?)),
+            11 => AnnotationValue::Array(reader.read_value()?),
+            tag => return Err(invalid_tag_error("annotation value", tag)),
+        })
+    }
+}
+
+impl<C> Writable<C> for IndexedAnnotationValue
+where
+    C: Context,
+{
+    fn write_to<T: ?Sized + Writer<C>>(&self, writer: &mut T) -> Result<(), C::Error> {
+        match self {
+            AnnotationValue::Boolean(v) => {
+                writer.write_u8(0)?;
+                v.write_to(writer)?;
+            }
+            AnnotationValue::Byte(v) => {
+                writer.write_u8(1)?;
+                v.write_to(writer)?;
+            }
+            AnnotationValue::Char(v) => {
+                writer.write_u8(2)?;
+                v.write_to(writer)?;
+            }
+            AnnotationValue::Short(v) => {
+                writer.write_u8(3)?;
+                v.write_to(writer)?;
+            }
+            AnnotationValue::Int(v) => {
+                writer.write_u8(4)?;
+                v.write_to(writer)?;
+            }
+            AnnotationValue::Long(v) => {
+                writer.write_u8(5)?;
+                v.write_to(writer)?;
+            }
+            AnnotationValue::Float(v) => {
+                writer.write_u8(6)?;
+                v.write_to(writer)?;
+            }
+            AnnotationValue::Double(v) => {
+                writer.write_u8(7)?;
+                v.write_to(writer)?;
+            }
+            AnnotationValue::String(v) => {
+                writer.write_u8(8)?;
+                v.write_to(writer)?;
+            }
+            AnnotationValue::Enum(inner) => {
+                writer.write_u8(9)?;
+                inner.write_to(writer)?;
+            }
+            AnnotationValue::Class(class) => {
+                writer.write_u8(10)?;
+                class.write_to(writer)?;
+            }
+            AnnotationValue::Array(values) => {
+                writer.write_u8(11)?;
+                values.write_to(writer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        load_class_index_from_file, save_class_index_to_file, IndexBytes, IndexCodec,
+        IndexFileHeader, MappedMembers, FORMAT_VERSION, INDEX_ENTRY_NAME, INDEX_FILE_MAGIC,
+    };
+    use crate::annotation::IndexedAnnotation;
+    use crate::builder::incremental::update_class_index;
+    use crate::builder::workers::{create_class_index_from_paths, ReadOptions};
+    use crate::call_graph::CallGraph;
+    use crate::class_index::ClassIndex;
+    use crate::class_index_members::{
+        IndexedClass, IndexedField, IndexedMethod, IndexedRecordComponent,
+    };
+    use crate::constant_pool::ClassIndexConstantPool;
+    use crate::package_index::PackageIndex;
+    use crate::reference::IndexedReference;
+    use crate::signature::{
+        IndexedClassSignature, IndexedEnclosingTypeInfo, IndexedMethodSignature,
+        IndexedSignatureType,
+    };
+    use crate::test::{class_bytes, temp_dir, zip_bytes};
+    use crate::validation::IndexBounds;
+    use jni::signature::Primitive;
+    use speedy::{Readable, Writable};
+    use std::path::Path;
+    use std::sync::Arc;
+
+    fn create_class_index(dir: &Path) -> ClassIndex {
+        let jar = dir.join("a.jar");
+        std::fs::write(
+            &jar,
+            zip_bytes(&[
+                ("a/X.class", &class_bytes("a/X", 52, &["first", "second"])),
+                ("a/b/Y.class", &class_bytes("a/b/Y", 61, &[])),
+            ]),
+        )
+        .unwrap();
+        create_class_index_from_paths(
+            vec![jar.to_str().unwrap().to_string()],
+            ReadOptions::default().with_string_literals(true),
+        )
+        .unwrap()
+        .1
+    }
+
+    fn assert_same_classes(expected: &ClassIndex, actual: &ClassIndex) {
+        assert_eq!(expected.classes().len(), actual.classes().len());
+        assert_eq!(expected.sources(), actual.sources());
+        assert_eq!(expected.source_fingerprints(), actual.source_fingerprints());
+        assert_eq!(expected.read_options(), actual.read_options());
+        for (expected_class, actual_class) in expected.classes().iter().zip(actual.classes()) {
+            assert_eq!(
+                expected_class
+                    .class_name_with_package(expected.package_index(), expected.constant_pool()),
+                actual_class
+                    .class_name_with_package(actual.package_index(), actual.constant_pool())
+            );
+            assert_eq!(
+                expected_class.class_file_version(),
+                actual_class.class_file_version()
+            );
+            let field_names = |class_index: &ClassIndex, class: &IndexedClass| {
+                class
+                    .fields()
+                    .iter()
+                    .map(|field| field.field_name(class_index.constant_pool()).to_string())
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(
+                field_names(expected, expected_class),
+                field_names(actual, actual_class)
+            );
+        }
+    }
+
+    fn header(magic: u32, format_version: u32) -> IndexFileHeader {
+        IndexFileHeader {
+            magic,
+            format_version,
+            library_version: "0.0.1".to_string(),
+            index_checksum: 0,
+            constant_pool_checksum: 0,
+            codec: IndexCodec::Stored,
+        }
+    }
+
+    #[test]
+    fn test_header_compatibility() {
+        let bytes = IndexFileHeader::current(&[], &[], IndexCodec::Deflate(Some(9)))
+            .write_to_vec()
+            .unwrap();
+        let header_from_bytes = IndexFileHeader::read_from_buffer(&bytes).unwrap();
+        assert_eq!(IndexCodec::Deflate(Some(9)), header_from_bytes.codec);
+        header_from_bytes.check_compatibility().unwrap();
+
+        assert!(header(0xCAFE_BABE, FORMAT_VERSION)
+            .check_compatibility()
+            .is_err());
+
+        let error = header(INDEX_FILE_MAGIC, FORMAT_VERSION + 1)
+            .check_compatibility()
+            .unwrap_err();
+        assert!(error.to_string().starts_with("Incompatible index version"));
+    }
+
+    #[test]
+    fn test_load_legacy_file() {
+        // Files without a header prefix every string with a single length byte and store all
+        // members in the index entry
+        let mut data = Vec::new();
+        (
+            vec![0u8, 1, b'X', 1, b'f'],
+            vec![(0u32, 0u32, Vec::<u32>::new(), vec![0u32])],
+            1u32,
+        )
+            .write_to_stream(&mut data)
+            .unwrap();
+        (0u32, 1u32, 0u8, 0x21u16, 0u32)
+            .write_to_stream(&mut data)
+            .unwrap();
+        (
+            IndexedClassSignature::new(None, None, None),
+            None::<IndexedEnclosingTypeInfo>,
+            Vec::<u32>::new(),
+            vec![(3u32, 1u16, IndexedSignatureType::Primitive(Primitive::Int))],
+            Vec::<(u32, u16, IndexedMethodSignature)>::new(),
+        )
+            .write_to_stream(&mut data)
+            .unwrap();
+
+        let dir = temp_dir("load_legacy_file");
+        let path = dir.join("legacy").to_str().unwrap().to_string();
+        std::fs::write(&path, zip_bytes(&[(INDEX_ENTRY_NAME, &data)])).unwrap();
+        let (_, class_index) = load_class_index_from_file(path).unwrap();
+        assert_eq!(1, class_index.classes().len());
+        assert!(class_index.sources().is_empty());
+        assert_eq!(&ReadOptions::default(), class_index.read_options());
+        let class = &class_index.classes()[0];
+        assert_eq!("X", class.class_name(class_index.constant_pool()));
+        assert_eq!(0, class.class_file_version());
+        assert!(!class.has_unloaded_members());
+        assert_eq!(
+            "f",
+            class.fields()[0].field_name(class_index.constant_pool())
+        );
+
+        let path = dir.join("index").to_str().unwrap().to_string();
+        save_class_index_to_file(&class_index, path.clone(), IndexCodec::default()).unwrap();
+        let (_, loaded_class_index) = load_class_index_from_file(path).unwrap();
+        assert_same_classes(&class_index, &loaded_class_index);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_codec_round_trip() {
+        assert_eq!(IndexCodec::Deflate(None), IndexCodec::default());
+
+        let dir = temp_dir("codec_round_trip");
+        let class_index = create_class_index(&dir);
+        let mut codecs = vec![
+            IndexCodec::Stored,
+            IndexCodec::Deflate(None),
+            IndexCodec::Deflate(Some(9)),
+        ];
+        if cfg!(feature = "zstd") {
+            codecs.push(IndexCodec::Zstd(Some(1)));
+        }
+        if cfg!(feature = "lz4") {
+            codecs.push(IndexCodec::Lz4);
+        }
+
+        for codec in codecs {
+            let path = dir.join("index").to_str().unwrap().to_string();
+            save_class_index_to_file(&class_index, path.clone(), codec).unwrap();
+            let (_, loaded_class_index) = load_class_index_from_file(path).unwrap();
+            assert_same_classes(&class_index, &loaded_class_index);
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_load_mapped_members() {
+        let dir = temp_dir("load_mapped_members");
+        let class_index = create_class_index(&dir);
+        let path = dir.join("index").to_str().unwrap().to_string();
+        save_class_index_to_file(&class_index, path.clone(), IndexCodec::Stored).unwrap();
+
+        let (_, loaded_class_index) = load_class_index_from_file(path).unwrap();
+        assert!(loaded_class_index
+            .classes()
+            .iter()
+            .all(IndexedClass::has_unloaded_members));
+        loaded_class_index.load_all_members().unwrap();
+        assert!(!loaded_class_index
+            .classes()
+            .iter()
+            .any(IndexedClass::has_unloaded_members));
+        assert_same_classes(&class_index, &loaded_class_index);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_corrupted_members() {
+        let data = vec![1, 2, 3];
+        let members = |checksum| MappedMembers {
+            data: IndexBytes::owned(data.clone()),
+            checksum,
+            bounds: None,
+        };
+        let error = members(0).read().err().unwrap();
+        assert_eq!("Class members checksum mismatch", error.to_string());
+        assert!(members(crc32fast::hash(&data)).read().is_err());
+
+        let mut class = IndexedClass::new(0, 0, 0, 0, 52, None, None);
+        class.set_mapped_members(members(0));
+        assert!(class.load_members().is_err());
+        assert!(class.fields().is_empty());
+        assert!(class.methods().is_empty());
+        assert!(class.load_members().is_err());
+
+        let mut constant_pool = ClassIndexConstantPool::new(0);
+        constant_pool.add_string(b"X").unwrap();
+        class.set_index(0);
+        let class_index = ClassIndex::new(
+            constant_pool,
+            PackageIndex::new(),
+            vec![class],
+            Vec::new(),
+            Vec::new(),
+        );
+        assert!(class_index.load_all_members().is_err());
+        assert!(class_index.find_usages_of_class(0).is_err());
+        assert!(CallGraph::new(&class_index).is_err());
+        assert!(update_class_index(&class_index, Vec::new(), Vec::new(), Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_validate_mapped_members() {
+        let members = |permitted_subclass: u32| {
+            let mut data = Vec::new();
+            (
+                IndexedClassSignature::new(None, None, None),
+                None::<IndexedEnclosingTypeInfo>,
+                Vec::<IndexedField>::new(),
+                Vec::<IndexedMethod>::new(),
+                Vec::<IndexedAnnotation>::new(),
+                vec![permitted_subclass],
+            )
+                .write_to_stream(&mut data)
+                .unwrap();
+            (
+                None::<Vec<IndexedRecordComponent>>,
+                None::<u32>,
+                Vec::<u32>::new(),
+                Vec::<Vec<IndexedReference>>::new(),
+                Vec::<u32>::new(),
+            )
+                .write_to_stream(&mut data)
+                .unwrap();
+            MappedMembers {
+                checksum: crc32fast::hash(&data),
+                data: IndexBytes::owned(data),
+                bounds: Some(Arc::new(IndexBounds::new(
+                    &ClassIndexConstantPool::new(0),
+                    1,
+                    2,
+                    0,
+                ))),
+            }
+        };
+
+        assert_eq!(vec![1], members(1).read().unwrap().permitted_subclasses);
+        let error = members(2).read().err().unwrap();
+        assert_eq!(
+            "Invalid class members: Class index 2 out of bounds, length is 2",
+            format!("{:#}", error)
+        );
+    }
+
+    #[test]
+    fn test_save_legacy_lengths() {
+        let mut constant_pool = ClassIndexConstantPool::new(0);
+        constant_pool.add_string(b"").unwrap();
+        let mut package_index = PackageIndex::new();
+        let package = package_index.get_or_add_package_index(&mut constant_pool, "a");
+        let class_name = "X".repeat(255);
+        let name_index = constant_pool.add_string(class_name.as_bytes()).unwrap();
+        // Files without a header prefixed the name with a single length byte instead of the
+        // marker and a u32
+        let mut legacy_bytes = constant_pool.bytes()[..=name_index as usize].to_vec();
+        legacy_bytes.extend_from_slice(class_name.as_bytes());
+        let constant_pool =
+            ClassIndexConstantPool::from_legacy_bytes(IndexBytes::owned(legacy_bytes));
+        assert!(constant_pool.has_legacy_lengths());
+
+        let class = IndexedClass::new(package, name_index, 0, 1, 52, None, None);
+        class.set_index(0);
+        class.set_signature(IndexedClassSignature::new(None, None, None));
+        class.set_fields(Vec::new()).unwrap();
+        class.set_methods(Vec::new()).unwrap();
+        package_index.package_at(package).add_class(0);
+        let mut class_index = ClassIndex::new(
+            constant_pool,
+            package_index,
+            vec![class],
+            Vec::new(),
+            Vec::new(),
+        );
+
+        let dir = temp_dir("save_legacy_lengths");
+        for name in ["first", "second"] {
+            let path = dir.join(name).to_str().unwrap().to_string();
+            save_class_index_to_file(&class_index, path.clone(), IndexCodec::Stored).unwrap();
+            class_index = load_class_index_from_file(path).unwrap().1;
+            assert!(class_index.constant_pool().has_legacy_lengths());
+            assert_eq!(
+                format!("a/{}", class_name),
+                class_index.classes()[0].class_name_with_package(
+                    class_index.package_index(),
+                    class_index.constant_pool()
+                )
+            );
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_save_over_loaded_file() {
+        let dir = temp_dir("save_over_loaded_file");
+        let class_index = create_class_index(&dir);
+        let path = dir.join("index").to_str().unwrap().to_string();
+        save_class_index_to_file(&class_index, path.clone(), IndexCodec::Stored).unwrap();
+        let (_, loaded_class_index) = load_class_index_from_file(path.clone()).unwrap();
+
+        let result =
+            save_class_index_to_file(&loaded_class_index, path.clone(), IndexCodec::Stored);
+        if cfg!(windows) {
+            assert!(result.is_err());
+            assert!(!Path::new(&format!("{}.tmp", path)).exists());
+        } else {
+            result.unwrap();
+        }
+        // The loaded index still uses the mapping of the replaced file
+        assert_same_classes(&class_index, &loaded_class_index);
+        let (_, reloaded_class_index) = load_class_index_from_file(path).unwrap();
+        assert_same_classes(&class_index, &reloaded_class_index);
+
+        drop(loaded_class_index);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
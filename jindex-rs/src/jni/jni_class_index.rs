@@ -1,18 +1,24 @@
-use crate::builder::workers::{create_class_index_from_bytes, create_class_index_from_jars};
+use crate::builder::incremental::update_class_index;
+use crate::builder::workers::{
+    create_class_index_from_bytes, create_class_index_from_jars, create_class_index_from_paths,
+    DuplicateClassPolicy, ReadOptions,
+};
 use crate::builder::BuildTimeInfo;
 use anyhow::anyhow;
-use ascii::IntoAsciiString;
 use jni::objects::{JObject, JString, JValue};
-use jni::sys::{jlong, jobject, jobjectArray};
+use jni::sys::{jboolean, jint, jlong, jobject, jobjectArray};
 use jni::JNIEnv;
 use std::ops::Deref;
 
+use crate::call_graph::CallGraph;
 use crate::class_index::ClassIndex;
-use crate::class_index_members::IndexedClass;
+use crate::class_index_members::{IndexedClass, IndexedField, IndexedMethod};
 use crate::constant_pool::{MatchMode, SearchMode, SearchOptions};
-use crate::io::{load_class_index_from_file, save_class_index_to_file};
-use crate::jni::cache::{cached_field_ids, get_class_index, init_field_ids};
-use crate::jni::{get_enum_ordinal, propagate_error};
+use crate::constant_value::ConstantValue;
+use crate::io::{load_class_index_from_file, save_class_index_to_file, IndexCodec};
+use crate::jni::cache::{cached_field_ids, get_class_index, get_field_with_id, init_field_ids};
+use crate::jni::{get_enum_ordinal, methods_to_array, propagate_error, search_results_to_array};
+use crate::name_pattern::NamePattern;
 use crate::package_index::IndexedPackage;
 
 #[no_mangle]
@@ -74,22 +80,89 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_createClas
     env: JNIEnv,
     this: JObject,
     jar_names_list: JObject,
+    options: JObject,
 ) -> jobject {
     propagate_error!(env, init_field_ids(env), JObject::null().into_raw());
 
-    let java_list = env.get_list(jar_names_list).unwrap();
-    let mut jar_names = Vec::with_capacity(java_list.size().unwrap() as usize);
-    for ar in java_list.iter().unwrap() {
-        jar_names.push(
-            env.get_string(JString::from(ar))
-                .expect("Not a string")
-                .into(),
-        );
-    }
+    let jar_names = convert_string_list(env, jar_names_list);
+    let options = propagate_error!(
+        env,
+        convert_read_options(env, options),
+        JObject::null().into_raw()
+    );
+    let (info, class_index) = propagate_error!(
+        env,
+        create_class_index_from_jars(jar_names, options),
+        JObject::null().into_raw()
+    );
+
+    env.set_field(
+        this,
+        "classIndexPointer",
+        "J",
+        JValue::Long(Box::into_raw(Box::new(class_index)) as jlong),
+    )
+    .expect("Unable to set field");
+
+    convert_build_time_info(env, info)
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_createClassIndexFromPaths(
+    env: JNIEnv,
+    this: JObject,
+    paths_list: JObject,
+    options: JObject,
+) -> jobject {
+    propagate_error!(env, init_field_ids(env), JObject::null().into_raw());
+
+    let paths = convert_string_list(env, paths_list);
+    let options = propagate_error!(
+        env,
+        convert_read_options(env, options),
+        JObject::null().into_raw()
+    );
+    let (info, class_index) = propagate_error!(
+        env,
+        create_class_index_from_paths(paths, options),
+        JObject::null().into_raw()
+    );
+
+    env.set_field(
+        this,
+        "classIndexPointer",
+        "J",
+        JValue::Long(Box::into_raw(Box::new(class_index)) as jlong),
+    )
+    .expect("Unable to set field");
 
+    convert_build_time_info(env, info)
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field of the given class index has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_updateClassIndex(
+    env: JNIEnv,
+    this: JObject,
+    class_index: JObject,
+    added_list: JObject,
+    removed_list: JObject,
+    changed_list: JObject,
+) -> jobject {
+    propagate_error!(env, init_field_ids(env), JObject::null().into_raw());
+
+    let (_, class_index) = get_class_index(env, class_index);
     let (info, class_index) = propagate_error!(
         env,
-        create_class_index_from_jars(jar_names),
+        update_class_index(
+            class_index,
+            convert_string_list(env, added_list),
+            convert_string_list(env, removed_list),
+            convert_string_list(env, changed_list),
+        ),
         JObject::null().into_raw()
     );
 
@@ -104,6 +177,20 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_createClas
     convert_build_time_info(env, info)
 }
 
+unsafe fn convert_string_list(env: JNIEnv, list: JObject) -> Vec<String> {
+    let java_list = env.get_list(list).unwrap();
+    let mut strings = Vec::with_capacity(java_list.size().unwrap() as usize);
+    for element in java_list.iter().unwrap() {
+        strings.push(
+            env.get_string(JString::from(element))
+                .expect("Not a string")
+                .into(),
+        );
+    }
+
+    strings
+}
+
 #[no_mangle]
 /// # Safety
 /// The pointer field has to be valid...
@@ -111,12 +198,36 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_saveToFile
     env: JNIEnv,
     this: JObject,
     path: JString,
+    codec: JObject,
+    level: jint,
 ) {
     let path: String = env.get_string(path).expect("Invalid path").into();
+    // Integer.MIN_VALUE selects the default level
+    let level = (level != jint::MIN).then_some(level);
+    let codec = match get_enum_ordinal(env, codec) {
+        0 => IndexCodec::Stored,
+        1 => IndexCodec::Deflate(level),
+        2 => IndexCodec::Zstd(level),
+        3 => IndexCodec::Lz4,
+        _ => propagate_error!(env, Err(anyhow!("Invalid enum ordinal for codec"))),
+    };
 
     let (_, class_index) = get_class_index(env, this);
 
-    propagate_error!(env, save_class_index_to_file(class_index, path));
+    propagate_error!(env, save_class_index_to_file(class_index, path, codec));
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_isStale(
+    env: JNIEnv,
+    this: JObject,
+    paths: JObject,
+) -> jboolean {
+    let (_, class_index) = get_class_index(env, this);
+
+    !class_index.is_up_to_date(&convert_string_list(env, paths)) as jboolean
 }
 
 #[no_mangle]
@@ -148,38 +259,102 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_loadClassI
 }
 
 unsafe fn convert_build_time_info(env: JNIEnv, info: BuildTimeInfo) -> jobject {
+    let duplicate_class_class = env
+        .find_class("com/github/tth05/jindex/DuplicateClass")
+        .expect("Unable to find class");
+    let string_class = env
+        .find_class("java/lang/String")
+        .expect("Unable to find class");
+
+    let duplicate_classes = env
+        .new_object_array(
+            info.duplicate_classes.len() as i32,
+            duplicate_class_class,
+            JObject::null(),
+        )
+        .expect("Failed to create result array");
+    for (index, duplicate_class) in info.duplicate_classes.iter().enumerate() {
+        let sources = env
+            .new_object_array(
+                duplicate_class.sources.len() as i32,
+                string_class,
+                JObject::null(),
+            )
+            .expect("Failed to create result array");
+        for (source_index, source) in duplicate_class.sources.iter().enumerate() {
+            if let Some(source) = source {
+                env.set_object_array_element(
+                    sources,
+                    source_index as i32,
+                    env.new_string(source).unwrap(),
+                )
+                .expect("Failed to set element into result array");
+            }
+        }
+
+        let object = env
+            .new_object(
+                duplicate_class_class,
+                "(Ljava/lang/String;[Ljava/lang/String;Z)V",
+                &[
+                    JValue::Object(env.new_string(&duplicate_class.name).unwrap().into()),
+                    JValue::Object(JObject::from_raw(sources)),
+                    JValue::Bool(duplicate_class.members_differ as jboolean),
+                ],
+            )
+            .expect("Failed to create result object");
+        env.set_object_array_element(duplicate_classes, index as i32, object)
+            .expect("Failed to set element into result array");
+    }
+
+    let skipped_source_class = env
+        .find_class("com/github/tth05/jindex/SkippedSource")
+        .expect("Unable to find class");
+    let skipped_sources = env
+        .new_object_array(
+            info.skipped_sources.len() as i32,
+            skipped_source_class,
+            JObject::null(),
+        )
+        .expect("Failed to create result array");
+    for (index, skipped_source) in info.skipped_sources.iter().enumerate() {
+        let object = env
+            .new_object(
+                skipped_source_class,
+                "(Ljava/lang/String;Ljava/lang/String;)V",
+                &[
+                    JValue::Object(env.new_string(&skipped_source.name).unwrap().into()),
+                    JValue::Object(env.new_string(&skipped_source.error).unwrap().into()),
+                ],
+            )
+            .expect("Failed to create result object");
+        env.set_object_array_element(skipped_sources, index as i32, object)
+            .expect("Failed to set element into result array");
+    }
+
     env.new_object(
         env.find_class("com/github/tth05/jindex/BuildTimeInfo")
             .expect("Unable to find class"),
-        "(JJJ)V",
+        "(JJJ[Lcom/github/tth05/jindex/DuplicateClass;[Lcom/github/tth05/jindex/SkippedSource;)V",
         &[
             JValue::Long(info.deserialization_time as jlong),
             JValue::Long(info.class_reading_time as jlong),
             JValue::Long(info.indexing_time as jlong),
+            JValue::Object(JObject::from_raw(duplicate_classes)),
+            JValue::Object(JObject::from_raw(skipped_sources)),
         ],
     )
     .expect("Unable to create object")
     .into_raw()
 }
 
-macro_rules! java_to_ascii_string {
+macro_rules! java_to_string {
     ($env:expr, $jstring:ident) => {
-        java_to_ascii_string!($env, $jstring, |s| s)
+        java_to_string!($env, $jstring, |s| s)
     };
     ($env:expr, $jstring:ident, $mapper:expr) => {{
-        let env_str: String = $mapper($env.get_string($jstring).expect("Not a string").into());
-
-        match env_str.into_ascii_string() {
-            Ok(s) => s,
-            Err(e) => {
-                $env.throw_new(
-                    "java/lang/IllegalArgumentException",
-                    format!("'{}' is not an ASCII string", e.into_source()),
-                )
-                .expect("Unable to throw exception");
-                return JObject::null().into_raw();
-            }
-        }
+        let env_str: String = $env.get_string($jstring).expect("Not a string").into();
+        $mapper(env_str)
     }};
 }
 
@@ -192,7 +367,7 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_findClasse
     input: JString,
     options: JObject,
 ) -> jobjectArray {
-    let input = java_to_ascii_string!(&env, input);
+    let input = java_to_string!(&env, input);
 
     let result_class = env
         .find_class("com/github/tth05/jindex/IndexedClass")
@@ -230,6 +405,107 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_findClasse
     result_array
 }
 
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_findClassesByStringLiteral(
+    env: JNIEnv,
+    this: JObject,
+    input: JString,
+    options: JObject,
+) -> jobjectArray {
+    let input = java_to_string!(&env, input);
+
+    let result_class = env
+        .find_class("com/github/tth05/jindex/IndexedClass")
+        .expect("Result class not found");
+
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+    let classes = propagate_error!(
+        env,
+        class_index.find_classes_by_string_literal(
+            &input,
+            propagate_error!(
+                env,
+                convert_search_options(env, options),
+                JObject::null().into_raw()
+            ),
+        ),
+        JObject::null().into_raw()
+    );
+
+    let result_array = env
+        .new_object_array(classes.len() as i32, result_class, JObject::null())
+        .expect("Failed to create result array");
+    for (index, class) in classes.into_iter().enumerate() {
+        let object = env
+            .new_object(
+                result_class,
+                "(JJ)V",
+                &[
+                    JValue::from(class_index_pointer as jlong),
+                    JValue::from((class as *const IndexedClass) as jlong),
+                ],
+            )
+            .expect("Failed to create result object");
+        env.set_object_array_element(result_array, index as i32, object)
+            .expect("Failed to set element into result array");
+    }
+
+    result_array
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_findFieldsWithConstant0(
+    env: JNIEnv,
+    this: JObject,
+    value_type: jint,
+    bits: jlong,
+    string: JString,
+) -> jobjectArray {
+    let value = match value_type {
+        0 => ConstantValue::Int(bits as i32),
+        1 => ConstantValue::Long(bits),
+        2 => ConstantValue::Float(f32::from_bits(bits as u32)),
+        3 => ConstantValue::Double(f64::from_bits(bits as u64)),
+        _ => ConstantValue::String(java_to_string!(&env, string).into()),
+    };
+
+    let result_class = env
+        .find_class("com/github/tth05/jindex/IndexedField")
+        .expect("Result class not found");
+
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+    let fields = propagate_error!(
+        env,
+        class_index.find_fields_with_constant(&value),
+        JObject::null().into_raw()
+    );
+
+    let result_array = env
+        .new_object_array(fields.len() as i32, result_class, JObject::null())
+        .expect("Failed to create result array");
+    for (index, (class, field)) in fields.into_iter().enumerate() {
+        let object = env
+            .new_object(
+                result_class,
+                "(JJJ)V",
+                &[
+                    JValue::from(class_index_pointer as jlong),
+                    JValue::from((class as *const IndexedClass) as jlong),
+                    JValue::from((field as *const IndexedField) as jlong),
+                ],
+            )
+            .expect("Failed to create result object");
+        env.set_object_array_element(result_array, index as i32, object)
+            .expect("Failed to set element into result array");
+    }
+
+    result_array
+}
+
 unsafe fn convert_search_options(env: JNIEnv, options: JObject) -> anyhow::Result<SearchOptions> {
     if options.is_null() {
         return Ok(SearchOptions::default());
@@ -265,6 +541,8 @@ unsafe fn convert_search_options(env: JNIEnv, options: JObject) -> anyhow::Resul
     ) {
         0 => SearchMode::Prefix,
         1 => SearchMode::Contains,
+        2 => SearchMode::CamelCase,
+        3 => SearchMode::Fuzzy,
         _ => return Err(anyhow!("Invalid enum ordinal for search mode")),
     };
 
@@ -281,6 +559,61 @@ unsafe fn convert_search_options(env: JNIEnv, options: JObject) -> anyhow::Resul
     })
 }
 
+unsafe fn convert_read_options(env: JNIEnv, options: JObject) -> anyhow::Result<ReadOptions> {
+    if options.is_null() {
+        return Ok(ReadOptions::default());
+    }
+
+    let get_bool = |name| {
+        env.get_field(options, name, "Z")
+            .expect("Field not found")
+            .z()
+            .unwrap()
+    };
+    let get_int = |name| {
+        env.get_field(options, name, "I")
+            .expect("Field not found")
+            .i()
+            .unwrap()
+    };
+
+    let duplicate_class_policy = match get_enum_ordinal(
+        env,
+        env.get_field(
+            options,
+            "duplicateClassPolicy",
+            "Lcom/github/tth05/jindex/IndexingOptions$DuplicateClassPolicy;",
+        )
+        .expect("Field not found")
+        .l()
+        .unwrap(),
+    ) {
+        0 => DuplicateClassPolicy::First,
+        1 => DuplicateClassPolicy::Last,
+        2 => DuplicateClassPolicy::HighestClassFileVersion,
+        _ => return Err(anyhow!("Invalid enum ordinal for duplicate class policy")),
+    };
+    let max_nested_jar_depth = u8::try_from(get_int("maxNestedJarDepth"))
+        .map_err(|_| anyhow!("Max nested jar depth must be between 0 and {}", u8::MAX))?;
+
+    let mut read_options = ReadOptions::default()
+        .with_references(get_bool("indexReferences"))
+        .with_string_literals(get_bool("indexStringLiterals"))
+        .with_local_variable_parameter_names(get_bool("localVariableParameterNames"))
+        .with_duplicate_class_policy(duplicate_class_policy)
+        .with_max_nested_jar_depth(max_nested_jar_depth);
+    // Zero selects the highest version of each class
+    let target_release = get_int("targetRelease");
+    if target_release != 0 {
+        read_options = read_options.with_target_release(
+            u16::try_from(target_release)
+                .map_err(|_| anyhow!("Invalid target release {}", target_release))?,
+        );
+    }
+
+    Ok(read_options)
+}
+
 #[no_mangle]
 /// # Safety
 /// The pointer field has to be valid...
@@ -290,8 +623,8 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_findClass(
     i_package_name: JString,
     i_class_name: JString,
 ) -> jobject {
-    let class_name = java_to_ascii_string!(&env, i_class_name);
-    let package_name = java_to_ascii_string!(&env, i_package_name, |s: String| s.replace('.', "/"));
+    let class_name = java_to_string!(&env, i_class_name);
+    let package_name = java_to_string!(&env, i_package_name, |s: String| s.replace('.', "/"));
 
     let result_class = env
         .find_class("com/github/tth05/jindex/IndexedClass")
@@ -323,7 +656,7 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_findPackag
     this: JObject,
     i_package_name: JString,
 ) -> jobject {
-    let package_name = java_to_ascii_string!(&env, i_package_name, |s: String| s.replace('.', "/"));
+    let package_name = java_to_string!(&env, i_package_name, |s: String| s.replace('.', "/"));
 
     let result_class = env
         .find_class("com/github/tth05/jindex/IndexedPackage")
@@ -355,7 +688,7 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_findPackag
     this: JObject,
     query: JString,
 ) -> jobject {
-    let query = java_to_ascii_string!(&env, query, |s: String| s.replace('.', "/"));
+    let query = java_to_string!(&env, query, |s: String| s.replace('.', "/"));
 
     let result_class = env
         .find_class("com/github/tth05/jindex/IndexedPackage")
@@ -390,3 +723,235 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_findPackag
 
     result_array
 }
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_findReachableMethods(
+    env: JNIEnv,
+    this: JObject,
+    entry_points: jobjectArray,
+) -> jobjectArray {
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+    let call_graph = propagate_error!(env, CallGraph::new(class_index), JObject::null().into_raw());
+
+    let length = env
+        .get_array_length(entry_points)
+        .expect("Failed to get array length");
+    let entry_points = (0..length)
+        .map(|index| {
+            let element = env
+                .get_object_array_element(entry_points, index)
+                .expect("Failed to get array element");
+            (
+                get_field_with_id::<IndexedClass>(
+                    env,
+                    element,
+                    &cached_field_ids().class_child_class_pointer,
+                ),
+                get_field_with_id::<IndexedMethod>(
+                    env,
+                    element,
+                    &cached_field_ids().class_index_child_self_pointer,
+                ),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    methods_to_array(
+        env,
+        class_index_pointer,
+        &call_graph.reachable_from(&entry_points),
+    )
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_findClassesWithScores(
+    env: JNIEnv,
+    this: JObject,
+    input: JString,
+    options: JObject,
+) -> jobjectArray {
+    let input = java_to_string!(&env, input);
+
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+
+    let results = class_index.find_classes_with_scores(
+        &input,
+        propagate_error!(
+            env,
+            convert_search_options(env, options),
+            JObject::null().into_raw()
+        ),
+    );
+
+    search_results_to_array(
+        env,
+        results.into_iter().map(|result| {
+            let object = env
+                .new_object(
+                    "com/github/tth05/jindex/IndexedClass",
+                    "(JJ)V",
+                    &[
+                        JValue::from(class_index_pointer as jlong),
+                        JValue::from((result.value as *const IndexedClass) as jlong),
+                    ],
+                )
+                .expect("Failed to create result object");
+            (result.score, object)
+        }),
+    )
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_findMethodsWithScores(
+    env: JNIEnv,
+    this: JObject,
+    input: JString,
+    options: JObject,
+) -> jobjectArray {
+    let input = java_to_string!(&env, input);
+
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+    let results = propagate_error!(
+        env,
+        class_index.find_methods_with_scores(
+            &input,
+            propagate_error!(
+                env,
+                convert_search_options(env, options),
+                JObject::null().into_raw()
+            ),
+        ),
+        JObject::null().into_raw()
+    );
+
+    search_results_to_array(
+        env,
+        results.into_iter().map(|result| {
+            let (class, method) = result.value;
+            let object = env
+                .new_object(
+                    "com/github/tth05/jindex/IndexedMethod",
+                    "(JJJ)V",
+                    &[
+                        JValue::from(class_index_pointer as jlong),
+                        JValue::from((class as *const IndexedClass) as jlong),
+                        JValue::from((method as *const IndexedMethod) as jlong),
+                    ],
+                )
+                .expect("Failed to create result object");
+            (result.score, object)
+        }),
+    )
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_findPackagesWithScores(
+    env: JNIEnv,
+    this: JObject,
+    query: JString,
+    options: JObject,
+) -> jobjectArray {
+    let query = java_to_string!(&env, query, |s: String| s.replace('.', "/"));
+
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+
+    let results = class_index.find_packages_with_scores(
+        &query,
+        propagate_error!(
+            env,
+            convert_search_options(env, options),
+            JObject::null().into_raw()
+        ),
+    );
+
+    search_results_to_array(
+        env,
+        results.into_iter().map(|result| {
+            let object = env
+                .new_object(
+                    "com/github/tth05/jindex/IndexedPackage",
+                    "(JJ)V",
+                    &[
+                        JValue::from(class_index_pointer as jlong),
+                        JValue::from((result.value as *const IndexedPackage) as jlong),
+                    ],
+                )
+                .expect("Failed to create result object");
+            (result.score, object)
+        }),
+    )
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_findClassesByGlob(
+    env: JNIEnv,
+    this: JObject,
+    pattern: JString,
+    limit: jint,
+) -> jobjectArray {
+    let pattern = java_to_string!(&env, pattern);
+    find_classes_matching(env, this, NamePattern::glob(&pattern), limit)
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_ClassIndex_findClassesByRegex(
+    env: JNIEnv,
+    this: JObject,
+    pattern: JString,
+    limit: jint,
+) -> jobjectArray {
+    let pattern = java_to_string!(&env, pattern);
+    find_classes_matching(env, this, NamePattern::regex(&pattern), limit)
+}
+
+unsafe fn find_classes_matching(
+    env: JNIEnv,
+    this: JObject,
+    pattern: anyhow::Result<NamePattern>,
+    limit: jint,
+) -> jobjectArray {
+    let result_class = env
+        .find_class("com/github/tth05/jindex/IndexedClass")
+        .expect("Result class not found");
+
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+
+    let pattern = propagate_error!(env, pattern, JObject::null().into_raw());
+    let classes = propagate_error!(
+        env,
+        class_index.find_classes_matching(&pattern, limit.max(0) as usize),
+        JObject::null().into_raw()
+    );
+
+    let result_array = env
+        .new_object_array(classes.len() as i32, result_class, JObject::null())
+        .expect("Failed to create result array");
+    for (index, class) in classes.into_iter().enumerate() {
+        let object = env
+            .new_object(
+                result_class,
+                "(JJ)V",
+                &[
+                    JValue::from(class_index_pointer as jlong),
+                    JValue::from((class as *const IndexedClass) as jlong),
+                ],
+            )
+            .expect("Failed to create result object");
+        env.set_object_array_element(result_array, index as i32, object)
+            .expect("Failed to set element into result array");
+    }
+
+    result_array
+}
@@ -1,10 +1,15 @@
-use crate::class_index_members::{IndexedClass, IndexedField, IndexedMethod};
+use crate::class_index::{AnnotatedElement, ClassMember};
+use crate::class_index_members::{
+    IndexedClass, IndexedField, IndexedMethod, IndexedRecordComponent,
+};
 use crate::jni::cache::{cached_field_ids, get_class_index, get_field_with_id};
-use crate::jni::{get_java_lang_object, is_basic_signature_type};
+use crate::jni::{
+    annotation_classes_to_array, get_java_lang_object, is_basic_signature_type, methods_to_array,
+    propagate_error,
+};
 use crate::package_index::IndexedPackage;
 use crate::signature::indexed_signature::{ToDescriptorIndexedType, ToSignatureIndexedType};
 use crate::signature::SignatureType;
-use ascii::AsAsciiStr;
 use jni::objects::{JObject, JValue};
 use jni::sys::{jboolean, jint, jlong, jobject, jobjectArray, jsize, jstring};
 use jni::JNIEnv;
@@ -81,6 +86,46 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_getPacka
     .into_raw()
 }
 
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_getModuleName(
+    env: JNIEnv,
+    this: JObject,
+) -> jstring {
+    let (_, class_index) = get_class_index(env, this);
+    let indexed_class = get_field_with_id::<IndexedClass>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+
+    match indexed_class.module_name(class_index.constant_pool()) {
+        Some(name) => env.new_string(name).unwrap().into_raw(),
+        None => JObject::null().into_raw(),
+    }
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_getSourceJar(
+    env: JNIEnv,
+    this: JObject,
+) -> jstring {
+    let (_, class_index) = get_class_index(env, this);
+    let indexed_class = get_field_with_id::<IndexedClass>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+
+    match indexed_class.source(class_index) {
+        Some(source) => env.new_string(source).unwrap().into_raw(),
+        None => JObject::null().into_raw(),
+    }
+}
+
 #[no_mangle]
 /// # Safety
 /// The pointer field has to be valid...
@@ -156,6 +201,11 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_getField
         this,
         &cached_field_ids().class_index_child_self_pointer,
     );
+    propagate_error!(
+        env,
+        indexed_class.load_members(),
+        JObject::null().into_raw()
+    );
 
     let result_class = env
         .find_class("com/github/tth05/jindex/IndexedField")
@@ -201,6 +251,11 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_getMetho
         this,
         &cached_field_ids().class_index_child_self_pointer,
     );
+    propagate_error!(
+        env,
+        indexed_class.load_members(),
+        JObject::null().into_raw()
+    );
 
     let result_class = env
         .find_class("com/github/tth05/jindex/IndexedMethod")
@@ -246,6 +301,11 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_getSuper
         this,
         &cached_field_ids().class_index_child_self_pointer,
     );
+    propagate_error!(
+        env,
+        indexed_class.load_members(),
+        JObject::null().into_raw()
+    );
 
     let result_class = env
         .find_class("com/github/tth05/jindex/IndexedClass")
@@ -264,7 +324,7 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_getSuper
             }
         },
         |s| match s {
-            SignatureType::Unresolved => None,
+            SignatureType::Unresolved(_) => None,
             _ => Some(class_index.class_at_index(s.extract_base_object_type().unwrap())),
         },
     );
@@ -298,6 +358,11 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_getInter
         this,
         &cached_field_ids().class_index_child_self_pointer,
     );
+    propagate_error!(
+        env,
+        indexed_class.load_members(),
+        JObject::null().into_raw()
+    );
 
     let result_class = env
         .find_class("com/github/tth05/jindex/IndexedClass")
@@ -357,6 +422,11 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_getGener
         this,
         &cached_field_ids().class_index_child_self_pointer,
     );
+    propagate_error!(
+        env,
+        indexed_class.load_members(),
+        JObject::null().into_raw()
+    );
     let signature = indexed_class.signature();
 
     //No generic signature available
@@ -394,6 +464,11 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_getEnclo
         this,
         &cached_field_ids().class_index_child_self_pointer,
     );
+    propagate_error!(
+        env,
+        indexed_class.load_members(),
+        JObject::null().into_raw()
+    );
     let (class_index_pointer, class_index) = get_class_index(env, this);
 
     if let Some(enclosing_class) = indexed_class.enclosing_class(class_index) {
@@ -424,6 +499,7 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_getInner
         this,
         &cached_field_ids().class_index_child_self_pointer,
     );
+    propagate_error!(env, indexed_class.load_members(), 0);
 
     if let Some(info) = indexed_class.enclosing_type_info() {
         info.inner_class_type().as_index() as jint
@@ -444,6 +520,11 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_getEnclo
         this,
         &cached_field_ids().class_index_child_self_pointer,
     );
+    propagate_error!(
+        env,
+        indexed_class.load_members(),
+        JObject::null().into_raw()
+    );
     let (_, class_index) = get_class_index(env, this);
 
     if let Some(info) = indexed_class.enclosing_type_info() {
@@ -454,15 +535,15 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_getEnclo
         let mut name = class_index
             .constant_pool()
             .string_view_at(*info.method_name().unwrap())
-            .into_ascii_str(class_index.constant_pool())
-            .to_ascii_string();
+            .into_str(class_index.constant_pool())
+            .to_string();
         name.push_str(
-            info.method_descriptor()
+            &info
+                .method_descriptor()
                 .unwrap()
                 //This signature was already a descriptor, meaning no generic param replacement is
                 // needed
-                .to_descriptor_string(class_index, &Vec::new())
-                .as_ascii_str_unchecked(),
+                .to_descriptor_string(class_index, &Vec::new()),
         );
 
         env.new_string(name)
@@ -535,8 +616,12 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_findImpl
     );
     let (class_index_pointer, class_index) = get_class_index(env, this);
 
-    let classes: Vec<_> = class_index
-        .find_implementations_of_class(indexed_class.index(), direct_sub_types_only != 0);
+    let classes = propagate_error!(
+        env,
+        class_index
+            .find_implementations_of_class(indexed_class.index(), direct_sub_types_only != 0),
+        JObject::null().into_raw()
+    );
 
     let result_array = env
         .new_object_array(classes.len() as i32, result_class, JObject::null())
@@ -558,3 +643,482 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_findImpl
 
     result_array
 }
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_getAnnotations(
+    env: JNIEnv,
+    this: JObject,
+) -> jobjectArray {
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+    let indexed_class = get_field_with_id::<IndexedClass>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+    propagate_error!(
+        env,
+        indexed_class.load_members(),
+        JObject::null().into_raw()
+    );
+
+    annotation_classes_to_array(
+        env,
+        class_index_pointer as jlong,
+        class_index,
+        indexed_class.annotations(),
+    )
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_findAnnotatedClasses(
+    env: JNIEnv,
+    this: JObject,
+) -> jobjectArray {
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+    let indexed_class = get_field_with_id::<IndexedClass>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+
+    let classes = propagate_error!(
+        env,
+        class_index.find_annotated(indexed_class.index()),
+        JObject::null().into_raw()
+    )
+    .into_iter()
+    .filter_map(|element| match element {
+        AnnotatedElement::Class(class) => Some(class),
+        _ => None,
+    })
+    .collect::<Vec<_>>();
+
+    let result_class = env
+        .find_class("com/github/tth05/jindex/IndexedClass")
+        .expect("Result class not found");
+    let result_array = env
+        .new_object_array(classes.len() as i32, result_class, JObject::null())
+        .expect("Failed to create result array");
+    for (index, class) in classes.into_iter().enumerate() {
+        let object = env
+            .new_object(
+                result_class,
+                "(JJ)V",
+                &[
+                    JValue::from(class_index_pointer as jlong),
+                    JValue::from((class as *const IndexedClass) as jlong),
+                ],
+            )
+            .expect("Failed to create result object");
+        env.set_object_array_element(result_array, index as i32, object)
+            .expect("Failed to set element into result array");
+    }
+
+    result_array
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_findAnnotatedFields(
+    env: JNIEnv,
+    this: JObject,
+) -> jobjectArray {
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+    let indexed_class = get_field_with_id::<IndexedClass>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+
+    let fields = propagate_error!(
+        env,
+        class_index.find_annotated(indexed_class.index()),
+        JObject::null().into_raw()
+    )
+    .into_iter()
+    .filter_map(|element| match element {
+        AnnotatedElement::Field(class, field) => Some((class, field)),
+        _ => None,
+    })
+    .collect::<Vec<_>>();
+
+    let result_class = env
+        .find_class("com/github/tth05/jindex/IndexedField")
+        .expect("Result class not found");
+    let result_array = env
+        .new_object_array(fields.len() as i32, result_class, JObject::null())
+        .expect("Failed to create result array");
+    for (index, (class, field)) in fields.into_iter().enumerate() {
+        let object = env
+            .new_object(
+                result_class,
+                "(JJJ)V",
+                &[
+                    JValue::from(class_index_pointer as jlong),
+                    JValue::from((class as *const IndexedClass) as jlong),
+                    JValue::from((field as *const IndexedField) as jlong),
+                ],
+            )
+            .expect("Failed to create result object");
+        env.set_object_array_element(result_array, index as i32, object)
+            .expect("Failed to set element into result array");
+    }
+
+    result_array
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_findAnnotatedMethods(
+    env: JNIEnv,
+    this: JObject,
+    include_parameters: jboolean,
+) -> jobjectArray {
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+    let indexed_class = get_field_with_id::<IndexedClass>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+
+    let annotated = propagate_error!(
+        env,
+        class_index.find_annotated(indexed_class.index()),
+        JObject::null().into_raw()
+    );
+    let mut methods: Vec<(&IndexedClass, &IndexedMethod)> = Vec::new();
+    for element in annotated {
+        let method = match element {
+            AnnotatedElement::Method(class, method) => (class, method),
+            AnnotatedElement::Parameter(class, method, _) if include_parameters != 0 => {
+                (class, method)
+            }
+            _ => continue,
+        };
+        // A method with multiple annotated parameters is only returned once
+        if !methods.iter().any(|(_, m)| std::ptr::eq(*m, method.1)) {
+            methods.push(method);
+        }
+    }
+
+    let result_class = env
+        .find_class("com/github/tth05/jindex/IndexedMethod")
+        .expect("Result class not found");
+    let result_array = env
+        .new_object_array(methods.len() as i32, result_class, JObject::null())
+        .expect("Failed to create result array");
+    for (index, (class, method)) in methods.into_iter().enumerate() {
+        let object = env
+            .new_object(
+                result_class,
+                "(JJJ)V",
+                &[
+                    JValue::from(class_index_pointer as jlong),
+                    JValue::from((class as *const IndexedClass) as jlong),
+                    JValue::from((method as *const IndexedMethod) as jlong),
+                ],
+            )
+            .expect("Failed to create result object");
+        env.set_object_array_element(result_array, index as i32, object)
+            .expect("Failed to set element into result array");
+    }
+
+    result_array
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_getPermittedSubclasses(
+    env: JNIEnv,
+    this: JObject,
+) -> jobjectArray {
+    let result_class = env
+        .find_class("com/github/tth05/jindex/IndexedClass")
+        .expect("Result class not found");
+
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+    let indexed_class = get_field_with_id::<IndexedClass>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+    propagate_error!(
+        env,
+        indexed_class.load_members(),
+        JObject::null().into_raw()
+    );
+
+    let classes = indexed_class.permitted_subclasses();
+
+    let result_array = env
+        .new_object_array(classes.len() as i32, result_class, JObject::null())
+        .expect("Failed to create result array");
+    for (index, class) in classes.iter().enumerate() {
+        let object = env
+            .new_object(
+                result_class,
+                "(JJ)V",
+                &[
+                    JValue::from(class_index_pointer as jlong),
+                    JValue::from(
+                        (class_index.class_at_index(*class) as *const IndexedClass) as jlong,
+                    ),
+                ],
+            )
+            .expect("Failed to create result object");
+        env.set_object_array_element(result_array, index as i32, object)
+            .expect("Failed to set element into result array");
+    }
+
+    result_array
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_getRecordComponents(
+    env: JNIEnv,
+    this: JObject,
+) -> jobjectArray {
+    let (class_index_pointer, _) = get_class_index(env, this);
+    let indexed_class = get_field_with_id::<IndexedClass>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+    propagate_error!(
+        env,
+        indexed_class.load_members(),
+        JObject::null().into_raw()
+    );
+
+    let components = match indexed_class.record_components() {
+        Some(components) => components,
+        None => return JObject::null().into_raw(),
+    };
+
+    let result_class = env
+        .find_class("com/github/tth05/jindex/IndexedRecordComponent")
+        .expect("Result class not found");
+
+    let result_array = env
+        .new_object_array(components.len() as i32, result_class, JObject::null())
+        .expect("Failed to create result array");
+    for (index, component) in components.iter().enumerate() {
+        let object = env
+            .new_object(
+                result_class,
+                "(JJJ)V",
+                &[
+                    JValue::from(class_index_pointer as jlong),
+                    JValue::from((indexed_class as *const IndexedClass) as jlong),
+                    JValue::from((component as *const IndexedRecordComponent) as jlong),
+                ],
+            )
+            .expect("Failed to create result object");
+        env.set_object_array_element(result_array, index as i32, object)
+            .expect("Failed to set element into result array");
+    }
+
+    result_array
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_getEnumConstants(
+    env: JNIEnv,
+    this: JObject,
+) -> jobjectArray {
+    let (class_index_pointer, _) = get_class_index(env, this);
+    let indexed_class = get_field_with_id::<IndexedClass>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+    propagate_error!(
+        env,
+        indexed_class.load_members(),
+        JObject::null().into_raw()
+    );
+
+    let constants = indexed_class.enum_constants().collect::<Vec<_>>();
+
+    let result_class = env
+        .find_class("com/github/tth05/jindex/IndexedField")
+        .expect("Result class not found");
+
+    let result_array = env
+        .new_object_array(constants.len() as i32, result_class, JObject::null())
+        .expect("Failed to create result array");
+    for (index, field) in constants.into_iter().enumerate() {
+        let object = env
+            .new_object(
+                result_class,
+                "(JJJ)V",
+                &[
+                    JValue::from(class_index_pointer as jlong),
+                    JValue::from((indexed_class as *const IndexedClass) as jlong),
+                    JValue::from((field as *const IndexedField) as jlong),
+                ],
+            )
+            .expect("Failed to create result object");
+        env.set_object_array_element(result_array, index as i32, object)
+            .expect("Failed to set element into result array");
+    }
+
+    result_array
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_getNestHost(
+    env: JNIEnv,
+    this: JObject,
+) -> jobject {
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+    let indexed_class = get_field_with_id::<IndexedClass>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+    propagate_error!(
+        env,
+        indexed_class.load_members(),
+        JObject::null().into_raw()
+    );
+
+    env.new_object(
+        env.find_class("com/github/tth05/jindex/IndexedClass")
+            .expect("Result class not found"),
+        "(JJ)V",
+        &[
+            JValue::from(class_index_pointer as jlong),
+            JValue::from((class_index.nest_host_of(indexed_class) as *const IndexedClass) as jlong),
+        ],
+    )
+    .expect("Failed to create result object")
+    .into_raw()
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_getNestMembers(
+    env: JNIEnv,
+    this: JObject,
+) -> jobjectArray {
+    let result_class = env
+        .find_class("com/github/tth05/jindex/IndexedClass")
+        .expect("Result class not found");
+
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+    let indexed_class = get_field_with_id::<IndexedClass>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+    propagate_error!(
+        env,
+        indexed_class.load_members(),
+        JObject::null().into_raw()
+    );
+
+    let classes = indexed_class.nest_members();
+
+    let result_array = env
+        .new_object_array(classes.len() as i32, result_class, JObject::null())
+        .expect("Failed to create result array");
+    for (index, class) in classes.iter().enumerate() {
+        let object = env
+            .new_object(
+                result_class,
+                "(JJ)V",
+                &[
+                    JValue::from(class_index_pointer as jlong),
+                    JValue::from(
+                        (class_index.class_at_index(*class) as *const IndexedClass) as jlong,
+                    ),
+                ],
+            )
+            .expect("Failed to create result object");
+        env.set_object_array_element(result_array, index as i32, object)
+            .expect("Failed to set element into result array");
+    }
+
+    result_array
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_isAccessibleFrom(
+    env: JNIEnv,
+    this: JObject,
+    from_class: JObject,
+) -> jboolean {
+    let (_, class_index) = get_class_index(env, this);
+    propagate_error!(env, class_index.load_all_members(), false as jboolean);
+    let indexed_class = get_field_with_id::<IndexedClass>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+    let from_class = get_field_with_id::<IndexedClass>(
+        env,
+        from_class,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+
+    class_index.is_accessible(from_class, indexed_class, None) as jboolean
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedClass_findUsages(
+    env: JNIEnv,
+    this: JObject,
+) -> jobjectArray {
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+    let indexed_class = get_field_with_id::<IndexedClass>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+
+    let methods = propagate_error!(
+        env,
+        class_index.find_usages_of_class(indexed_class.index()),
+        JObject::null().into_raw()
+    );
+    methods_to_array(env, class_index_pointer, &methods)
+}
+
+/// Checks the access to a member of a class from Java
+pub(crate) unsafe fn is_member_accessible(
+    env: JNIEnv,
+    this: JObject,
+    from_class: JObject,
+    member: ClassMember,
+) -> jboolean {
+    let (_, class_index) = get_class_index(env, this);
+    propagate_error!(env, class_index.load_all_members(), false as jboolean);
+    let declaring_class =
+        get_field_with_id::<IndexedClass>(env, this, &cached_field_ids().class_child_class_pointer);
+    let from_class = get_field_with_id::<IndexedClass>(
+        env,
+        from_class,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+
+    class_index.is_accessible(from_class, declaring_class, Some(member)) as jboolean
+}
@@ -1,10 +1,16 @@
+use crate::class_index::ClassMember;
 use crate::class_index_members::{IndexedClass, IndexedField};
-use jni::objects::JObject;
-use jni::sys::{jint, jstring};
+use crate::constant_value::ConstantValue;
+use jni::objects::{JObject, JValue};
+use jni::sys::{jboolean, jint, jlong, jobject, jobjectArray, jstring};
 use jni::JNIEnv;
 
 use crate::jni::cache::{cached_field_ids, get_class_index, get_field_with_id};
-use crate::jni::{collect_type_parameters, is_basic_signature_type};
+use crate::jni::jni_indexed_class::is_member_accessible;
+use crate::jni::{
+    annotation_classes_to_array, collect_type_parameters, is_basic_signature_type,
+    methods_to_array, propagate_error,
+};
 use crate::signature::indexed_signature::{ToDescriptorIndexedType, ToSignatureIndexedType};
 
 #[no_mangle]
@@ -95,3 +101,100 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedField_getGener
         .expect("Unable to create descriptor String")
         .into_raw()
 }
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedField_getAnnotations(
+    env: JNIEnv,
+    this: JObject,
+) -> jobjectArray {
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+    let indexed_field = get_field_with_id::<IndexedField>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+
+    annotation_classes_to_array(
+        env,
+        class_index_pointer as jlong,
+        class_index,
+        indexed_field.annotations(),
+    )
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedField_getConstantValue(
+    env: JNIEnv,
+    this: JObject,
+) -> jobject {
+    let (_, class_index) = get_class_index(env, this);
+    let indexed_field = get_field_with_id::<IndexedField>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+
+    let (class, signature, value) = match indexed_field.constant_value() {
+        Some(ConstantValue::Int(v)) => ("java/lang/Integer", "(I)V", JValue::from(*v)),
+        Some(ConstantValue::Long(v)) => ("java/lang/Long", "(J)V", JValue::from(*v)),
+        Some(ConstantValue::Float(v)) => ("java/lang/Float", "(F)V", JValue::from(*v)),
+        Some(ConstantValue::Double(v)) => ("java/lang/Double", "(D)V", JValue::from(*v)),
+        Some(ConstantValue::String(v)) => {
+            let constant_pool = class_index.constant_pool();
+            return env
+                .new_string(constant_pool.string_view_at(*v).into_str(constant_pool))
+                .expect("Failed to create String")
+                .into_raw();
+        }
+        None => return JObject::null().into_raw(),
+    };
+
+    env.new_object(class, signature, &[value])
+        .expect("Failed to create result object")
+        .into_raw()
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedField_isAccessibleFrom(
+    env: JNIEnv,
+    this: JObject,
+    from_class: JObject,
+) -> jboolean {
+    let indexed_field = get_field_with_id::<IndexedField>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+
+    is_member_accessible(env, this, from_class, ClassMember::Field(indexed_field))
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedField_findUsages(
+    env: JNIEnv,
+    this: JObject,
+) -> jobjectArray {
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+    let indexed_field = get_field_with_id::<IndexedField>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+    let indexed_class =
+        get_field_with_id::<IndexedClass>(env, this, &cached_field_ids().class_child_class_pointer);
+
+    let methods = propagate_error!(
+        env,
+        class_index.find_usages_of_field(indexed_class.index(), indexed_field),
+        JObject::null().into_raw()
+    );
+    methods_to_array(env, class_index_pointer, &methods)
+}
@@ -1,11 +1,15 @@
-use crate::class_index::{ClassIndex, MethodWithClass};
+use crate::class_index::{ClassIndex, ClassMember, MethodWithClass};
 use crate::class_index_members::{IndexedClass, IndexedMethod};
 use crate::jni::cache::{cached_field_ids, get_class_index, get_field_with_id};
-use crate::jni::{collect_type_parameters, is_basic_signature_type};
+use crate::jni::jni_indexed_class::is_member_accessible;
+use crate::jni::{
+    annotation_classes_to_array, collect_type_parameters, is_basic_signature_type,
+    methods_to_array, propagate_error,
+};
 use crate::signature::indexed_signature::{ToDescriptorIndexedType, ToSignatureIndexedType};
 use crate::signature::{IndexedMethodSignature, IndexedSignatureType, TypeParameterData};
 use jni::objects::{JObject, JValue};
-use jni::sys::{jint, jlong, jobject, jobjectArray, jsize, jstring};
+use jni::sys::{jboolean, jint, jlong, jobject, jobjectArray, jsize, jstring};
 use jni::JNIEnv;
 
 #[no_mangle]
@@ -233,7 +237,11 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedMethod_findImp
     let indexed_class =
         get_field_with_id::<IndexedClass>(env, this, &cached_field_ids().class_child_class_pointer);
 
-    let impls = class_index.find_implementations_of_method(indexed_class.index(), indexed_method);
+    let impls = propagate_error!(
+        env,
+        class_index.find_implementations_of_method(indexed_class.index(), indexed_method),
+        JObject::null().into_raw()
+    );
 
     let result_class = env
         .find_class("com/github/tth05/jindex/IndexedMethod")
@@ -270,6 +278,11 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedMethod_findBas
     this: JObject,
 ) -> jobjectArray {
     let (class_index_pointer, class_index) = get_class_index(env, this);
+    propagate_error!(
+        env,
+        class_index.load_all_members(),
+        JObject::null().into_raw()
+    );
 
     let indexed_method = get_field_with_id::<IndexedMethod>(
         env,
@@ -307,3 +320,117 @@ pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedMethod_findBas
 
     result_array
 }
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedMethod_getAnnotations(
+    env: JNIEnv,
+    this: JObject,
+) -> jobjectArray {
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+    let indexed_method = get_field_with_id::<IndexedMethod>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+
+    annotation_classes_to_array(
+        env,
+        class_index_pointer as jlong,
+        class_index,
+        indexed_method.annotations(),
+    )
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedMethod_getParameterAnnotations(
+    env: JNIEnv,
+    this: JObject,
+    parameter_index: jint,
+) -> jobjectArray {
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+    let indexed_method = get_field_with_id::<IndexedMethod>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+
+    let annotations = usize::try_from(parameter_index)
+        .ok()
+        .and_then(|index| indexed_method.parameter_annotations().get(index))
+        .map_or(&[][..], |annotations| annotations.as_slice());
+    annotation_classes_to_array(env, class_index_pointer as jlong, class_index, annotations)
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedMethod_getParameterNames(
+    env: JNIEnv,
+    this: JObject,
+) -> jobjectArray {
+    let (_, class_index) = get_class_index(env, this);
+    let indexed_method = get_field_with_id::<IndexedMethod>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+
+    let name_count = indexed_method.parameter_name_indices().len();
+    let result_array = env
+        .new_object_array(name_count as jsize, "java/lang/String", JObject::null())
+        .expect("Failed to create result array");
+    for index in 0..name_count {
+        if let Some(name) = indexed_method.parameter_name(class_index.constant_pool(), index) {
+            let string = env.new_string(name).expect("Failed to create String");
+            env.set_object_array_element(result_array, index as i32, string)
+                .expect("Failed to set element into result array");
+        }
+    }
+
+    result_array
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedMethod_isAccessibleFrom(
+    env: JNIEnv,
+    this: JObject,
+    from_class: JObject,
+) -> jboolean {
+    let indexed_method = get_field_with_id::<IndexedMethod>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+
+    is_member_accessible(env, this, from_class, ClassMember::Method(indexed_method))
+}
+
+#[no_mangle]
+/// # Safety
+/// The pointer field has to be valid...
+pub unsafe extern "system" fn Java_com_github_tth05_jindex_IndexedMethod_findUsages(
+    env: JNIEnv,
+    this: JObject,
+) -> jobjectArray {
+    let (class_index_pointer, class_index) = get_class_index(env, this);
+    let indexed_method = get_field_with_id::<IndexedMethod>(
+        env,
+        this,
+        &cached_field_ids().class_index_child_self_pointer,
+    );
+    let indexed_class =
+        get_field_with_id::<IndexedClass>(env, this, &cached_field_ids().class_child_class_pointer);
+
+    let methods = propagate_error!(
+        env,
+        class_index.find_usages_of_method(indexed_class.index(), indexed_method),
+        JObject::null().into_raw()
+    );
+    methods_to_array(env, class_index_pointer, &methods)
+}
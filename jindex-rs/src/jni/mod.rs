@@ -1,9 +1,10 @@
+use crate::annotation::IndexedAnnotation;
 use crate::class_index::ClassIndex;
-use crate::class_index_members::IndexedClass;
+use crate::class_index_members::{IndexedClass, IndexedMethod};
 use crate::signature::{IndexedSignatureType, IndexedTypeParameterData, SignatureType};
-use ascii::AsAsciiStr;
 use cafebabe::attributes::InnerClassAccessFlags;
-use jni::objects::JObject;
+use jni::objects::{JObject, JValue};
+use jni::sys::{jlong, jobjectArray};
 use jni::JNIEnv;
 
 mod cache;
@@ -12,12 +13,10 @@ pub mod jni_indexed_class;
 pub mod jni_indexed_field;
 pub mod jni_indexed_method;
 pub mod jni_indexed_package;
+pub mod jni_indexed_record_component;
 
 unsafe fn get_java_lang_object(class_index: &ClassIndex) -> Option<&IndexedClass> {
-    class_index.find_class(
-        "java/lang".as_ascii_str_unchecked(),
-        "Object".as_ascii_str_unchecked(),
-    )
+    class_index.find_class("java/lang", "Object")
 }
 
 unsafe fn get_enum_ordinal(env: JNIEnv, enum_object: JObject) -> u32 {
@@ -37,7 +36,7 @@ macro_rules! propagate_error {
             Err(error) => {
                 $env.throw_new(
                     "com/github/tth05/jindex/ClassIndexBuildingException",
-                    error.to_string(),
+                    format!("{:#}", error),
                 )
                 .expect("Failed to throw exception");
                 return $return_value;
@@ -48,10 +47,112 @@ macro_rules! propagate_error {
 
 pub(crate) use propagate_error;
 
+/// Creates an array of the classes of the given annotations. Annotations whose class is not part of
+/// the index are skipped.
+unsafe fn annotation_classes_to_array(
+    env: JNIEnv,
+    class_index_pointer: jlong,
+    class_index: &ClassIndex,
+    annotations: &[IndexedAnnotation],
+) -> jobjectArray {
+    let result_class = env
+        .find_class("com/github/tth05/jindex/IndexedClass")
+        .expect("Result class not found");
+
+    let classes = annotations
+        .iter()
+        .filter_map(|annotation| annotation.annotation_class())
+        .collect::<Vec<_>>();
+    let result_array = env
+        .new_object_array(classes.len() as i32, result_class, JObject::null())
+        .expect("Failed to create result array");
+    for (index, class) in classes.into_iter().enumerate() {
+        let class = class_index.class_at_index(class);
+        let object = env
+            .new_object(
+                result_class,
+                "(JJ)V",
+                &[
+                    JValue::from(class_index_pointer),
+                    JValue::from((class as *const IndexedClass) as jlong),
+                ],
+            )
+            .expect("Failed to create result object");
+        env.set_object_array_element(result_array, index as i32, object)
+            .expect("Failed to set element into result array");
+    }
+
+    result_array
+}
+
+/// Creates an array of the given methods
+unsafe fn methods_to_array(
+    env: JNIEnv,
+    class_index_pointer: jlong,
+    methods: &[(&IndexedClass, &IndexedMethod)],
+) -> jobjectArray {
+    let result_class = env
+        .find_class("com/github/tth05/jindex/IndexedMethod")
+        .expect("Result class not found");
+
+    let result_array = env
+        .new_object_array(methods.len() as i32, result_class, JObject::null())
+        .expect("Failed to create result array");
+    for (index, (class, method)) in methods.iter().enumerate() {
+        let object = env
+            .new_object(
+                result_class,
+                "(JJJ)V",
+                &[
+                    JValue::from(class_index_pointer),
+                    JValue::from((*class as *const IndexedClass) as jlong),
+                    JValue::from((*method as *const IndexedMethod) as jlong),
+                ],
+            )
+            .expect("Failed to create result object");
+        env.set_object_array_element(result_array, index as i32, object)
+            .expect("Failed to set element into result array");
+    }
+
+    result_array
+}
+
+/// Creates an array of `SearchResult` objects from the given scores and values
+unsafe fn search_results_to_array<'a>(
+    env: JNIEnv<'a>,
+    results: impl ExactSizeIterator<Item = (usize, JObject<'a>)>,
+) -> jobjectArray {
+    let result_class = env
+        .find_class("com/github/tth05/jindex/SearchResult")
+        .expect("Result class not found");
+
+    let result_array = env
+        .new_object_array(results.len() as i32, result_class, JObject::null())
+        .expect("Failed to create result array");
+    for (index, (score, value)) in results.enumerate() {
+        let object = env
+            .new_object(
+                result_class,
+                "(Ljava/lang/Object;I)V",
+                &[
+                    JValue::from(value),
+                    JValue::from(score.min(i32::MAX as usize) as i32),
+                ],
+            )
+            .expect("Failed to create result object");
+        env.set_object_array_element(result_array, index as i32, object)
+            .expect("Failed to set element into result array");
+    }
+
+    result_array
+}
+
 fn is_basic_signature_type(s: &IndexedSignatureType) -> bool {
     match s {
         SignatureType::Array(inner) => is_basic_signature_type(inner),
-        SignatureType::Unresolved | SignatureType::Primitive(_) | SignatureType::Object(_) => true,
+        SignatureType::Unresolved(_) | SignatureType::Primitive(_) | SignatureType::Object(_) => {
+            true
+        }
         _ => false,
     }
 }
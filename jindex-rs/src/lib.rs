@@ -2,31 +2,132 @@
 #![feature(once_cell)]
 #![feature(try_blocks)]
 
-use ascii::{AsAsciiStr, AsciiChar, AsciiStr};
 use mimalloc::MiMalloc;
 
+pub mod annotation;
 pub mod builder;
+pub mod call_graph;
 pub mod class_index;
 pub mod class_index_members;
 pub mod constant_pool;
+pub mod constant_value;
 pub mod io;
+pub mod name_pattern;
 pub mod package_index;
+pub mod reference;
 pub mod signature;
+mod validation;
 
 pub mod jni;
 
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
 
-pub(crate) fn rsplit_once(str: &AsciiStr, separator: AsciiChar) -> (&AsciiStr, &AsciiStr) {
-    str.chars()
-        .enumerate()
-        .rev()
-        .find(|(_, c)| *c == separator)
-        .map(|(i, _)| (&str[0..i], &str[(i + 1)..]))
-        .unwrap_or_else(|| (unsafe { "".as_ascii_str_unchecked() }, str))
+pub(crate) fn rsplit_once(str: &str, separator: char) -> (&str, &str) {
+    str.rsplit_once(separator).unwrap_or(("", str))
 }
 
 #[cfg(test)]
-mod test {
+pub(crate) mod test {
+    use crate::builder::{ClassInfo, MethodInfo};
+    use crate::signature::{RawClassSignature, RawMethodSignature};
+    use cafebabe::MethodAccessFlags;
+    use std::io::{Cursor, Write};
+    use std::path::PathBuf;
+    use std::str::FromStr;
+    use std::sync::Arc;
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    /// Creates a class from the source `a.jar` with the given signature and a single public method
+    /// called `get`
+    pub(crate) fn class_info(name: &str, signature: &str, method_signature: &str) -> ClassInfo {
+        let (package_name, class_name) = name.rsplit_once('/').unwrap();
+        ClassInfo {
+            package_name: package_name.into(),
+            class_name: class_name.into(),
+            class_name_start_index: class_name.rfind('$').map_or(0, |i| i + 1),
+            access_flags: 0,
+            class_file_version: 52,
+            module_name: None,
+            source: Some(Arc::from("a.jar")),
+            enclosing_type: None,
+            member_classes: None,
+            signature: RawClassSignature::from_str(signature).unwrap(),
+            fields: Vec::new(),
+            methods: vec![MethodInfo {
+                method_name: "get".into(),
+                signature: RawMethodSignature::from_data(method_signature, &|| None).unwrap(),
+                access_flags: MethodAccessFlags::PUBLIC,
+                annotations: Vec::new(),
+                parameter_annotations: Vec::new(),
+                parameter_names: Vec::new(),
+                references: Vec::new(),
+            }],
+            annotations: Vec::new(),
+            permitted_subclasses: Vec::new(),
+            nest_host: None,
+            nest_members: Vec::new(),
+            record_components: None,
+            string_literals: Vec::new(),
+        }
+    }
+
+    /// Creates a minimal class file extending `java/lang/Object` which declares a public int field
+    /// for each of the given names
+    pub(crate) fn class_bytes(name: &str, major_version: u16, fields: &[&str]) -> Vec<u8> {
+        let mut constant_pool = Vec::new();
+        let mut add_utf8 = |value: &str| {
+            constant_pool.push(1u8);
+            constant_pool.extend_from_slice(&(value.len() as u16).to_be_bytes());
+            constant_pool.extend_from_slice(value.as_bytes());
+        };
+        add_utf8(name);
+        add_utf8("java/lang/Object");
+        add_utf8("I");
+        fields.iter().for_each(|field| add_utf8(field));
+        for name_index in [1u16, 2] {
+            constant_pool.push(7);
+            constant_pool.extend_from_slice(&name_index.to_be_bytes());
+        }
+        let class_index = 4 + fields.len() as u16;
+
+        let mut bytes = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0];
+        bytes.extend_from_slice(&major_version.to_be_bytes());
+        bytes.extend_from_slice(&(class_index + 2).to_be_bytes());
+        bytes.extend_from_slice(&constant_pool);
+        // Access flags, this class, super class and no interfaces
+        for value in [0x21, class_index, class_index + 1, 0, fields.len() as u16] {
+            bytes.extend_from_slice(&value.to_be_bytes());
+        }
+        for index in 0..fields.len() as u16 {
+            for value in [0x1, 4 + index, 3, 0] {
+                bytes.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+        // No methods and attributes
+        bytes.extend_from_slice(&[0, 0, 0, 0]);
+        bytes
+    }
+
+    pub(crate) fn zip_bytes(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in entries {
+            writer.start_file(*name, FileOptions::default()).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    /// Returns an empty directory which is only used by the test with the given name
+    pub(crate) fn temp_dir(test_name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "jindex-rs-{}-{}",
+            test_name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        path
+    }
 }
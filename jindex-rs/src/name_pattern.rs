@@ -0,0 +1,142 @@
+use anyhow::Result;
+use regex_automata::hybrid::dfa::{Cache, DFA};
+use regex_automata::hybrid::LazyStateID;
+use regex_automata::{Anchored, Input, MatchKind};
+
+/// A pattern which is matched against the fully qualified name of a class, e.g.
+/// `java/util/Map$Entry`. Partial names can be fed to the pattern one package at a time, which
+/// allows skipping packages whose classes can't match.
+pub struct NamePattern {
+    dfa: DFA,
+}
+
+impl NamePattern {
+    /// Creates a pattern from a regex which has to match the whole name
+    pub fn regex(pattern: &str) -> Result<Self> {
+        let dfa = DFA::builder()
+            .configure(DFA::config().match_kind(MatchKind::All))
+            .build(&format!("(?:{})$", pattern))?;
+        Ok(Self { dfa })
+    }
+
+    /// Creates a pattern from a glob which has to match the whole name. `*` matches any part of a
+    /// package or class name, `?` matches a single char and `**` matches any number of packages,
+    /// e.g. `org/apache/**/util/*Utils`.
+    pub fn glob(pattern: &str) -> Result<Self> {
+        let segments = pattern.split('/').collect::<Vec<_>>();
+        let mut regex = String::with_capacity(pattern.len() * 2);
+        for (index, segment) in segments.iter().enumerate() {
+            let is_last = index == segments.len() - 1;
+            if *segment == "**" {
+                regex.push_str(if is_last { ".*" } else { "(?:[^/]*/)*" });
+                continue;
+            }
+
+            for c in segment.chars() {
+                match c {
+                    '*' => regex.push_str("[^/]*"),
+                    '?' => regex.push_str("[^/]"),
+                    c => {
+                        if "\\.+*?()|[]{}^$#&-~".contains(c) {
+                            regex.push('\\');
+                        }
+                        regex.push(c);
+                    }
+                }
+            }
+            if !is_last {
+                regex.push('/');
+            }
+        }
+
+        Self::regex(&regex)
+    }
+
+    pub(crate) fn create_cache(&self) -> Cache {
+        self.dfa.create_cache()
+    }
+
+    /// Returns the state before any part of a name was fed to the pattern
+    pub(crate) fn start_state(&self, cache: &mut Cache) -> Result<LazyStateID> {
+        Ok(self
+            .dfa
+            .start_state_forward(cache, &Input::new("").anchored(Anchored::Yes))?)
+    }
+
+    /// Feeds the given part of a name to the pattern. The returned state is dead if no name
+    /// starting with the parts fed so far can match.
+    pub(crate) fn feed(
+        &self,
+        cache: &mut Cache,
+        mut state: LazyStateID,
+        part: &str,
+    ) -> Result<LazyStateID> {
+        for byte in part.as_bytes() {
+            if state.is_dead() {
+                break;
+            }
+            state = self.dfa.next_state(cache, state, *byte)?;
+        }
+
+        Ok(state)
+    }
+
+    /// Checks whether the name which was fed to the pattern matches it
+    pub(crate) fn is_match(&self, cache: &mut Cache, state: LazyStateID) -> Result<bool> {
+        Ok(!state.is_dead() && self.dfa.next_eoi_state(cache, state)?.is_match())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NamePattern;
+    use crate::builder::workers::{create_class_index_from_infos, DuplicateClassPolicy};
+    use crate::test::class_info;
+
+    #[test]
+    fn test_name_patterns() {
+        let (_, class_index) = create_class_index_from_infos(
+            [
+                "a/util/IoUtils",
+                "a/b/c/util/StringUtils",
+                "a/b/Utils",
+                "d/util/DUtils",
+            ]
+            .into_iter()
+            .map(|name| class_info(name, "Ljava/lang/Object;", "()V"))
+            .collect(),
+            DuplicateClassPolicy::First,
+        )
+        .unwrap();
+
+        let find = |pattern: NamePattern| {
+            let mut names = class_index
+                .find_classes_matching(&pattern, usize::MAX)
+                .unwrap()
+                .into_iter()
+                .map(|class| {
+                    class.class_name_with_package(
+                        class_index.package_index(),
+                        class_index.constant_pool(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            names.sort_unstable();
+            names
+        };
+        assert_eq!(
+            find(NamePattern::glob("a/**/util/*Utils").unwrap()),
+            vec!["a/b/c/util/StringUtils", "a/util/IoUtils"]
+        );
+        assert_eq!(
+            find(NamePattern::glob("?/*/Util?").unwrap()),
+            vec!["a/b/Utils"]
+        );
+        assert!(find(NamePattern::glob("Utils").unwrap()).is_empty());
+        assert_eq!(
+            find(NamePattern::regex("[ad]/util/.*").unwrap()),
+            vec!["a/util/IoUtils", "d/util/DUtils"]
+        );
+        assert!(NamePattern::regex("a/(").is_err());
+    }
+}
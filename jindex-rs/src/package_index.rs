@@ -1,5 +1,4 @@
 use crate::constant_pool::ClassIndexConstantPool;
-use ascii::{AsAsciiStr, AsciiStr, AsciiString};
 use atomic_refcell::{AtomicRef, AtomicRefCell};
 use compact_str::CompactString;
 use speedy::{Readable, Writable};
@@ -33,7 +32,7 @@ impl PackageIndex {
         constant_pool: &mut ClassIndexConstantPool,
         name: &str,
     ) -> u32 {
-        let slash_index_or_none = name.chars().position(|char| char == '/');
+        let slash_index_or_none = name.find('/');
         let sub_name = match slash_index_or_none {
             Some(dot_index) => &name[..dot_index],
             None => name,
@@ -82,6 +81,10 @@ impl PackageIndex {
     pub fn package_at(&self, index: u32) -> &IndexedPackage {
         self.indexed_packages.get(index as usize).unwrap()
     }
+
+    pub fn packages(&self) -> &Vec<IndexedPackage> {
+        &self.indexed_packages
+    }
 }
 
 pub struct IndexedPackage {
@@ -105,17 +108,17 @@ impl IndexedPackage {
         self.sub_classes_indices.borrow_mut().push(class_index);
     }
 
-    pub fn package_name<'a>(&self, constant_pool: &'a ClassIndexConstantPool) -> &'a AsciiStr {
+    pub fn package_name<'a>(&self, constant_pool: &'a ClassIndexConstantPool) -> &'a str {
         constant_pool
             .string_view_at(self.package_name_index)
-            .into_ascii_str(constant_pool)
+            .into_str(constant_pool)
     }
 
     pub fn package_name_with_parents_cmp(
         &self,
         package_index: &PackageIndex,
         constant_pool: &ClassIndexConstantPool,
-        str: &AsciiStr,
+        str: &str,
     ) -> Ordering {
         let mut index = str.len() - 1;
 
@@ -133,7 +136,7 @@ impl IndexedPackage {
             for i in (0..current_part.len()).rev() {
                 let compare = current_part
                     .byte_at(constant_pool, i)
-                    .cmp(&str[index].as_byte());
+                    .cmp(&str.as_bytes()[index]);
                 if compare != Ordering::Equal {
                     return compare;
                 }
@@ -149,7 +152,7 @@ impl IndexedPackage {
             }
 
             //If we do not end a slash, the package names don't match
-            if str[index] != '/' {
+            if str.as_bytes()[index] != b'/' {
                 return Ordering::Less;
             } else {
                 index -= 1;
@@ -170,12 +173,12 @@ impl IndexedPackage {
         &self,
         package_index: &PackageIndex,
         constant_pool: &ClassIndexConstantPool,
-    ) -> AsciiString {
+    ) -> String {
         let mut parts = Vec::with_capacity(3);
         parts.push(
             constant_pool
                 .string_view_at(self.package_name_index)
-                .into_ascii_str(constant_pool),
+                .into_str(constant_pool),
         );
 
         let mut total_length = parts.first().unwrap().len();
@@ -189,11 +192,11 @@ impl IndexedPackage {
             parent_index = parent_package.previous_package_index;
         }
 
-        let mut result = AsciiString::with_capacity(total_length);
+        let mut result = String::with_capacity(total_length);
         parts.iter().rev().enumerate().for_each(|(i, part)| {
             //Add separator if we're not the last part
             if i != 0 {
-                unsafe { result.push_str("/".as_ascii_str_unchecked()) }
+                result.push('/');
             }
 
             result.push_str(part)
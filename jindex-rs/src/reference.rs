@@ -0,0 +1,362 @@
+use crate::all_direct_super_types;
+use crate::builder::ClassToIndexMap;
+use crate::class_index::ClassIndex;
+use crate::class_index_members::IndexedClass;
+use crate::constant_pool::ClassIndexConstantPool;
+use crate::signature::indexed_signature::{
+    index_for_object_type, raw_class_name, ToIndexedType, ToRawType,
+};
+use crate::signature::RawMethodSignature;
+use crate::validation::{IndexBounds, Validate};
+use compact_str::{CompactString, ToCompactString};
+use rustc_hash::{FxHashMap, FxHashSet};
+use speedy::{Readable, Writable};
+
+/// A reference from the code of a method to a class or one of its members
+#[derive(Debug, PartialEq)]
+pub enum RawReference {
+    /// Any invoke instruction except `invokedynamic`
+    Method {
+        class_name: CompactString,
+        method_name: CompactString,
+        signature: RawMethodSignature,
+    },
+    FieldRead {
+        class_name: CompactString,
+        field_name: CompactString,
+    },
+    FieldWrite {
+        class_name: CompactString,
+        field_name: CompactString,
+    },
+    New(CompactString),
+    CheckCast(CompactString),
+    InstanceOf(CompactString),
+}
+
+/// A [RawReference] whose class and member were resolved. Members are referred to by their index
+/// in the fields or methods of the class which declares them.
+#[derive(Readable, Writable, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IndexedReference {
+    Method { class_index: u32, method_index: u32 },
+    FieldRead { class_index: u32, field_index: u32 },
+    FieldWrite { class_index: u32, field_index: u32 },
+    New(u32),
+    CheckCast(u32),
+    InstanceOf(u32),
+}
+
+impl IndexedReference {
+    /// The class which is referenced, or which declares the referenced member
+    pub fn class_index(&self) -> u32 {
+        match self {
+            IndexedReference::Method { class_index, .. }
+            | IndexedReference::FieldRead { class_index, .. }
+            | IndexedReference::FieldWrite { class_index, .. } => *class_index,
+            IndexedReference::New(class_index)
+            | IndexedReference::CheckCast(class_index)
+            | IndexedReference::InstanceOf(class_index) => *class_index,
+        }
+    }
+}
+
+/// Resolves the given references. Members which are not declared in the referenced class are
+/// searched in its super types. References to classes or members which are not part of the index
+/// are dropped, duplicates are only kept once.
+pub(crate) fn index_references<'b>(
+    references: &[RawReference],
+    constant_pool: &ClassIndexConstantPool,
+    class_to_index_map: &ClassToIndexMap,
+    class_at_index: &dyn Fn(u32) -> &'b IndexedClass,
+) -> anyhow::Result<Vec<IndexedReference>> {
+    let mut indexed_references = Vec::with_capacity(references.len());
+    let mut seen = FxHashSet::default();
+    for reference in references {
+        let indexed_reference = match reference {
+            RawReference::Method {
+                class_name,
+                method_name,
+                signature,
+            } => match index_for_object_type(class_name, class_to_index_map) {
+                Some(class_index) => {
+                    // The signature is only used to select the method, therefore its strings are
+                    // added to a separate pool instead of the pool of the index
+                    let signature = signature.to_indexed_type(
+                        &mut ClassIndexConstantPool::new(0),
+                        &mut FxHashMap::default(),
+                        class_to_index_map,
+                    )?;
+                    find_declaring_class(class_index, class_at_index, &|class| {
+                        class.methods().iter().position(|method| {
+                            method.method_name(constant_pool) == *method_name
+                                && method.method_signature().eq_erased_parameters(&signature)
+                        })
+                    })
+                    .map(|(class_index, method_index)| {
+                        IndexedReference::Method {
+                            class_index,
+                            method_index,
+                        }
+                    })
+                }
+                None => None,
+            },
+            RawReference::FieldRead {
+                class_name,
+                field_name,
+            }
+            | RawReference::FieldWrite {
+                class_name,
+                field_name,
+            } => index_for_object_type(class_name, class_to_index_map)
+                .and_then(|class_index| {
+                    find_declaring_class(class_index, class_at_index, &|class| {
+                        class
+                            .fields()
+                            .iter()
+                            .position(|field| field.field_name(constant_pool) == *field_name)
+                    })
+                })
+                .map(|(class_index, field_index)| {
+                    if matches!(reference, RawReference::FieldRead { .. }) {
+                        IndexedReference::FieldRead {
+                            class_index,
+                            field_index,
+                        }
+                    } else {
+                        IndexedReference::FieldWrite {
+                            class_index,
+                            field_index,
+                        }
+                    }
+                }),
+            RawReference::New(class_name) => {
+                index_for_object_type(class_name, class_to_index_map).map(IndexedReference::New)
+            }
+            RawReference::CheckCast(class_name) => {
+                index_for_object_type(class_name, class_to_index_map)
+                    .map(IndexedReference::CheckCast)
+            }
+            RawReference::InstanceOf(class_name) => {
+                index_for_object_type(class_name, class_to_index_map)
+                    .map(IndexedReference::InstanceOf)
+            }
+        };
+
+        if let Some(indexed_reference) = indexed_reference.filter(|r| seen.insert(*r)) {
+            indexed_references.push(indexed_reference);
+        }
+    }
+
+    Ok(indexed_references)
+}
+
+/// Searches the given class and then its super types for a member. Returns the index of the class
+/// which declares the member and the index of the member in that class.
+fn find_declaring_class<'b>(
+    class_index: u32,
+    class_at_index: &dyn Fn(u32) -> &'b IndexedClass,
+    find_member: &dyn Fn(&'b IndexedClass) -> Option<usize>,
+) -> Option<(u32, u32)> {
+    // Interfaces can be reached through multiple paths, and the super types of malformed classes
+    // can form a cycle
+    let mut visited = FxHashSet::default();
+    let mut queue = vec![class_index];
+    while let Some(current_index) = queue.pop() {
+        if !visited.insert(current_index) {
+            continue;
+        }
+
+        let current = class_at_index(current_index);
+        if let Some(member_index) = find_member(current) {
+            return Some((current_index, member_index as u32));
+        }
+
+        // Push in reverse to search the super class before the interfaces
+        let super_types = all_direct_super_types!(current)
+            .filter_map(|t| t.extract_base_object_type())
+            .collect::<Vec<_>>();
+        queue.extend(super_types.into_iter().rev());
+    }
+
+    None
+}
+
+impl ToRawType for IndexedReference {
+    type Out = RawReference;
+
+    fn to_raw_type(&self, class_index: &ClassIndex) -> Self::Out {
+        let constant_pool = class_index.constant_pool();
+        let class_name = raw_class_name(class_index, self.class_index());
+        match self {
+            IndexedReference::Method {
+                class_index: declaring_class,
+                method_index,
+            } => {
+                let method =
+                    &class_index.class_at_index(*declaring_class).methods()[*method_index as usize];
+                RawReference::Method {
+                    class_name,
+                    method_name: method.method_name(constant_pool).to_compact_string(),
+                    signature: method.method_signature().to_raw_type(class_index),
+                }
+            }
+            IndexedReference::FieldRead {
+                class_index: declaring_class,
+                field_index,
+            }
+            | IndexedReference::FieldWrite {
+                class_index: declaring_class,
+                field_index,
+            } => {
+                let field_name = class_index.class_at_index(*declaring_class).fields()
+                    [*field_index as usize]
+                    .field_name(constant_pool)
+                    .to_compact_string();
+                if matches!(self, IndexedReference::FieldRead { .. }) {
+                    RawReference::FieldRead {
+                        class_name,
+                        field_name,
+                    }
+                } else {
+                    RawReference::FieldWrite {
+                        class_name,
+                        field_name,
+                    }
+                }
+            }
+            IndexedReference::New(_) => RawReference::New(class_name),
+            IndexedReference::CheckCast(_) => RawReference::CheckCast(class_name),
+            IndexedReference::InstanceOf(_) => RawReference::InstanceOf(class_name),
+        }
+    }
+}
+
+impl Validate for IndexedReference {
+    fn validate(&self, bounds: &IndexBounds) -> anyhow::Result<()> {
+        // The members of the referenced class might not be loaded yet, therefore the member
+        // index can't be checked here
+        bounds.check_class(self.class_index())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RawReference;
+    use crate::builder::incremental::to_class_info;
+    use crate::builder::workers::{create_class_index_from_infos, DuplicateClassPolicy};
+    use crate::builder::FieldInfo;
+    use crate::class_index_members::{IndexedClass, IndexedMethod};
+    use crate::signature::{RawMethodSignature, RawSignatureType};
+    use crate::test::class_info;
+    use cafebabe::FieldAccessFlags;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_references() {
+        let mut base = class_info("a/Base", "Ljava/lang/Object;", "()V");
+        base.fields.push(FieldInfo {
+            field_name: "count".into(),
+            descriptor: RawSignatureType::from_str("I").unwrap(),
+            access_flags: FieldAccessFlags::PROTECTED,
+            annotations: Vec::new(),
+            constant_value: None,
+        });
+        let mut caller = class_info("a/Caller", "Ljava/lang/Object;", "()V");
+        caller.methods[0].references = vec![
+            RawReference::New("a/Sub".into()),
+            // Resolved to the method inherited from Base
+            RawReference::Method {
+                class_name: "a/Sub".into(),
+                method_name: "get".into(),
+                signature: RawMethodSignature::from_data("()V", &|| None).unwrap(),
+            },
+            RawReference::FieldWrite {
+                class_name: "a/Base".into(),
+                field_name: "count".into(),
+            },
+            RawReference::CheckCast("b/Missing".into()),
+        ];
+        let (_, class_index) = create_class_index_from_infos(
+            vec![base, caller, class_info("a/Sub", "La/Base;", "(I)V")],
+            DuplicateClassPolicy::First,
+        )
+        .unwrap();
+
+        let class = |name| class_index.find_class("a", name).unwrap();
+        let callers = |usages: Vec<(&IndexedClass, &IndexedMethod)>| {
+            usages
+                .into_iter()
+                .map(|(class, _)| class.class_name(class_index.constant_pool()))
+                .collect::<Vec<_>>()
+        };
+        let base = class("Base");
+        assert_eq!(
+            callers(
+                class_index
+                    .find_usages_of_method(base.index(), &base.methods()[0])
+                    .unwrap()
+            ),
+            vec!["Caller"]
+        );
+        assert_eq!(
+            callers(
+                class_index
+                    .find_usages_of_field(base.index(), &base.fields()[0])
+                    .unwrap()
+            ),
+            vec!["Caller"]
+        );
+        assert_eq!(
+            callers(
+                class_index
+                    .find_usages_of_class(class("Sub").index())
+                    .unwrap()
+            ),
+            vec!["Caller"]
+        );
+        let sub = class("Sub");
+        assert!(class_index
+            .find_usages_of_method(sub.index(), &sub.methods()[0])
+            .unwrap()
+            .is_empty());
+
+        let class_info = to_class_info(class("Caller"), &class_index, None);
+        let references = &class_info.methods[0].references;
+        assert_eq!(references.len(), 3);
+        assert!(matches!(
+            &references[1],
+            RawReference::Method { class_name, .. } if class_name.as_str() == "a/Base"
+        ));
+    }
+
+    #[test]
+    fn test_references_with_cyclic_super_types() {
+        let method = |name: &str, signature| RawReference::Method {
+            class_name: "a/A".into(),
+            method_name: name.into(),
+            signature: RawMethodSignature::from_data(signature, &|| None).unwrap(),
+        };
+        let mut caller = class_info("a/Caller", "Ljava/lang/Object;", "()V");
+        caller.methods[0].references =
+            vec![method("get", "()V"), method("missing", "(Lb/Unknown;)V")];
+        let (_, class_index) = create_class_index_from_infos(
+            vec![
+                class_info("a/A", "La/B;", "()V"),
+                class_info("a/B", "La/A;", "()V"),
+                caller,
+            ],
+            DuplicateClassPolicy::First,
+        )
+        .unwrap();
+
+        let caller = class_index.find_class("a", "Caller").unwrap();
+        assert_eq!(caller.method_references()[0].len(), 1);
+        // Signatures of references are not stored in the index
+        assert!(!class_index
+            .constant_pool()
+            .bytes()
+            .windows(9)
+            .any(|bytes| bytes == b"b/Unknown"));
+    }
+}
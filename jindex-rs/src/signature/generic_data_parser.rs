@@ -1,43 +1,31 @@
 use crate::signature::{
-    starts_with, ParseError, ParseResultData, RawTypeParameterData, SignatureType,
-    TypeParameterData,
+    byte_at, ParseError, ParseResultData, RawTypeParameterData, SignatureType, TypeParameterData,
 };
-use ascii::{AsAsciiStr, AsciiChar, AsciiStr};
-use compact_str::ToCompactString;
+use compact_str::CompactString;
 
 /// Parses stuff like '<T:Ljava/lang/Object;:Ljava/lang/Comparable;B>'
 pub fn parse_generic_signature_data(
     input: &str,
 ) -> Result<ParseResultData<Vec<RawTypeParameterData>>, ParseError> {
-    let input = input.as_ascii_str()?;
-    if let Some(first_char) = input.get_ascii(0) {
-        if first_char != '<' {
-            return Err(ParseError::UnexpectedChar(first_char.as_char()));
-        }
+    if byte_at(input, 0)? != b'<' {
+        return Err(ParseError::unexpected_char(input, 0));
+    }
 
-        let mut parts = Vec::new();
-        let mut current_index = 1;
-        while current_index < input.len()
-            && !starts_with(&input[current_index..], AsciiChar::GreaterThan)
-        {
-            let part = parse_generic_signature_data_single(&input[current_index..])?;
-            current_index += part.0 as usize;
-            parts.push(part.1);
-        }
-        Ok((current_index as u16 + 1, parts))
-    } else {
-        Err(ParseError::Eof)
+    let mut parts = Vec::new();
+    let mut current_index = 1;
+    while current_index < input.len() && !input[current_index..].starts_with('>') {
+        let part = parse_generic_signature_data_single(&input[current_index..])?;
+        current_index += part.0 as usize;
+        parts.push(part.1);
     }
+    Ok((current_index as u16 + 1, parts))
 }
 
 fn parse_generic_signature_data_single(
-    input: &AsciiStr,
+    input: &str,
 ) -> Result<ParseResultData<RawTypeParameterData>, ParseError> {
-    let mut separator_index = input
-        .chars()
-        .position(|ch| ch == ':')
-        .ok_or(ParseError::Eof)?;
-    let name = input[..separator_index].as_str().to_compact_string();
+    let mut separator_index = input.find(':').ok_or(ParseError::Eof)?;
+    let name = CompactString::new(&input[..separator_index]);
 
     let mut is_first = true;
     let mut type_bound = None;
@@ -47,21 +35,21 @@ fn parse_generic_signature_data_single(
             return Err(ParseError::Eof);
         }
 
-        if is_first && starts_with(&input[separator_index + 1..], AsciiChar::Colon) {
+        if is_first && input[separator_index + 1..].starts_with(':') {
             separator_index += 1;
         } else {
-            if !is_first && !starts_with(&input[separator_index..], AsciiChar::Colon) {
+            if !is_first && !input[separator_index..].starts_with(':') {
                 break;
             }
 
             separator_index += 1;
-            let t = SignatureType::parse_ascii(&input[separator_index..])?;
+            let t = SignatureType::parse_str(&input[separator_index..])?;
             separator_index += t.0 as usize;
 
             if is_first {
                 //Exclude Object signatures because these are the default
                 if let SignatureType::Object(str) = &t.1 {
-                    if &str[..] == unsafe { "java/lang/Object".as_ascii_str_unchecked() } {
+                    if *str == "java/lang/Object" {
                         is_first = false;
                         continue;
                     }
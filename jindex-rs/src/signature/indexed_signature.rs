@@ -1,15 +1,19 @@
-use crate::builder::{get_index_from_pool, ClassToIndexMap};
+use crate::builder::{get_index_from_pool, get_owned_index_from_pool, ClassToIndexMap};
 use crate::class_index::ClassIndex;
 use crate::constant_pool::ClassIndexConstantPool;
 use crate::rsplit_once;
 use crate::signature::{
-    IndexedClassSignature, IndexedEnclosingTypeInfo, IndexedMethodSignature, IndexedSignatureType,
-    IndexedTypeParameterData, RawClassSignature, RawEnclosingTypeInfo, RawMethodSignature,
-    RawSignatureType, RawTypeParameterData, SignatureType,
+    ClassSignature, IndexedClassSignature, IndexedEnclosingTypeInfo, IndexedMethodSignature,
+    IndexedSignatureType, IndexedTypeParameterData, MethodSignature, RawClassSignature,
+    RawEnclosingTypeInfo, RawMethodSignature, RawSignatureType, RawTypeParameterData,
+    SignatureType, TypeParameterData,
 };
-use ascii::{AsAsciiStr, AsciiChar, AsciiStr, AsciiString};
-use compact_str::CompactString;
+use crate::validation::{IndexBounds, Validate};
+use anyhow::bail;
+use compact_str::{CompactString, ToCompactString};
 use rustc_hash::FxHashMap;
+use std::borrow::Cow;
+use std::str::FromStr;
 
 pub trait ToIndexedType {
     type Out;
@@ -17,11 +21,19 @@ pub trait ToIndexedType {
     fn to_indexed_type<'a>(
         &'a self,
         constant_pool: &mut ClassIndexConstantPool,
-        constant_pool_map: &mut FxHashMap<&'a str, u32>,
+        constant_pool_map: &mut FxHashMap<Cow<'a, str>, u32>,
         class_to_index_map: &ClassToIndexMap,
     ) -> anyhow::Result<Self::Out>;
 }
 
+/// Converts an indexed type back into its raw representation, which references classes by name
+/// instead of by index. This allows indexed types to be indexed again as part of a new index.
+pub trait ToRawType {
+    type Out;
+
+    fn to_raw_type(&self, class_index: &ClassIndex) -> Self::Out;
+}
+
 pub trait ToSignatureIndexedType {
     fn to_signature_string(&self, class_index: &ClassIndex) -> String {
         self.to_signature_string0(class_index, false)
@@ -56,6 +68,72 @@ impl<T> SignatureType<T> {
     }
 }
 
+impl Validate for IndexedSignatureType {
+    fn validate(&self, bounds: &IndexBounds) -> anyhow::Result<()> {
+        match self {
+            SignatureType::Unresolved(None) | SignatureType::Primitive(_) => Ok(()),
+            SignatureType::Generic(index) | SignatureType::Unresolved(Some(index)) => {
+                bounds.check_string(*index)
+            }
+            SignatureType::Object(index) => bounds.check_class(*index),
+            SignatureType::ObjectPlus(inner)
+            | SignatureType::ObjectMinus(inner)
+            | SignatureType::Array(inner) => inner.validate(bounds),
+            SignatureType::ObjectInnerClass(parts) => {
+                if parts.is_empty() {
+                    bail!("Inner class type without any parts");
+                }
+                parts.validate(bounds)
+            }
+            SignatureType::ObjectTypeBounds(inner) => {
+                bounds.check_class(inner.0)?;
+                inner
+                    .1
+                    .iter()
+                    .flatten()
+                    .try_for_each(|t| t.validate(bounds))
+            }
+        }
+    }
+}
+
+impl Validate for IndexedTypeParameterData {
+    fn validate(&self, bounds: &IndexBounds) -> anyhow::Result<()> {
+        bounds.check_string(self.name)?;
+        self.type_bound.validate(bounds)?;
+        self.interface_bounds.validate(bounds)
+    }
+}
+
+impl Validate for IndexedClassSignature {
+    fn validate(&self, bounds: &IndexBounds) -> anyhow::Result<()> {
+        self.generic_data.validate(bounds)?;
+        self.super_class.validate(bounds)?;
+        self.interfaces.validate(bounds)
+    }
+}
+
+impl Validate for IndexedMethodSignature {
+    fn validate(&self, bounds: &IndexBounds) -> anyhow::Result<()> {
+        self.generic_data.validate(bounds)?;
+        self.parameters.validate(bounds)?;
+        self.return_type.validate(bounds)?;
+        self.exceptions.validate(bounds)
+    }
+}
+
+impl Validate for IndexedEnclosingTypeInfo {
+    fn validate(&self, bounds: &IndexBounds) -> anyhow::Result<()> {
+        if let Some(class_name) = self.class_name {
+            bounds.check_class(class_name)?;
+        }
+        if let Some(method_name) = self.method_name {
+            bounds.check_string(method_name)?;
+        }
+        self.method_descriptor.validate(bounds)
+    }
+}
+
 impl IndexedSignatureType {
     pub fn eq_erased(&self, other: &IndexedSignatureType) -> bool {
         match self {
@@ -67,7 +145,9 @@ impl IndexedSignatureType {
                 IndexedSignatureType::Array(outer) => inner.eq_erased(outer),
                 _ => false,
             },
-            IndexedSignatureType::Unresolved => matches!(other, IndexedSignatureType::Unresolved),
+            IndexedSignatureType::Unresolved(_) => {
+                matches!(other, IndexedSignatureType::Unresolved(_))
+            }
             // Generics gets erased to Object
             IndexedSignatureType::Generic(_) => true,
             _ => match other {
@@ -113,10 +193,20 @@ impl ToIndexedType for RawSignatureType {
     fn to_indexed_type<'a>(
         &'a self,
         constant_pool: &mut ClassIndexConstantPool,
-        constant_pool_map: &mut FxHashMap<&'a str, u32>,
+        constant_pool_map: &mut FxHashMap<Cow<'a, str>, u32>,
         class_to_index_map: &ClassToIndexMap,
     ) -> anyhow::Result<Self::Out> {
         Ok(match &self {
+            RawSignatureType::Unresolved(signature) => {
+                IndexedSignatureType::Unresolved(match signature {
+                    Some(signature) => Some(get_index_from_pool(
+                        signature,
+                        constant_pool_map,
+                        constant_pool,
+                    )?),
+                    None => None,
+                })
+            }
             RawSignatureType::Primitive(p) => IndexedSignatureType::Primitive(*p),
             RawSignatureType::Generic(name) => IndexedSignatureType::Generic(get_index_from_pool(
                 name,
@@ -126,7 +216,11 @@ impl ToIndexedType for RawSignatureType {
             RawSignatureType::Array(inner) => IndexedSignatureType::Array(Box::new(
                 inner.to_indexed_type(constant_pool, constant_pool_map, class_to_index_map)?,
             )),
-            RawSignatureType::Object(name) => index_object_type(name, class_to_index_map),
+            RawSignatureType::Object(name) => match index_for_object_type(name, class_to_index_map)
+            {
+                Some(i) => IndexedSignatureType::Object(i),
+                None => unresolved_type(self, constant_pool, constant_pool_map)?,
+            },
             RawSignatureType::ObjectPlus(inner) => IndexedSignatureType::ObjectPlus(Box::new(
                 inner.to_indexed_type(constant_pool, constant_pool_map, class_to_index_map)?,
             )),
@@ -136,7 +230,10 @@ impl ToIndexedType for RawSignatureType {
             RawSignatureType::ObjectInnerClass(inner) => {
                 let inner = inner.as_ref();
                 let base_type_signature = inner.first().unwrap();
-                let mut type_name = base_type_signature.extract_base_object_type().unwrap();
+                // The base type is only unresolved if it was already unresolved in a previous index
+                let mut type_name = base_type_signature
+                    .extract_base_object_type()
+                    .unwrap_or_default();
 
                 let mut new_vec = Vec::with_capacity(inner.len());
                 //Add base type
@@ -145,21 +242,19 @@ impl ToIndexedType for RawSignatureType {
                     constant_pool_map,
                     class_to_index_map,
                 )?);
-                //Add inner classes
-                inner.iter().skip(1).for_each(|s| {
+                //Add inner classes, unresolved parts keep their signature relative to the outer
+                //class
+                for s in inner.iter().skip(1) {
                     //Separator
-                    type_name.push_str("$");
+                    type_name.push('$');
                     new_vec.push(match s {
                         RawSignatureType::Object(name) => {
                             //Add inner class name
                             type_name.push_str(name);
 
-                            let index_or_none =
-                                index_for_object_type(&type_name, class_to_index_map);
-
-                            match index_or_none {
+                            match index_for_object_type(&type_name, class_to_index_map) {
                                 Some(i) => IndexedSignatureType::Object(i),
-                                _ => IndexedSignatureType::Unresolved,
+                                _ => unresolved_type(s, constant_pool, constant_pool_map)?,
                             }
                         }
                         RawSignatureType::ObjectTypeBounds(inner) => {
@@ -171,26 +266,25 @@ impl ToIndexedType for RawSignatureType {
                                 index_for_object_type(&type_name, class_to_index_map);
 
                             match main_type_index_or_none {
-                                Some(main_type_index) => vec
-                                    .to_indexed_type(
-                                        constant_pool,
-                                        constant_pool_map,
-                                        class_to_index_map,
-                                    )
-                                    .ok()
-                                    .map(|params| {
-                                        IndexedSignatureType::ObjectTypeBounds(Box::new((
-                                            main_type_index,
-                                            params,
-                                        )))
-                                    })
-                                    .unwrap_or(IndexedSignatureType::Unresolved),
-                                _ => IndexedSignatureType::Unresolved,
+                                Some(main_type_index) => {
+                                    IndexedSignatureType::ObjectTypeBounds(Box::new((
+                                        main_type_index,
+                                        vec.to_indexed_type(
+                                            constant_pool,
+                                            constant_pool_map,
+                                            class_to_index_map,
+                                        )?,
+                                    )))
+                                }
+                                _ => unresolved_type(s, constant_pool, constant_pool_map)?,
                             }
                         }
+                        RawSignatureType::Unresolved(_) => {
+                            s.to_indexed_type(constant_pool, constant_pool_map, class_to_index_map)?
+                        }
                         _ => unreachable!(),
-                    })
-                });
+                    });
+                }
 
                 IndexedSignatureType::ObjectInnerClass(Box::new(new_vec))
             }
@@ -198,22 +292,38 @@ impl ToIndexedType for RawSignatureType {
                 let (main_type, vec) = inner.as_ref();
                 let main_type_index_or_none = index_for_object_type(main_type, class_to_index_map);
 
-                let mut indexed_vec =
-                    vec.to_indexed_type(constant_pool, constant_pool_map, class_to_index_map)?;
-                indexed_vec.shrink_to_fit();
                 match main_type_index_or_none {
-                    Some(main_type_index) => IndexedSignatureType::ObjectTypeBounds(Box::new((
-                        main_type_index,
-                        indexed_vec,
-                    ))),
-                    _ => IndexedSignatureType::Unresolved,
+                    Some(main_type_index) => {
+                        let mut indexed_vec = vec.to_indexed_type(
+                            constant_pool,
+                            constant_pool_map,
+                            class_to_index_map,
+                        )?;
+                        indexed_vec.shrink_to_fit();
+                        IndexedSignatureType::ObjectTypeBounds(Box::new((
+                            main_type_index,
+                            indexed_vec,
+                        )))
+                    }
+                    _ => unresolved_type(self, constant_pool, constant_pool_map)?,
                 }
             }
-            _ => unreachable!(),
         })
     }
 }
 
+/// Stores the signature of a type which is not part of the index, so it can be resolved again when
+/// the type is converted back with [ToRawType]
+fn unresolved_type(
+    signature: &RawSignatureType,
+    constant_pool: &mut ClassIndexConstantPool,
+    constant_pool_map: &mut FxHashMap<Cow<str>, u32>,
+) -> anyhow::Result<IndexedSignatureType> {
+    Ok(IndexedSignatureType::Unresolved(Some(
+        get_owned_index_from_pool(signature.to_string(), constant_pool_map, constant_pool)?,
+    )))
+}
+
 impl ToSignatureIndexedType for IndexedSignatureType {
     fn to_signature_string0(&self, class_index: &ClassIndex, simple_class_names: bool) -> String {
         match &self {
@@ -271,8 +381,7 @@ impl ToSignatureIndexedType for IndexedSignatureType {
                     + class_index
                         .constant_pool()
                         .string_view_at(*index)
-                        .into_ascii_str(class_index.constant_pool())
-                        .as_str()
+                        .into_str(class_index.constant_pool())
                     + ";"
             }
             SignatureType::ObjectMinus(inner) => {
@@ -284,7 +393,7 @@ impl ToSignatureIndexedType for IndexedSignatureType {
             SignatureType::Array(inner) => {
                 String::from('[') + &inner.to_signature_string(class_index)
             }
-            SignatureType::Unresolved => String::from("Ljindex_unresolved;"),
+            SignatureType::Unresolved(_) => String::from("Ljindex_unresolved;"),
         }
     }
 }
@@ -322,12 +431,12 @@ impl ToDescriptorIndexedType for IndexedSignatureType {
                 String::from('[') + &inner.to_descriptor_string(class_index, generic_data)
             }
             SignatureType::Primitive(p) => p.to_string(),
-            SignatureType::Unresolved => String::from("Ljindex_unresolved;"),
+            SignatureType::Unresolved(_) => String::from("Ljindex_unresolved;"),
         }
     }
 }
 
-fn get_full_class_name(class_index: &ClassIndex, index: &u32, simple: bool) -> AsciiString {
+fn get_full_class_name(class_index: &ClassIndex, index: &u32, simple: bool) -> String {
     let class = class_index.class_at_index(*index);
     if simple {
         class.simple_class_name_with_package(
@@ -365,7 +474,7 @@ impl ToIndexedType for RawClassSignature {
     fn to_indexed_type<'a>(
         &'a self,
         constant_pool: &mut ClassIndexConstantPool,
-        constant_pool_map: &mut FxHashMap<&'a str, u32>,
+        constant_pool_map: &mut FxHashMap<Cow<'a, str>, u32>,
         class_to_index_map: &ClassToIndexMap,
     ) -> anyhow::Result<Self::Out> {
         Ok(IndexedClassSignature::new(
@@ -421,7 +530,7 @@ impl ToIndexedType for RawTypeParameterData {
     fn to_indexed_type<'a>(
         &'a self,
         constant_pool: &mut ClassIndexConstantPool,
-        constant_pool_map: &mut FxHashMap<&'a str, u32>,
+        constant_pool_map: &mut FxHashMap<Cow<'a, str>, u32>,
         class_to_index_map: &ClassToIndexMap,
     ) -> anyhow::Result<Self::Out> {
         Ok(IndexedTypeParameterData::new(
@@ -445,7 +554,7 @@ impl ToSignatureIndexedType for IndexedTypeParameterData {
         class_index
             .constant_pool()
             .string_view_at(self.name)
-            .into_ascii_str(class_index.constant_pool())
+            .into_str(class_index.constant_pool())
             .to_string()
             + ":"
             + &(match &self.type_bound {
@@ -485,6 +594,22 @@ impl IndexedMethodSignature {
             }),
         }
     }
+
+    /// Returns true if both signatures have the same parameters after type erasure
+    pub fn eq_erased_parameters(&self, other: &IndexedMethodSignature) -> bool {
+        self.parameter_count() == other.parameter_count()
+            && self
+                .parameters()
+                .map(|a| {
+                    a.iter()
+                        // Safety: We know that the parameter count is equal, so we know that the
+                        // other signature must have parameters
+                        .zip(other.parameters().unwrap().iter())
+                        .all(|(a, b)| a.eq_erased(b))
+                })
+                // If we don't have any parameters, the count is 0, so we automatically match
+                .unwrap_or(true)
+    }
 }
 
 impl ToIndexedType for RawMethodSignature {
@@ -493,7 +618,7 @@ impl ToIndexedType for RawMethodSignature {
     fn to_indexed_type<'a>(
         &'a self,
         constant_pool: &mut ClassIndexConstantPool,
-        constant_pool_map: &mut FxHashMap<&'a str, u32>,
+        constant_pool_map: &mut FxHashMap<Cow<'a, str>, u32>,
         class_to_index_map: &ClassToIndexMap,
     ) -> anyhow::Result<Self::Out> {
         Ok(IndexedMethodSignature::new(
@@ -570,11 +695,13 @@ impl ToIndexedType for RawEnclosingTypeInfo {
     fn to_indexed_type<'a>(
         &'a self,
         constant_pool: &mut ClassIndexConstantPool,
-        constant_pool_map: &mut FxHashMap<&'a str, u32>,
+        constant_pool_map: &mut FxHashMap<Cow<'a, str>, u32>,
         class_to_index_map: &ClassToIndexMap,
     ) -> anyhow::Result<Self::Out> {
-        let class_name =
-            index_for_object_type(self.class_name.as_ref().unwrap(), class_to_index_map);
+        let class_name = self
+            .class_name
+            .as_ref()
+            .and_then(|name| index_for_object_type(name, class_to_index_map));
 
         Ok(IndexedEnclosingTypeInfo::new(
             class_name,
@@ -608,7 +735,7 @@ where
     fn to_indexed_type<'a>(
         &'a self,
         constant_pool: &mut ClassIndexConstantPool,
-        constant_pool_map: &mut FxHashMap<&'a str, u32>,
+        constant_pool_map: &mut FxHashMap<Cow<'a, str>, u32>,
         class_to_index_map: &ClassToIndexMap,
     ) -> anyhow::Result<Self::Out> {
         Ok(match self.as_ref() {
@@ -629,7 +756,7 @@ where
     fn to_indexed_type<'a>(
         &'a self,
         constant_pool: &mut ClassIndexConstantPool,
-        constant_pool_map: &mut FxHashMap<&'a str, u32>,
+        constant_pool_map: &mut FxHashMap<Cow<'a, str>, u32>,
         class_to_index_map: &ClassToIndexMap,
     ) -> anyhow::Result<Self::Out> {
         self.as_ref()
@@ -646,7 +773,7 @@ where
     fn to_indexed_type<'a>(
         &'a self,
         constant_pool: &mut ClassIndexConstantPool,
-        constant_pool_map: &mut FxHashMap<&'a str, u32>,
+        constant_pool_map: &mut FxHashMap<Cow<'a, str>, u32>,
         class_to_index_map: &ClassToIndexMap,
     ) -> anyhow::Result<Self::Out> {
         self.iter()
@@ -730,22 +857,224 @@ where
     }
 }
 
-fn index_object_type(
+pub(crate) fn index_for_object_type(
     name: &CompactString,
     class_to_index_map: &ClassToIndexMap,
-) -> IndexedSignatureType {
-    let index_or_none = index_for_object_type(name, class_to_index_map);
+) -> Option<u32> {
+    let class_name_parts = name.rsplit_once('/').unwrap_or_else(|| ("", name.as_str()));
+    class_to_index_map.get(&class_name_parts).map(|p| p.0)
+}
 
-    match index_or_none {
-        Some(i) => IndexedSignatureType::Object(i),
-        _ => IndexedSignatureType::Unresolved,
+impl ToRawType for IndexedSignatureType {
+    type Out = RawSignatureType;
+
+    fn to_raw_type(&self, class_index: &ClassIndex) -> Self::Out {
+        match self {
+            SignatureType::Unresolved(signature) => signature
+                .and_then(|index| {
+                    RawSignatureType::from_str(
+                        class_index
+                            .constant_pool()
+                            .string_view_at(index)
+                            .into_str(class_index.constant_pool()),
+                    )
+                    .ok()
+                })
+                .unwrap_or(RawSignatureType::Unresolved(None)),
+            SignatureType::Primitive(p) => RawSignatureType::Primitive(*p),
+            SignatureType::Generic(index) => RawSignatureType::Generic(
+                class_index
+                    .constant_pool()
+                    .string_view_at(*index)
+                    .into_str(class_index.constant_pool())
+                    .to_compact_string(),
+            ),
+            SignatureType::Object(index) => {
+                RawSignatureType::Object(raw_class_name(class_index, *index))
+            }
+            SignatureType::ObjectPlus(inner) => {
+                RawSignatureType::ObjectPlus(Box::new(inner.to_raw_type(class_index)))
+            }
+            SignatureType::ObjectMinus(inner) => {
+                RawSignatureType::ObjectMinus(Box::new(inner.to_raw_type(class_index)))
+            }
+            SignatureType::Array(inner) => {
+                RawSignatureType::Array(Box::new(inner.to_raw_type(class_index)))
+            }
+            SignatureType::ObjectTypeBounds(inner) => {
+                let (main_type, bounds) = inner.as_ref();
+                RawSignatureType::ObjectTypeBounds(Box::new((
+                    raw_class_name(class_index, *main_type),
+                    bounds.to_raw_type(class_index),
+                )))
+            }
+            SignatureType::ObjectInnerClass(inner) => {
+                // The raw representation only contains the full name for the first part, all
+                // following parts only contain the name relative to their outer class
+                let mut outer_class_name: Option<CompactString> = None;
+                let parts = inner
+                    .iter()
+                    .map(|part| {
+                        if let SignatureType::Unresolved(_) = part {
+                            // Unresolved parts already contain the name relative to their outer
+                            // class
+                            let raw_part = part.to_raw_type(class_index);
+                            outer_class_name = match (
+                                outer_class_name.take(),
+                                raw_part.extract_base_object_type(),
+                            ) {
+                                (Some(outer_name), Some(name)) => Some(outer_name + "$" + &name),
+                                (_, name) => name,
+                            };
+                            return raw_part;
+                        }
+
+                        let class_name = part
+                            .extract_base_object_type()
+                            .map(|index| raw_class_name(class_index, index));
+                        let relative_name = match (&outer_class_name, &class_name) {
+                            (None, _) => None,
+                            (Some(outer_name), Some(name)) => Some(
+                                name.strip_prefix(outer_name.as_str())
+                                    .and_then(|n| n.strip_prefix('$'))
+                                    .unwrap_or(name)
+                                    .to_compact_string(),
+                            ),
+                            (Some(_), None) => return RawSignatureType::Unresolved(None),
+                        };
+                        if class_name.is_some() {
+                            outer_class_name = class_name;
+                        }
+
+                        match (relative_name, part) {
+                            (Some(name), SignatureType::Object(_)) => {
+                                RawSignatureType::Object(name)
+                            }
+                            (Some(name), SignatureType::ObjectTypeBounds(inner)) => {
+                                RawSignatureType::ObjectTypeBounds(Box::new((
+                                    name,
+                                    inner.1.to_raw_type(class_index),
+                                )))
+                            }
+                            _ => part.to_raw_type(class_index),
+                        }
+                    })
+                    .collect();
+
+                RawSignatureType::ObjectInnerClass(Box::new(parts))
+            }
+        }
     }
 }
 
-fn index_for_object_type(
-    name: &CompactString,
-    class_to_index_map: &ClassToIndexMap,
-) -> Option<u32> {
-    let class_name_parts = name.rsplit_once('/').unwrap_or_else(|| ("", name.as_str()));
-    class_to_index_map.get(&class_name_parts).map(|p| p.0)
+impl ToRawType for IndexedTypeParameterData {
+    type Out = RawTypeParameterData;
+
+    fn to_raw_type(&self, class_index: &ClassIndex) -> Self::Out {
+        TypeParameterData {
+            name: class_index
+                .constant_pool()
+                .string_view_at(self.name)
+                .into_str(class_index.constant_pool())
+                .to_compact_string(),
+            type_bound: self.type_bound.to_raw_type(class_index),
+            interface_bounds: self.interface_bounds.to_raw_type(class_index),
+        }
+    }
+}
+
+impl ToRawType for IndexedClassSignature {
+    type Out = RawClassSignature;
+
+    fn to_raw_type(&self, class_index: &ClassIndex) -> Self::Out {
+        ClassSignature {
+            generic_data: self.generic_data.to_raw_type(class_index),
+            super_class: self.super_class.to_raw_type(class_index),
+            interfaces: self.interfaces.to_raw_type(class_index),
+        }
+    }
+}
+
+impl ToRawType for IndexedMethodSignature {
+    type Out = RawMethodSignature;
+
+    fn to_raw_type(&self, class_index: &ClassIndex) -> Self::Out {
+        MethodSignature {
+            generic_data: self
+                .generic_data
+                .as_ref()
+                .map(|v| Box::new(v.to_raw_type(class_index))),
+            parameters: self
+                .parameters
+                .as_ref()
+                .map(|v| Box::new(v.to_raw_type(class_index))),
+            return_type: self.return_type.to_raw_type(class_index),
+            exceptions: self
+                .exceptions
+                .as_ref()
+                .map(|v| Box::new(v.to_raw_type(class_index))),
+        }
+    }
+}
+
+impl ToRawType for IndexedEnclosingTypeInfo {
+    type Out = RawEnclosingTypeInfo;
+
+    fn to_raw_type(&self, class_index: &ClassIndex) -> Self::Out {
+        RawEnclosingTypeInfo::new(
+            self.class_name()
+                .map(|index| raw_class_name(class_index, *index)),
+            *self.inner_class_type(),
+            self.method_name().map(|index| {
+                class_index
+                    .constant_pool()
+                    .string_view_at(*index)
+                    .into_str(class_index.constant_pool())
+                    .to_compact_string()
+            }),
+            self.method_descriptor()
+                .map(|descriptor| descriptor.to_raw_type(class_index)),
+        )
+    }
+}
+
+impl<T, X> ToRawType for Option<T>
+where
+    T: ToRawType<Out = X>,
+{
+    type Out = Option<X>;
+
+    fn to_raw_type(&self, class_index: &ClassIndex) -> Self::Out {
+        self.as_ref().map(|t| t.to_raw_type(class_index))
+    }
+}
+
+impl<T, X> ToRawType for Vec<T>
+where
+    T: ToRawType<Out = X>,
+{
+    type Out = Vec<X>;
+
+    fn to_raw_type(&self, class_index: &ClassIndex) -> Self::Out {
+        self.as_slice().to_raw_type(class_index)
+    }
+}
+
+impl<T, X> ToRawType for [T]
+where
+    T: ToRawType<Out = X>,
+{
+    type Out = Vec<X>;
+
+    fn to_raw_type(&self, class_index: &ClassIndex) -> Self::Out {
+        self.iter().map(|t| t.to_raw_type(class_index)).collect()
+    }
+}
+
+pub(crate) fn raw_class_name(class_index: &ClassIndex, index: u32) -> CompactString {
+    class_index
+        .class_at_index(index)
+        .class_name_with_package(class_index.package_index(), class_index.constant_pool())
+        .as_str()
+        .to_compact_string()
 }
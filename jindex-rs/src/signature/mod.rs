@@ -2,7 +2,6 @@ mod generic_data_parser;
 pub mod indexed_signature;
 pub mod raw_signature;
 
-use ascii::{AsAsciiStrError, AsciiStr, AsciiString};
 use compact_str::CompactString;
 use speedy::{Readable, Writable};
 use std::error::Error;
@@ -11,9 +10,12 @@ use std::ops::Deref;
 
 pub type SignaturePrimitive = jni::signature::Primitive;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum SignatureType<T> {
-    Unresolved,
+    /// A type which could not be found while indexing. Contains the signature of the type, which
+    /// allows resolving it again in a later index, or None if the index was created by an older
+    /// version.
+    Unresolved(Option<T>),
     /// I, J, L...
     Primitive(SignaturePrimitive),
     /// TPARAM_NAME;
@@ -41,7 +43,7 @@ type ParseResultData<T> = (u16, T);
 
 /// Maps generic parameter names to their bound types. If the associated Option
 /// is None, java/lang/Object should be implied as the only bound.
-#[derive(Readable, Writable, Debug)]
+#[derive(Readable, Writable, Debug, PartialEq)]
 pub struct TypeParameterData<T> {
     name: T,
     type_bound: Option<SignatureType<T>>,
@@ -76,7 +78,7 @@ impl<T> ClassSignature<T> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 /// Some fields here are in an extra Box because they blow up the size of the
 /// struct otherwise. This makes sense because a lot of these are created and
 /// without the Boxes the size of this struct would be doubled, even though
@@ -176,25 +178,17 @@ impl<T> EnclosingTypeInfo<T> {
 pub type RawEnclosingTypeInfo = EnclosingTypeInfo<CompactString>;
 pub type IndexedEnclosingTypeInfo = EnclosingTypeInfo<u32>;
 
-fn starts_with<T>(str: &AsciiStr, prefix: T) -> bool
-where
-    T: AsRef<AsciiStr>,
-{
-    str.as_bytes().starts_with(prefix.as_ref().as_bytes())
+/// Returns the byte at the given index. All chars with a special meaning in signatures are
+/// ASCII, which makes it safe to compare them byte by byte.
+fn byte_at(input: &str, index: usize) -> Result<u8, ParseError> {
+    input.as_bytes().get(index).copied().ok_or(ParseError::Eof)
 }
 
 pub enum ParseError {
     Eof,
-    AsciiStringError(AsAsciiStrError),
     UnexpectedChar(char),
 }
 
-impl From<AsAsciiStrError> for ParseError {
-    fn from(e: AsAsciiStrError) -> Self {
-        ParseError::AsciiStringError(e)
-    }
-}
-
 impl Debug for ParseError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.as_str())
@@ -210,11 +204,17 @@ impl Display for ParseError {
 impl Error for ParseError {}
 
 impl ParseError {
+    fn unexpected_char(input: &str, index: usize) -> Self {
+        input
+            .get(index..)
+            .and_then(|s| s.chars().next())
+            .map_or(ParseError::Eof, ParseError::UnexpectedChar)
+    }
+
     fn as_str(&self) -> String {
         use self::ParseError::*;
         match self {
             UnexpectedChar(c) => format!("Unexpected char '{}'", c),
-            AsciiStringError(e) => e.to_string(),
             Eof => "End of input reached unexpectedly".to_owned(),
         }
     }
@@ -1,92 +1,76 @@
 use crate::signature::generic_data_parser::parse_generic_signature_data;
 use crate::signature::{
-    MethodSignature, ParseError, ParseResultData, RawClassSignature, RawMethodSignature,
+    byte_at, MethodSignature, ParseError, ParseResultData, RawClassSignature, RawMethodSignature,
     RawSignatureType, RawTypeParameterData, SignaturePrimitive, SignatureType,
 };
-use ascii::{AsAsciiStr, AsciiChar, AsciiStr};
 use compact_str::{CompactString, ToCompactString};
 use std::borrow::Cow;
 use std::str::FromStr;
 
 impl RawSignatureType {
     pub(super) fn parse_str(input: &str) -> Result<ParseResultData<RawSignatureType>, ParseError> {
-        return RawSignatureType::parse_ascii(input.as_ascii_str()?);
-    }
-
-    pub(super) fn parse_ascii(
-        input: &AsciiStr,
-    ) -> Result<ParseResultData<RawSignatureType>, ParseError> {
-        if let Some(first_char) = input.get_ascii(0) {
-            let result = match first_char {
-                AsciiChar::Z => (1, SignatureType::Primitive(SignaturePrimitive::Boolean)),
-                AsciiChar::B => (1, SignatureType::Primitive(SignaturePrimitive::Byte)),
-                AsciiChar::C => (1, SignatureType::Primitive(SignaturePrimitive::Char)),
-                AsciiChar::D => (1, SignatureType::Primitive(SignaturePrimitive::Double)),
-                AsciiChar::F => (1, SignatureType::Primitive(SignaturePrimitive::Float)),
-                AsciiChar::I => (1, SignatureType::Primitive(SignaturePrimitive::Int)),
-                AsciiChar::J => (1, SignatureType::Primitive(SignaturePrimitive::Long)),
-                AsciiChar::S => (1, SignatureType::Primitive(SignaturePrimitive::Short)),
-                AsciiChar::V => (1, SignatureType::Primitive(SignaturePrimitive::Void)),
-                AsciiChar::L => {
-                    let object = SignatureType::parse_object(&input[1..])?;
-                    let mut index = object.0 as usize;
-                    if input.get_ascii(index).ok_or(ParseError::Eof)? == ';' {
-                        (index as u16 + 1, object.1)
-                    } else {
-                        let mut parts = vec![object.1];
-                        while input.get_ascii(index).ok_or(ParseError::Eof)? != ';' {
-                            let data = SignatureType::parse_object(&input[(index + 1)..])?;
-                            index += data.0 as usize;
-                            parts.push(data.1);
-                        }
-
-                        (
-                            index as u16 + 1,
-                            RawSignatureType::ObjectInnerClass(Box::new(parts)),
-                        )
+        let first_char = byte_at(input, 0)?;
+        let result = match first_char {
+            b'Z' => (1, SignatureType::Primitive(SignaturePrimitive::Boolean)),
+            b'B' => (1, SignatureType::Primitive(SignaturePrimitive::Byte)),
+            b'C' => (1, SignatureType::Primitive(SignaturePrimitive::Char)),
+            b'D' => (1, SignatureType::Primitive(SignaturePrimitive::Double)),
+            b'F' => (1, SignatureType::Primitive(SignaturePrimitive::Float)),
+            b'I' => (1, SignatureType::Primitive(SignaturePrimitive::Int)),
+            b'J' => (1, SignatureType::Primitive(SignaturePrimitive::Long)),
+            b'S' => (1, SignatureType::Primitive(SignaturePrimitive::Short)),
+            b'V' => (1, SignatureType::Primitive(SignaturePrimitive::Void)),
+            b'L' => {
+                let object = SignatureType::parse_object(&input[1..])?;
+                let mut index = object.0 as usize;
+                if byte_at(input, index)? == b';' {
+                    (index as u16 + 1, object.1)
+                } else {
+                    let mut parts = vec![object.1];
+                    while byte_at(input, index)? != b';' {
+                        let data = SignatureType::parse_object(&input[(index + 1)..])?;
+                        index += data.0 as usize;
+                        parts.push(data.1);
                     }
-                }
-                AsciiChar::BracketOpen => {
-                    let inner = SignatureType::parse_ascii(&input[1..])?;
 
-                    (1 + inner.0, SignatureType::Array(Box::new(inner.1)))
-                }
-                AsciiChar::T => {
-                    let semi_colon_index = input
-                        .chars()
-                        .position(|ch| ch == ';')
-                        .ok_or(ParseError::Eof)?;
-                    let sig = SignatureType::Generic(
-                        input[1..semi_colon_index].as_str().to_compact_string(),
-                    );
-
-                    (semi_colon_index as u16 + 1, sig)
+                    (
+                        index as u16 + 1,
+                        RawSignatureType::ObjectInnerClass(Box::new(parts)),
+                    )
                 }
-                AsciiChar::Minus => {
-                    let inner = SignatureType::parse_ascii(&input[1..])?;
-                    (1 + inner.0, SignatureType::ObjectMinus(Box::new(inner.1)))
-                }
-                AsciiChar::Plus => {
-                    let inner = SignatureType::parse_ascii(&input[1..])?;
-                    (1 + inner.0, SignatureType::ObjectPlus(Box::new(inner.1)))
-                }
-                _ => return Err(ParseError::UnexpectedChar(first_char.as_char())),
-            };
+            }
+            b'[' => {
+                let inner = SignatureType::parse_str(&input[1..])?;
 
-            Ok(result)
-        } else {
-            Err(ParseError::Eof)
-        }
+                (1 + inner.0, SignatureType::Array(Box::new(inner.1)))
+            }
+            b'T' => {
+                let semi_colon_index = input.find(';').ok_or(ParseError::Eof)?;
+                let sig = SignatureType::Generic(CompactString::new(&input[1..semi_colon_index]));
+
+                (semi_colon_index as u16 + 1, sig)
+            }
+            b'-' => {
+                let inner = SignatureType::parse_str(&input[1..])?;
+                (1 + inner.0, SignatureType::ObjectMinus(Box::new(inner.1)))
+            }
+            b'+' => {
+                let inner = SignatureType::parse_str(&input[1..])?;
+                (1 + inner.0, SignatureType::ObjectPlus(Box::new(inner.1)))
+            }
+            _ => return Err(ParseError::unexpected_char(input, 0)),
+        };
+
+        Ok(result)
     }
 
-    fn parse_object(input: &AsciiStr) -> Result<ParseResultData<RawSignatureType>, ParseError> {
+    fn parse_object(input: &str) -> Result<ParseResultData<RawSignatureType>, ParseError> {
         //Find < or ;
         let mut special_char_index = input
-            .chars()
-            .position(|ch| ch == '<' || ch == ';' || ch == '.')
+            .find(|ch| ch == '<' || ch == ';' || ch == '.')
             .ok_or(ParseError::Eof)?;
         //Parse the first type, which we'll need either way
-        let base_type = unsafe { CompactString::from_utf8_unchecked(&input[..special_char_index]) };
+        let base_type = CompactString::new(&input[..special_char_index]);
 
         //Parse the generic type bounds if there are any
         let sig = match SignatureType::parse_generic_type_bounds(&input[special_char_index..]) {
@@ -101,24 +85,23 @@ impl RawSignatureType {
     }
 
     fn parse_generic_type_bounds(
-        input: &AsciiStr,
+        input: &str,
     ) -> Result<ParseResultData<Vec<Option<RawSignatureType>>>, ParseError> {
         let mut index = 0;
-        let first_char = input.get_ascii(index).ok_or(ParseError::Eof)?;
-        if first_char != '<' {
-            return Err(ParseError::UnexpectedChar(first_char.as_char()));
+        if byte_at(input, index)? != b'<' {
+            return Err(ParseError::unexpected_char(input, index));
         }
 
         let mut vec = Vec::with_capacity(1);
 
         //Consume '<'
         index += 1;
-        while input.get_ascii(index).ok_or(ParseError::Eof)? != '>' {
-            vec.push(if input.get_ascii(index).ok_or(ParseError::Eof)? == '*' {
+        while byte_at(input, index)? != b'>' {
+            vec.push(if byte_at(input, index)? == b'*' {
                 index += 1;
                 None
             } else {
-                let parse_result = SignatureType::parse_ascii(&input[index..])?;
+                let parse_result = SignatureType::parse_str(&input[index..])?;
                 index += parse_result.0 as usize;
                 Some(parse_result.1)
             });
@@ -176,6 +159,7 @@ impl ToString for RawSignatureType {
             SignatureType::ObjectMinus(inner) => String::from('-') + &inner.to_string(),
             SignatureType::ObjectPlus(inner) => String::from('+') + &inner.to_string(),
             SignatureType::Array(inner) => String::from('[') + &inner.to_string(),
+            SignatureType::Unresolved(Some(signature)) => signature.to_string(),
             _ => unreachable!(),
         }
     }
@@ -231,7 +215,6 @@ impl FromStr for RawClassSignature {
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
         let generic_data = parse_generic_signature_data(input).ok();
-        let input = unsafe { input.as_ascii_str_unchecked() }; //parse_generic_signature_data validated the input for us
 
         let mut start_index = if let Some(ref result) = generic_data {
             result.0 as usize
@@ -242,7 +225,7 @@ impl FromStr for RawClassSignature {
         let mut other_classes = {
             let mut parameters = Vec::new();
             while start_index < input.len() {
-                let parse_result = unsafe { SignatureType::parse_ascii(&input[start_index..])? };
+                let parse_result = SignatureType::parse_str(&input[start_index..])?;
                 start_index += parse_result.0 as usize;
                 parameters.push(parse_result.1);
             }
@@ -292,7 +275,6 @@ impl RawMethodSignature {
         exception_attribute_supplier: &dyn Fn() -> Option<&'a Vec<Cow<'a, str>>>,
     ) -> Result<Self, ParseError> {
         let generic_data = parse_generic_signature_data(input).ok();
-        let input = unsafe { input.as_ascii_str_unchecked() }; //parse_generic_signature_data validated the input for us
 
         let mut start_index = if let Some(ref result) = generic_data {
             result.0 as usize
@@ -300,12 +282,12 @@ impl RawMethodSignature {
             0
         };
 
-        let parameters = if input.get_ascii(start_index).ok_or(ParseError::Eof)? == '(' {
+        let parameters = if byte_at(input, start_index)? == b'(' {
             start_index += 1; //Skip '('
 
             let mut parameters = Vec::new();
-            while input.get_ascii(start_index).ok_or(ParseError::Eof)? != ')' {
-                let parse_result = unsafe { SignatureType::parse_ascii(&input[start_index..])? };
+            while byte_at(input, start_index)? != b')' {
+                let parse_result = SignatureType::parse_str(&input[start_index..])?;
                 start_index += parse_result.0 as usize;
                 parameters.push(parse_result.1);
             }
@@ -314,30 +296,27 @@ impl RawMethodSignature {
 
             parameters
         } else {
-            return Err(ParseError::UnexpectedChar(
-                input.get_ascii(start_index).unwrap().as_char(),
-            ));
+            return Err(ParseError::unexpected_char(input, start_index));
         };
 
-        let return_type = unsafe { SignatureType::parse_ascii(&input[start_index..])? };
+        let return_type = SignatureType::parse_str(&input[start_index..])?;
         start_index += return_type.0 as usize;
 
         let mut exceptions = Vec::new();
-        while input.get_ascii(start_index).map_or(false, |ch| ch == '^') {
+        while byte_at(input, start_index).map_or(false, |ch| ch == b'^') {
             start_index += 1; //Skip '^'
 
-            let parse_result = unsafe { SignatureType::parse_ascii(&input[start_index..])? };
+            let parse_result = SignatureType::parse_str(&input[start_index..])?;
             start_index += parse_result.0 as usize;
             exceptions.push(parse_result.1);
         }
 
         if exceptions.is_empty() {
             if let Some(vec) = exception_attribute_supplier() {
-                exceptions.extend(vec.iter().filter_map(|s: &Cow<'_, str>| {
-                    Some(s)
-                        .filter(|s| s.is_ascii())
-                        .map(|a| RawSignatureType::Object(a.to_compact_string()))
-                }));
+                exceptions.extend(
+                    vec.iter()
+                        .map(|s: &Cow<'_, str>| RawSignatureType::Object(s.to_compact_string())),
+                );
             }
         }
 
@@ -0,0 +1,371 @@
+use anyhow::{anyhow, bail, Context};
+
+use crate::all_direct_super_types;
+use crate::class_index_members::{
+    IndexedClass, IndexedField, IndexedMethod, IndexedRecordComponent,
+};
+use crate::constant_pool::ClassIndexConstantPool;
+use crate::io::ClassMembers;
+use crate::package_index::PackageIndex;
+
+/// Checks that an index read from an untrusted source only contains references which are in
+/// bounds. Most accessors assume that all references are valid and panic otherwise.
+pub(crate) trait Validate {
+    fn validate(&self, bounds: &IndexBounds) -> anyhow::Result<()>;
+}
+
+/// The sizes of everything a deserialized index can refer to. Kept alive by classes whose members
+/// are loaded lazily, because their members can only be validated once they are read.
+pub(crate) struct IndexBounds {
+    constant_pool: ClassIndexConstantPool,
+    package_count: usize,
+    class_count: usize,
+    source_count: usize,
+}
+
+impl IndexBounds {
+    /// The pool of a loaded index shares its bytes with the given pool
+    pub(crate) fn new(
+        constant_pool: &ClassIndexConstantPool,
+        package_count: usize,
+        class_count: usize,
+        source_count: usize,
+    ) -> Self {
+        Self {
+            constant_pool: constant_pool.clone(),
+            package_count,
+            class_count,
+            source_count,
+        }
+    }
+
+    pub(crate) fn check_string(&self, index: u32) -> anyhow::Result<()> {
+        self.constant_pool.check_string_at(index)
+    }
+
+    pub(crate) fn check_class(&self, index: u32) -> anyhow::Result<()> {
+        check_bounds("Class", index, self.class_count)
+    }
+
+    fn check_package(&self, index: u32) -> anyhow::Result<()> {
+        check_bounds("Package", index, self.package_count)
+    }
+
+    fn check_source(&self, index: u32) -> anyhow::Result<()> {
+        check_bounds("Source", index, self.source_count)
+    }
+}
+
+fn check_bounds(kind: &str, index: u32, count: usize) -> anyhow::Result<()> {
+    if index as usize >= count {
+        bail!(
+            "{} index {} out of bounds, length is {}",
+            kind,
+            index,
+            count
+        );
+    }
+
+    Ok(())
+}
+
+/// Validates all parts of a deserialized index before a [crate::class_index::ClassIndex] is
+/// created from them
+pub(crate) fn validate_class_index(
+    bounds: &IndexBounds,
+    package_index: &PackageIndex,
+    classes: &[IndexedClass],
+    duplicate_class_sources: &[(u32, Vec<u32>)],
+) -> anyhow::Result<()> {
+    for (index, package) in package_index.packages().iter().enumerate() {
+        let result: anyhow::Result<()> = try {
+            // Parents always come before their sub packages, this prevents cycles
+            if index != 0 {
+                bounds.check_string(package.package_name_index())?;
+                if package.previous_package_index() as usize >= index {
+                    Err(anyhow!(
+                        "Parent package {} is invalid",
+                        package.previous_package_index()
+                    ))?;
+                }
+            }
+            // Sub packages always come after their parent and refer back to it, otherwise
+            // searches which walk the sub packages could loop forever
+            for sub_package in package.sub_packages_indices() {
+                bounds.check_package(*sub_package)?;
+                if *sub_package as usize <= index
+                    || package_index
+                        .package_at(*sub_package)
+                        .previous_package_index() as usize
+                        != index
+                {
+                    Err(anyhow!("Sub package {} is invalid", sub_package))?;
+                }
+            }
+            for class in package.sub_classes_indices().iter() {
+                bounds.check_class(*class)?;
+            }
+        };
+        result.with_context(|| format!("Invalid package at index {}", index))?;
+    }
+
+    for (index, class) in classes.iter().enumerate() {
+        let result: anyhow::Result<()> = try {
+            if class.index() as usize != index {
+                Err(anyhow!("Class has index {}", class.index()))?;
+            }
+            validate_class(class, bounds)?;
+        };
+        result.with_context(|| format!("Invalid class at index {}", index))?;
+    }
+
+    for (class, sources) in duplicate_class_sources {
+        bounds.check_class(*class)?;
+        for source in sources {
+            bounds.check_source(*source)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that no class is its own super type. The super types are stored with the lazily loaded
+/// members, therefore this can only run once the members of all classes are loaded.
+pub(crate) fn validate_super_types(classes: &[IndexedClass]) -> anyhow::Result<()> {
+    const UNVISITED: u8 = 0;
+    const IN_PROGRESS: u8 = 1;
+    const DONE: u8 = 2;
+
+    let mut states = vec![UNVISITED; classes.len()];
+    let mut stack = Vec::new();
+    for start in 0..classes.len() {
+        if states[start] != UNVISITED {
+            continue;
+        }
+
+        states[start] = IN_PROGRESS;
+        stack.push((start, 0));
+        while let Some((index, next_super_type)) = stack.pop() {
+            let class = &classes[index];
+            let super_type = all_direct_super_types!(class)
+                .filter_map(|s| s.extract_base_object_type())
+                .nth(next_super_type);
+            let super_type = match super_type {
+                Some(super_type) => super_type as usize,
+                None => {
+                    states[index] = DONE;
+                    continue;
+                }
+            };
+
+            stack.push((index, next_super_type + 1));
+            match states[super_type] {
+                UNVISITED => {
+                    states[super_type] = IN_PROGRESS;
+                    stack.push((super_type, 0));
+                }
+                IN_PROGRESS => bail!("Super types of class at index {} form a cycle", super_type),
+                _ => {}
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_class(class: &IndexedClass, bounds: &IndexBounds) -> anyhow::Result<()> {
+    bounds.check_package(class.package_index())?;
+    bounds.check_string(class.class_name_index())?;
+    let name = bounds
+        .constant_pool
+        .string_view_at(class.class_name_index())
+        .into_str(&bounds.constant_pool);
+    let start_index = class.class_name_start_index() as usize;
+    if name.is_empty() || start_index >= name.len() || !name.is_char_boundary(start_index) {
+        bail!("Invalid class name");
+    }
+
+    if let Some(module_name_index) = class.module_name_index() {
+        bounds.check_string(module_name_index)?;
+    }
+    if let Some(source_index) = class.source_index() {
+        bounds.check_source(source_index)?;
+    }
+    for member_class in class.member_classes().iter() {
+        bounds.check_class(*member_class)?;
+    }
+
+    // Members which are loaded lazily are validated when they are read
+    if class.has_unloaded_members() {
+        return Ok(());
+    }
+
+    // Older formats only stored these members, and stored them eagerly
+    class.signature().validate(bounds)?;
+    if let Some(info) = class.enclosing_type_info() {
+        info.validate(bounds)?;
+    }
+    class.fields().validate(bounds)?;
+    class.methods().validate(bounds)?;
+
+    Ok(())
+}
+
+impl Validate for ClassMembers {
+    fn validate(&self, bounds: &IndexBounds) -> anyhow::Result<()> {
+        self.signature.validate(bounds)?;
+        self.enclosing_type_info.validate(bounds)?;
+        self.fields.validate(bounds)?;
+        self.methods.validate(bounds)?;
+        self.annotations
+            .validate(bounds)
+            .context("Invalid annotation")?;
+        for permitted_subclass in self.permitted_subclasses.iter() {
+            bounds.check_class(*permitted_subclass)?;
+        }
+        if let Some(nest_host) = self.nest_host {
+            bounds.check_class(nest_host)?;
+        }
+        for nest_member in self.nest_members.iter() {
+            bounds.check_class(*nest_member)?;
+        }
+        self.record_components.validate(bounds)?;
+        self.method_references
+            .validate(bounds)
+            .context("Invalid reference")?;
+        for literal in self.string_literals.iter() {
+            bounds.check_string(*literal)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Validate for IndexedRecordComponent {
+    fn validate(&self, bounds: &IndexBounds) -> anyhow::Result<()> {
+        bounds
+            .check_string(self.name_index())
+            .and_then(|_| self.signature().validate(bounds))
+            .context("Invalid record component")
+    }
+}
+
+impl Validate for IndexedField {
+    fn validate(&self, bounds: &IndexBounds) -> anyhow::Result<()> {
+        bounds
+            .check_string(self.field_name_index())
+            .and_then(|_| self.field_signature().validate(bounds))
+            .and_then(|_| self.annotations().validate(bounds))
+            .and_then(|_| self.constant_value().map_or(Ok(()), |v| v.validate(bounds)))
+            .context("Invalid field")
+    }
+}
+
+impl Validate for IndexedMethod {
+    fn validate(&self, bounds: &IndexBounds) -> anyhow::Result<()> {
+        bounds
+            .check_string(self.method_name_index())
+            .and_then(|_| self.method_signature().validate(bounds))
+            .and_then(|_| self.annotations().validate(bounds))
+            .and_then(|_| {
+                self.parameter_annotations()
+                    .iter()
+                    .try_for_each(|annotations| annotations.validate(bounds))
+            })
+            .and_then(|_| {
+                self.parameter_name_indices()
+                    .iter()
+                    .try_for_each(|name| bounds.check_string(*name))
+            })
+            .context("Invalid method")
+    }
+}
+
+impl<T: Validate> Validate for Option<T> {
+    fn validate(&self, bounds: &IndexBounds) -> anyhow::Result<()> {
+        self.iter().try_for_each(|t| t.validate(bounds))
+    }
+}
+
+impl<T: Validate> Validate for Vec<T> {
+    fn validate(&self, bounds: &IndexBounds) -> anyhow::Result<()> {
+        self.as_slice().validate(bounds)
+    }
+}
+
+impl<T: Validate> Validate for [T] {
+    fn validate(&self, bounds: &IndexBounds) -> anyhow::Result<()> {
+        self.iter().try_for_each(|t| t.validate(bounds))
+    }
+}
+
+impl<T: Validate> Validate for Box<T> {
+    fn validate(&self, bounds: &IndexBounds) -> anyhow::Result<()> {
+        self.as_ref().validate(bounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_class_index, IndexBounds, Validate};
+    use crate::builder::workers::{create_class_index_from_infos, DuplicateClassPolicy};
+    use crate::constant_pool::ClassIndexConstantPool;
+    use crate::package_index::PackageIndex;
+    use crate::signature::IndexedSignatureType;
+    use crate::test::class_info;
+    use speedy::{Readable, Writable};
+
+    #[test]
+    fn test_validate_references() {
+        let mut constant_pool = ClassIndexConstantPool::new(16);
+        let name_index = constant_pool.add_string(b"T").unwrap();
+        let bounds = IndexBounds::new(&constant_pool, 1, 2, 0);
+
+        assert!(bounds.check_string(name_index).is_ok());
+        assert!(bounds.check_string(name_index + 1).is_err());
+        assert!(bounds.check_string(100).is_err());
+        assert!(bounds.check_source(0).is_err());
+
+        let valid = IndexedSignatureType::Array(Box::new(IndexedSignatureType::Object(1)));
+        assert!(valid.validate(&bounds).is_ok());
+        let invalid = IndexedSignatureType::ObjectInnerClass(Box::new(vec![
+            IndexedSignatureType::Object(0),
+            IndexedSignatureType::Generic(name_index + 5),
+        ]));
+        assert!(invalid.validate(&bounds).is_err());
+        assert!(IndexedSignatureType::ObjectInnerClass(Box::new(Vec::new()))
+            .validate(&bounds)
+            .is_err());
+    }
+
+    #[test]
+    fn test_reject_cycles() {
+        let mut constant_pool = ClassIndexConstantPool::new(16);
+        constant_pool.add_string(b"").unwrap();
+        let name_index = constant_pool.add_string(b"a").unwrap();
+        let bounds = IndexBounds::new(&constant_pool, 3, 0, 0);
+        let package_index = |sub_packages: Vec<u32>| {
+            let packages = vec![
+                (0u32, 0u32, vec![1u32], Vec::<u32>::new()),
+                (name_index, 0, sub_packages, Vec::new()),
+                (name_index, 1, Vec::new(), Vec::new()),
+            ];
+            PackageIndex::read_from_buffer(&packages.write_to_vec().unwrap()).unwrap()
+        };
+        assert!(validate_class_index(&bounds, &package_index(vec![2]), &[], &[]).is_ok());
+        assert!(validate_class_index(&bounds, &package_index(vec![1]), &[], &[]).is_err());
+        assert!(validate_class_index(&bounds, &package_index(vec![0]), &[], &[]).is_err());
+
+        let (_, class_index) = create_class_index_from_infos(
+            vec![
+                class_info("a/A", "La/B;", "()V"),
+                class_info("a/B", "La/A;", "()V"),
+            ],
+            DuplicateClassPolicy::First,
+        )
+        .unwrap();
+        let error = class_index.load_all_members().unwrap_err();
+        assert!(error.to_string().ends_with("form a cycle"));
+        assert!(class_index.find_implementations_of_class(0, false).is_err());
+    }
+}
@@ -8,11 +8,16 @@ public class BuildTimeInfo {
     private final long deserializationTime;
     private final long classReadingTime;
     private final long indexingTime;
+    private final DuplicateClass[] duplicateClasses;
+    private final SkippedSource[] skippedSources;
 
-    private BuildTimeInfo(long deserializationTime, long classReadingTime, long indexingTime) {
+    private BuildTimeInfo(long deserializationTime, long classReadingTime, long indexingTime,
+                          DuplicateClass[] duplicateClasses, SkippedSource[] skippedSources) {
         this.deserializationTime = deserializationTime;
         this.classReadingTime = classReadingTime;
         this.indexingTime = indexingTime;
+        this.duplicateClasses = duplicateClasses;
+        this.skippedSources = skippedSources;
     }
 
     /**
@@ -36,6 +41,22 @@ public class BuildTimeInfo {
         return indexingTime;
     }
 
+    /**
+     * @return All classes which were found in more than one source while building the index. Only one version of
+     * each of these classes is contained in the index.
+     */
+    public DuplicateClass[] getDuplicateClasses() {
+        return duplicateClasses;
+    }
+
+    /**
+     * @return All nested jars which could not be read while building the index. The classes of the jars containing
+     * them are still indexed.
+     */
+    public SkippedSource[] getSkippedSources() {
+        return skippedSources;
+    }
+
     /**
      * @return The total time in milliseconds
      */
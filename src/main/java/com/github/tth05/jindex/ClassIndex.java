@@ -73,6 +73,65 @@ public class ClassIndex extends ClassIndexChildObject {
      */
     public native IndexedClass[] findClasses(String query, SearchOptions options);
 
+    /**
+     * <p>Like {@link #findClasses(String, SearchOptions)}, but also returns the score of each class. The results are
+     * sorted by their score.</p>
+     *
+     * @param query   The query to search for
+     * @param options The search options
+     * @return The classes which match the query and options, or an empty array if no classes were found
+     */
+    public native SearchResult<IndexedClass>[] findClassesWithScores(String query, SearchOptions options);
+
+    /**
+     * <p>Returns an array of classes whose fully qualified name matches the given glob. Packages are separated by '/'.
+     * {@code *} matches any part of a package or class name, {@code ?} matches a single character and {@code **}
+     * matches any number of packages.</p>
+     * Examples:
+     * <blockquote><pre>
+     *     findClassesByGlob("org/apache/&#42;&#42;/util/*Utils", limit)
+     *     findClassesByGlob("java/util/*Map", limit)
+     * </pre></blockquote>
+     *
+     * @param glob  The glob which has to match the whole name
+     * @param limit The maximum number of classes to return
+     * @return The matching classes, or an empty array if no classes were found
+     */
+    public native IndexedClass[] findClassesByGlob(String glob, int limit);
+
+    /**
+     * <p>Returns an array of classes whose fully qualified name matches the given regex, e.g.
+     * {@code java/util/concurrent/.*Queue}. Packages are separated by '/'.</p>
+     *
+     * @param regex The regex which has to match the whole name
+     * @param limit The maximum number of classes to return
+     * @return The matching classes, or an empty array if no classes were found
+     */
+    public native IndexedClass[] findClassesByRegex(String regex, int limit);
+
+    /**
+     * <p>Returns an array of classes which use a string literal matching the given query and search options, either in
+     * their code or as the constant value of a field. Only available if the class index was created with
+     * {@link IndexingOptions#withStringLiterals(boolean)}.</p>
+     *
+     * @param query   The query to search for
+     * @param options The search options
+     * @return The classes which use a matching string, or an empty array if no classes were found
+     */
+    public native IndexedClass[] findClassesByStringLiteral(String query, SearchOptions options);
+
+    /**
+     * <p>Builds a call graph of all methods and returns the methods which are transitively called by the given entry
+     * points, including the entry points themselves. A call of a method which can be overridden also reaches all
+     * methods overriding it. Methods which are not returned are never called from any entry point, which makes this
+     * useful to find dead code. Only available if the class index was created with
+     * {@link IndexingOptions#withReferences(boolean)}.</p>
+     *
+     * @param entryPoints The methods from which the search starts
+     * @return The reachable methods
+     */
+    public native IndexedMethod[] findReachableMethods(IndexedMethod[] entryPoints);
+
     /**
      * <p>Searches for a package which exactly matches the given name. Both '/' and '.' may be used as package
      * separators.</p>
@@ -97,11 +156,119 @@ public class ClassIndex extends ClassIndexChildObject {
      */
     public native IndexedPackage[] findPackages(String query);
 
+    /**
+     * <p>Like {@link #findPackages(String)}, but the last part of the query is matched using the given search options.
+     * The results are sorted by their score.</p>
+     *
+     * @param query   The query to search for
+     * @param options The search options
+     * @return The packages which match the query and options, or an empty array if no packages were found
+     */
+    public native SearchResult<IndexedPackage>[] findPackagesWithScores(String query, SearchOptions options);
+
+    /**
+     * @param value The constant value
+     * @return All {@code static final} fields of type {@code int}, {@code short}, {@code char}, {@code byte} or
+     * {@code boolean} whose constant value is the given value, or an empty array if there are none
+     */
+    public IndexedField[] findFieldsWithConstant(int value) {
+        return findFieldsWithConstant0(0, value, null);
+    }
+
+    /**
+     * @param value The constant value
+     * @return All {@code static final long} fields whose constant value is the given value, or an empty array if there
+     * are none
+     */
+    public IndexedField[] findFieldsWithConstant(long value) {
+        return findFieldsWithConstant0(1, value, null);
+    }
+
+    /**
+     * @param value The constant value
+     * @return All {@code static final float} fields whose constant value is the given value, or an empty array if
+     * there are none
+     */
+    public IndexedField[] findFieldsWithConstant(float value) {
+        return findFieldsWithConstant0(2, Float.floatToRawIntBits(value), null);
+    }
+
+    /**
+     * @param value The constant value
+     * @return All {@code static final double} fields whose constant value is the given value, or an empty array if
+     * there are none
+     */
+    public IndexedField[] findFieldsWithConstant(double value) {
+        return findFieldsWithConstant0(3, Double.doubleToRawLongBits(value), null);
+    }
+
+    /**
+     * @param value The constant value
+     * @return All {@code static final String} fields whose constant value is the given value, or an empty array if
+     * there are none
+     */
+    public IndexedField[] findFieldsWithConstant(String value) {
+        return findFieldsWithConstant0(4, 0, Objects.requireNonNull(value));
+    }
+
+    private native IndexedField[] findFieldsWithConstant0(int type, long bits, String string);
+
     public List<String> findMethods(String query, int limit) {
         throw new UnsupportedOperationException();
     }
 
-    public native void saveToFile(String filePath);
+    /**
+     * <p>Returns the methods of all classes whose name matches the given query and search options, sorted by their
+     * score.</p>
+     *
+     * @param query   The query to search for
+     * @param options The search options
+     * @return The methods which match the query and options, or an empty array if no methods were found
+     */
+    public native SearchResult<IndexedMethod>[] findMethodsWithScores(String query, SearchOptions options);
+
+    /**
+     * Saves this class index to a file using deflate compression. Use {@link CompressionCodec#STORED} to create files
+     * which load faster, because they do not have to be read completely.
+     *
+     * @param filePath The path of the file, an existing file is replaced
+     */
+    public void saveToFile(String filePath) {
+        saveToFile(filePath, CompressionCodec.DEFLATE);
+    }
+
+    /**
+     * Saves this class index to a file using the default compression level of the given codec.
+     *
+     * @param filePath The path of the file, an existing file is replaced
+     * @param codec    The codec used to compress the file
+     */
+    public void saveToFile(String filePath, CompressionCodec codec) {
+        saveToFile(filePath, codec, CompressionCodec.DEFAULT_LEVEL);
+    }
+
+    /**
+     * Saves this class index to a file. The codec is recorded in the file, {@link #fromFile(String)} detects it
+     * automatically.
+     * <p>An index loaded using {@link #fromFile(String)} keeps its file mapped until it is destroyed. On Windows, such a
+     * file cannot be replaced and saving to its path fails with a {@link ClassIndexBuildingException}.</p>
+     *
+     * @param filePath The path of the file, an existing file is replaced
+     * @param codec    The codec used to compress the file
+     * @param level    The compression level, see {@link CompressionCodec} for the supported range
+     */
+    public native void saveToFile(String filePath, CompressionCodec codec, int level);
+
+    /**
+     * Checks if this class index needs to be created again for the given paths. This is the case if it was created
+     * from different paths, or if any of the paths changed since then. Only the size and modification time of a path
+     * are checked, unless the modification time changed but the size did not, in which case its contents are compared.
+     * Class indexes loaded from files which were saved by older versions are always stale.
+     *
+     * @param paths The paths which would be given to {@link #fromPaths(List)}, in any order
+     * @return {@code true} if the class index is stale, {@code false} otherwise
+     */
+    public native boolean isStale(List<String> paths);
 
     /**
      * Drops all natively managed memory used by this class index. Any further attempt to use this class index will
@@ -118,10 +285,15 @@ public class ClassIndex extends ClassIndexChildObject {
 
     private native BuildTimeInfo createClassIndexFromBytes(List<byte[]> classes);
 
-    private native BuildTimeInfo createClassIndexFromJars(List<String> classes);
+    private native BuildTimeInfo createClassIndexFromJars(List<String> classes, IndexingOptions options);
+
+    private native BuildTimeInfo createClassIndexFromPaths(List<String> paths, IndexingOptions options);
 
     private native BuildTimeInfo loadClassIndexFromFile(String filePath);
 
+    private native BuildTimeInfo updateClassIndex(ClassIndex classIndex, List<String> addedPaths,
+                                                  List<String> removedPaths, List<String> changedPaths);
+
     @Override
     protected void finalize() {
         if (this.destroyed)
@@ -138,14 +310,69 @@ public class ClassIndex extends ClassIndexChildObject {
     }
 
     /**
-     * Creates a new ClassIndex from the given jar file path.
+     * Creates a new ClassIndex from the given jar file path. Besides jar files, this also accepts {@code .jmod} files
+     * and the {@code lib/modules} runtime image of a JDK.
      *
      * @param jarFilePaths The jar file paths to index
      * @return The class index
      */
     public static ClassIndex fromJars(List<String> jarFilePaths) {
+        return fromJars(jarFilePaths, IndexingOptions.defaultOptions());
+    }
+
+    /**
+     * Creates a new ClassIndex from the given jar file paths, see {@link #fromJars(List)}.
+     *
+     * @param jarFilePaths The jar file paths to index
+     * @param options      The options which select the optional data to index
+     * @return The class index
+     */
+    public static ClassIndex fromJars(List<String> jarFilePaths, IndexingOptions options) {
+        ClassIndex c = new ClassIndex();
+        c.buildTimeInfo = c.createClassIndexFromJars(jarFilePaths, options);
+        c.destroyed = false;
+        return c;
+    }
+
+    /**
+     * Creates a new ClassIndex from the given paths. A path can either point to anything accepted by
+     * {@link #fromJars(List)}, or to a directory which contains class files, e.g. {@code build/classes/java/main}.
+     *
+     * @param paths The jar file and directory paths to index
+     * @return The class index
+     */
+    public static ClassIndex fromPaths(List<String> paths) {
+        return fromPaths(paths, IndexingOptions.defaultOptions());
+    }
+
+    /**
+     * Creates a new ClassIndex from the given paths, see {@link #fromPaths(List)}.
+     *
+     * @param paths   The jar file and directory paths to index
+     * @param options The options which select the optional data to index
+     * @return The class index
+     */
+    public static ClassIndex fromPaths(List<String> paths, IndexingOptions options) {
+        ClassIndex c = new ClassIndex();
+        c.buildTimeInfo = c.createClassIndexFromPaths(paths, options);
+        c.destroyed = false;
+        return c;
+    }
+
+    /**
+     * Creates a new ClassIndex from this one after some of the paths it was created from have changed. Only the added
+     * and changed paths are read again, all other classes are taken from this index. Paths have to be given exactly
+     * like they were given when this index was created. Classes are read with the {@link IndexingOptions} this index
+     * was created with. This index stays valid and has to be destroyed separately.
+     *
+     * @param addedPaths   The paths which should be added to the index, see {@link #fromPaths(List)}
+     * @param removedPaths The paths which should be removed from the index
+     * @param changedPaths The paths whose contents have changed
+     * @return The updated class index
+     */
+    public ClassIndex update(List<String> addedPaths, List<String> removedPaths, List<String> changedPaths) {
         ClassIndex c = new ClassIndex();
-        c.buildTimeInfo = c.createClassIndexFromJars(jarFilePaths);
+        c.buildTimeInfo = c.updateClassIndex(this, addedPaths, removedPaths, changedPaths);
         c.destroyed = false;
         return c;
     }
@@ -0,0 +1,26 @@
+package com.github.tth05.jindex;
+
+public enum CompressionCodec {
+    /**
+     * No compression. Files saved without compression are memory mapped and only read when needed.
+     */
+    STORED,
+    /**
+     * Deflate with a level from 0 to 9. The default level is 6.
+     */
+    DEFLATE,
+    /**
+     * Zstandard with a level from -7 to 22. The default level is 3. Requires the native library to be built with the
+     * {@code zstd} feature.
+     */
+    ZSTD,
+    /**
+     * LZ4, which does not support levels. Requires the native library to be built with the {@code lz4} feature.
+     */
+    LZ4;
+
+    /**
+     * Selects the default level of a codec.
+     */
+    public static final int DEFAULT_LEVEL = Integer.MIN_VALUE;
+}
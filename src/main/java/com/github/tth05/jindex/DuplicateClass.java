@@ -0,0 +1,53 @@
+package com.github.tth05.jindex;
+
+import java.util.Arrays;
+
+/**
+ * A class which was found in more than one source while building a class index, e.g. because two jars on the
+ * classpath contain different versions of the same library.
+ */
+public class DuplicateClass {
+
+    private final String name;
+    private final String[] sources;
+    private final boolean membersDiffer;
+
+    private DuplicateClass(String name, String[] sources, boolean membersDiffer) {
+        this.name = name;
+        this.sources = sources;
+        this.membersDiffer = membersDiffer;
+    }
+
+    /**
+     * @return The name of the class including the package, e.g. "java/lang/String"
+     */
+    public String getName() {
+        return name;
+    }
+
+    /**
+     * @return The sources of all found versions of this class, in the order they were read. An element is
+     * {@code null} if the class was created from raw class bytes.
+     * @see IndexedClass#getSourceJar()
+     */
+    public String[] getSources() {
+        return sources;
+    }
+
+    /**
+     * @return {@code true} if the found versions of this class declare fields or methods with different names,
+     * descriptors or access flags
+     */
+    public boolean hasDifferentMembers() {
+        return membersDiffer;
+    }
+
+    @Override
+    public String toString() {
+        return "DuplicateClass{" +
+               "name='" + name + '\'' +
+               ", sources=" + Arrays.toString(sources) +
+               ", membersDiffer=" + membersDiffer +
+               '}';
+    }
+}
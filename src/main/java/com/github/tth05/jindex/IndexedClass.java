@@ -38,6 +38,18 @@ public class IndexedClass extends ClassIndexChildObject {
      */
     public native IndexedPackage getPackage();
 
+    /**
+     * @return The name of the module this class was read from, e.g. "java.base", or {@code null} if it was not read
+     * from a jmod file or a runtime image
+     */
+    public native String getModuleName();
+
+    /**
+     * @return The path of the jar, directory or runtime image this class was read from, or {@code null} if it was
+     * created from raw class bytes. Jars nested in other jars are represented as "outer.jar!/inner.jar".
+     */
+    public native String getSourceJar();
+
     /**
      * @return The name of this class including the package, e.g. "java/lang/String"
      */
@@ -117,6 +129,75 @@ public class IndexedClass extends ClassIndexChildObject {
      */
     public native int getAccessFlags();
 
+    /**
+     * @return The annotations of this class, or an empty array if there are none. Annotations whose class is not part
+     * of the index are not included.
+     */
+    public native IndexedClass[] getAnnotations();
+
+    /**
+     * @return All classes which are annotated with this annotation class, or an empty array if there are none
+     */
+    public native IndexedClass[] findAnnotatedClasses();
+
+    /**
+     * @return All fields which are annotated with this annotation class, or an empty array if there are none
+     */
+    public native IndexedField[] findAnnotatedFields();
+
+    /**
+     * Returns all methods which are annotated with this annotation class.
+     *
+     * @param includeParameters Whether to also return methods which only have a parameter annotated with this
+     *                          annotation class
+     * @return All methods which are annotated with this annotation class, or an empty array if there are none
+     */
+    public native IndexedMethod[] findAnnotatedMethods(boolean includeParameters);
+
+    /**
+     * @return The classes which are permitted to extend this sealed class, or an empty array if this class is not
+     * sealed. Permitted subclasses which are not part of the index are not included.
+     */
+    public native IndexedClass[] getPermittedSubclasses();
+
+    /**
+     * @return The components of this record in declaration order, or {@code null} if this class is not a record
+     */
+    public native IndexedRecordComponent[] getRecordComponents();
+
+    /**
+     * @return The enum constants of this class in declaration order, or an empty array if this class is not an enum
+     */
+    public native IndexedField[] getEnumConstants();
+
+    /**
+     * @return The host of the nest this class belongs to, which is this class itself if it is the nest host. Classes
+     * without a 'NestHost' attribute belong to the nest of their outermost enclosing class.
+     */
+    public native IndexedClass getNestHost();
+
+    /**
+     * @return The members of the nest hosted by this class, or an empty array if this class is not a nest host. Nest
+     * members which are not part of the index are not included.
+     */
+    public native IndexedClass[] getNestMembers();
+
+    /**
+     * @param fromClass The class which contains the accessing code
+     * @return {@code true} if code in the given class can access this class
+     */
+    public native boolean isAccessibleFrom(IndexedClass fromClass);
+
+    /**
+     * Searches the code of all methods to find the ones which instantiate this class, cast to it, check for it using
+     * {@code instanceof} or access one of its members. Only available if the class index was created with references,
+     * see {@link IndexingOptions#withReferences(boolean)}. Code in synthetic methods, e.g. the body of a lambda,
+     * is not included.
+     *
+     * @return The methods which use this class, or an empty array if there are none
+     */
+    public native IndexedMethod[] findUsages();
+
     @Override
     public String toString() {
         return "FindClassesResult{" +
@@ -26,6 +26,37 @@ public class IndexedField extends ClassChildObject {
      */
     public native String getGenericSignatureString();
 
+    /**
+     * @return The annotations of this field, or an empty array if there are none. Annotations whose class is not part
+     * of the index are not included.
+     */
+    public native IndexedClass[] getAnnotations();
+
+    /**
+     * @return The value of this field if it is a {@code static final} field with a constant initializer, or
+     * {@code null} otherwise. Constants of type {@code boolean}, {@code byte}, {@code char} and {@code short} are
+     * returned as an {@link Integer}.
+     */
+    public native Object getConstantValue();
+
+    /**
+     * Checks whether code in the given class can access this field. Protected members are accessible from all
+     * subclasses of the declaring class, the type of the receiver is not checked.
+     *
+     * @param fromClass The class which contains the accessing code
+     * @return {@code true} if this field and its declaring class are accessible from the given class
+     */
+    public native boolean isAccessibleFrom(IndexedClass fromClass);
+
+    /**
+     * Searches the code of all methods to find the ones which read or write this field. Only available if the class
+     * index was created with references, see {@link IndexingOptions#withReferences(boolean)}. Code in synthetic
+     * methods, e.g. the body of a lambda, is not included.
+     *
+     * @return The methods which use this field, or an empty array if there are none
+     */
+    public native IndexedMethod[] findUsages();
+
     @Override
     public String toString() {
         return getName();
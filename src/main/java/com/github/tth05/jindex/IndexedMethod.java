@@ -53,6 +53,49 @@ public class IndexedMethod extends ClassChildObject {
      */
     public native IndexedMethod[] findBaseMethods();
 
+    /**
+     * @return The annotations of this method, or an empty array if there are none. Annotations whose class is not part
+     * of the index are not included.
+     */
+    public native IndexedClass[] getAnnotations();
+
+    /**
+     * @param parameterIndex The index of the parameter
+     * @return The annotations of the given parameter, or an empty array if there are none. Annotations whose class
+     * is not part of the index are not included.
+     */
+    public native IndexedClass[] getParameterAnnotations(int parameterIndex);
+
+    /**
+     * Returns the parameter names found in the 'MethodParameters' attribute, or in the local variable table of this
+     * method's code if there is no such attribute and
+     * {@link IndexingOptions#withLocalVariableParameterNames(boolean)} was enabled. The names belong to the
+     * parameters of the descriptor, which can include synthetic parameters, e.g. the outer instance of an inner class
+     * constructor.
+     *
+     * @return The name of each parameter with {@code null} for unknown names, or an empty array if no name is known
+     */
+    public native String[] getParameterNames();
+
+    /**
+     * Checks whether code in the given class can access this method. Protected members are accessible from all
+     * subclasses of the declaring class, the type of the receiver is not checked.
+     *
+     * @param fromClass The class which contains the accessing code
+     * @return {@code true} if this method and its declaring class are accessible from the given class
+     */
+    public native boolean isAccessibleFrom(IndexedClass fromClass);
+
+    /**
+     * Searches the code of all methods to find the ones which invoke this method. Calls through a subclass which
+     * inherits this method are included, calls of methods which override it are not. Only available if the class index
+     * was created with references, see {@link IndexingOptions#withReferences(boolean)}. Code in synthetic
+     * methods, e.g. the body of a lambda, is not included.
+     *
+     * @return The methods which use this method, or an empty array if there are none
+     */
+    public native IndexedMethod[] findUsages();
+
     @Override
     public String toString() {
         return getName();
@@ -0,0 +1,28 @@
+package com.github.tth05.jindex;
+
+public class IndexedRecordComponent extends ClassChildObject {
+
+    private IndexedRecordComponent(long classIndexPointer, long classPointer, long pointer) {
+        super(classIndexPointer, classPointer, pointer);
+    }
+
+    /**
+     * @return The name of this record component
+     */
+    public native String getName();
+
+    /**
+     * @return The descriptor of this record component's type
+     */
+    public native String getDescriptorString();
+
+    /**
+     * @return The generic signature of this record component, or {@code null} if its type is not generic
+     */
+    public native String getGenericSignatureString();
+
+    @Override
+    public String toString() {
+        return getName();
+    }
+}
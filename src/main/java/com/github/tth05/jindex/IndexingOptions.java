@@ -0,0 +1,112 @@
+package com.github.tth05.jindex;
+
+public class IndexingOptions {
+
+    private boolean indexReferences;
+    private boolean indexStringLiterals;
+    private boolean localVariableParameterNames;
+    private int maxNestedJarDepth = 2;
+    private int targetRelease;
+    private DuplicateClassPolicy duplicateClassPolicy = DuplicateClassPolicy.FIRST;
+
+    private IndexingOptions() {
+    }
+
+    /**
+     * @return Options which only index the declarations of classes and their members
+     */
+    public static IndexingOptions defaultOptions() {
+        return new IndexingOptions();
+    }
+
+    /**
+     * @param indexReferences Whether the code of all methods should be read to record which classes, fields and
+     *                        methods they use. This is required for finding usages, but makes indexing slower and the
+     *                        index larger.
+     * @return These options
+     */
+    public IndexingOptions withReferences(boolean indexReferences) {
+        this.indexReferences = indexReferences;
+        return this;
+    }
+
+    /**
+     * @param indexStringLiterals Whether the strings used by each class should be recorded. This is required for
+     *                            {@link ClassIndex#findClassesByStringLiteral(String, SearchOptions)}, but makes
+     *                            indexing slower and the index larger.
+     * @return These options
+     */
+    public IndexingOptions withStringLiterals(boolean indexStringLiterals) {
+        this.indexStringLiterals = indexStringLiterals;
+        return this;
+    }
+
+    /**
+     * @param localVariableParameterNames Whether parameter names should be read from the local variable table of a
+     *                                    method's code if the method has no 'MethodParameters' attribute. The table
+     *                                    is only present if the class was compiled with debug information, and
+     *                                    obfuscated classes can contain misleading names.
+     * @return These options
+     */
+    public IndexingOptions withLocalVariableParameterNames(boolean localVariableParameterNames) {
+        this.localVariableParameterNames = localVariableParameterNames;
+        return this;
+    }
+
+    /**
+     * @param maxNestedJarDepth How deep jars contained in other jars are read, from 0 to 255. Only jars in the
+     *                          {@code BOOT-INF/lib/} and {@code WEB-INF/lib/} directories of fat jars and wars are
+     *                          read. A depth of 0 disables reading nested jars completely. The default depth is 2.
+     * @return These options
+     */
+    public IndexingOptions withMaxNestedJarDepth(int maxNestedJarDepth) {
+        if (maxNestedJarDepth < 0 || maxNestedJarDepth > 255)
+            throw new IllegalArgumentException("Max nested jar depth must be between 0 and 255");
+
+        this.maxNestedJarDepth = maxNestedJarDepth;
+        return this;
+    }
+
+    /**
+     * @param targetRelease The Java release used to select classes from multi-release jars, e.g. {@code 11}. The
+     *                      highest version of a class which is not newer than this release is indexed. Use {@code 0}
+     *                      to index the highest available version, which is the default.
+     * @return These options
+     */
+    public IndexingOptions withTargetRelease(int targetRelease) {
+        if (targetRelease < 0 || targetRelease > 65535)
+            throw new IllegalArgumentException("Invalid target release " + targetRelease);
+
+        this.targetRelease = targetRelease;
+        return this;
+    }
+
+    /**
+     * @param duplicateClassPolicy Which class is kept if multiple sources contain a class with the same name. All
+     *                             duplicates are reported in {@link BuildTimeInfo} regardless of this setting.
+     * @return These options
+     */
+    public IndexingOptions withDuplicateClassPolicy(DuplicateClassPolicy duplicateClassPolicy) {
+        if (duplicateClassPolicy == null)
+            throw new IllegalArgumentException("Duplicate class policy must not be null");
+
+        this.duplicateClassPolicy = duplicateClassPolicy;
+        return this;
+    }
+
+    public enum DuplicateClassPolicy {
+        /**
+         * Keep the class from the source which was read first. This is the default.
+         */
+        FIRST,
+        /**
+         * Keep the class from the source which was read last.
+         */
+        LAST,
+        /**
+         * Keep the class with the highest class file version. If multiple classes have the same version, the one which
+         * was read first is kept.
+         */
+        HIGHEST_CLASS_FILE_VERSION
+    }
+}
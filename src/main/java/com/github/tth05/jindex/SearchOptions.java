@@ -47,7 +47,19 @@ public class SearchOptions {
         /**
          * The match can occur anywhere in the string.
          */
-        CONTAINS
+        CONTAINS,
+        /**
+         * Every upper case character of the query starts a part which has to match the start of a word in the string,
+         * e.g. {@code HMap} matches {@code HashMap} and {@code CHM} matches {@code ConcurrentHashMap}. Words may be
+         * skipped. Results are ranked by how many words of the string were matched.
+         */
+        CAMEL_CASE,
+        /**
+         * The match has to occur at the start of the string, but may contain a few typos, e.g. {@code ArrayLsit}
+         * matches {@code ArrayList}. Queries with at least 3 characters tolerate one edit, queries with at least 6
+         * characters tolerate two. Results are ranked by the number of edits.
+         */
+        FUZZY
     }
 
     public enum MatchMode {
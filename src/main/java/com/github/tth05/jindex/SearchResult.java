@@ -0,0 +1,38 @@
+package com.github.tth05.jindex;
+
+/**
+ * A value found by a search together with the score of the match.
+ *
+ * @param <T> The type of the value
+ */
+public class SearchResult<T> {
+
+    private final T value;
+    private final int score;
+
+    private SearchResult(T value, int score) {
+        this.value = value;
+        this.score = score;
+    }
+
+    /**
+     * @return The value which matched the query
+     */
+    public T getValue() {
+        return value;
+    }
+
+    /**
+     * @return The score of the match. Lower scores are better matches, an exact match has a score of {@code 0}. The
+     * meaning of the score depends on the {@link SearchOptions.SearchMode}, e.g. it is based on the number of edits
+     * for {@link SearchOptions.SearchMode#FUZZY}.
+     */
+    public int getScore() {
+        return score;
+    }
+
+    @Override
+    public String toString() {
+        return value + " (" + score + ")";
+    }
+}
@@ -0,0 +1,37 @@
+package com.github.tth05.jindex;
+
+/**
+ * A jar nested in another jar which could not be read while building a class index, e.g. because it is corrupt.
+ */
+public class SkippedSource {
+
+    private final String name;
+    private final String error;
+
+    private SkippedSource(String name, String error) {
+        this.name = name;
+        this.error = error;
+    }
+
+    /**
+     * @return The name of the nested jar, e.g. "outer.jar!/inner.jar"
+     */
+    public String getName() {
+        return name;
+    }
+
+    /**
+     * @return The reason why the nested jar could not be read
+     */
+    public String getError() {
+        return error;
+    }
+
+    @Override
+    public String toString() {
+        return "SkippedSource{" +
+               "name='" + name + '\'' +
+               ", error='" + error + '\'' +
+               '}';
+    }
+}
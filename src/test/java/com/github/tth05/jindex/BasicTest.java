@@ -4,12 +4,21 @@ import org.junit.jupiter.api.AfterAll;
 import org.junit.jupiter.api.BeforeAll;
 import org.junit.jupiter.api.Test;
 import org.junit.jupiter.api.TestInstance;
+import org.junit.jupiter.api.io.TempDir;
 
+import java.io.ByteArrayOutputStream;
+import java.io.IOException;
+import java.io.InputStream;
 import java.lang.reflect.Modifier;
 import java.nio.file.Files;
+import java.nio.file.Path;
 import java.nio.file.Paths;
 import java.util.Arrays;
 import java.util.Collections;
+import java.util.LinkedHashMap;
+import java.util.Map;
+import java.util.zip.ZipEntry;
+import java.util.zip.ZipOutputStream;
 
 import static org.junit.jupiter.api.Assertions.*;
 
@@ -59,4 +68,168 @@ public class BasicTest {
         assertEquals("lastIndexOfSupplementary", resultClass.getMethods()[48].getName());
         assertTrue(Modifier.isPrivate(resultClass.getMethods()[48].getAccessFlags()));
     }
+
+    @Test
+    public void testAnnotations() {
+        IndexedClass runnable = index.findClass("java/lang", "Runnable");
+        assertTrue(Arrays.stream(runnable.getAnnotations()).anyMatch(c -> c.getNameWithPackage().equals("java/lang/FunctionalInterface")));
+
+        IndexedClass[] annotatedClasses = index.findClass("java/lang", "FunctionalInterface").findAnnotatedClasses();
+        assertTrue(Arrays.stream(annotatedClasses).anyMatch(c -> c.getNameWithPackage().equals("java/lang/Runnable")));
+    }
+
+    @Test
+    public void testConstantValues() {
+        IndexedField[] fields = index.findFieldsWithConstant(Integer.MAX_VALUE);
+        IndexedField maxValue = Arrays.stream(fields).filter(f -> f.getName().equals("MAX_VALUE")).findFirst().get();
+        assertEquals(Integer.MAX_VALUE, maxValue.getConstantValue());
+
+        IndexedField[] enumConstants = index.findClass("java/util/concurrent", "TimeUnit").getEnumConstants();
+        assertTrue(Arrays.stream(enumConstants).anyMatch(f -> f.getName().equals("SECONDS")));
+    }
+
+    @Test
+    public void testIsAccessibleFrom() {
+        IndexedClass arrayList = index.findClass("java/util", "ArrayList");
+        IndexedClass iterator = index.findClass("java/util", "ArrayList$Itr");
+        assertTrue(iterator.isAccessibleFrom(arrayList));
+        assertFalse(iterator.isAccessibleFrom(index.findClass("java/lang", "String")));
+    }
+
+    @Test
+    public void testNamePatterns() {
+        IndexedClass[] results = index.findClassesByGlob("java/util/concurrent/atomic/Atomic*", 100);
+        assertTrue(results.length > 0);
+        for (IndexedClass result : results)
+            assertTrue(result.getNameWithPackage().startsWith("java/util/concurrent/atomic/Atomic"));
+        assertTrue(Arrays.stream(results).anyMatch(c -> c.getName().equals("AtomicInteger")));
+
+        results = index.findClassesByRegex("java/lang/Str.*Builder", 10);
+        assertTrue(Arrays.stream(results).anyMatch(c -> c.getNameWithPackage().equals("java/lang/StringBuilder")));
+    }
+
+    @Test
+    public void testScoredSearch() {
+        SearchResult<IndexedClass>[] results = index.findClassesWithScores("ArrayLsit", SearchOptions.defaultWith(SearchOptions.SearchMode.FUZZY));
+        assertTrue(Arrays.stream(results).anyMatch(r -> r.getValue().getNameWithPackage().equals("java/util/ArrayList")));
+        for (int i = 1; i < results.length; i++)
+            assertTrue(results[i - 1].getScore() <= results[i].getScore());
+    }
+
+    @Test
+    public void testIndexingOptions(@TempDir Path tempDir) {
+        Map<String, byte[]> innerEntries = new LinkedHashMap<>();
+        innerEntries.put("java/lang/Runnable.class", readClass("java/lang/Runnable"));
+        Map<String, byte[]> entries = new LinkedHashMap<>();
+        entries.put("java/lang/Integer.class", readClass("java/lang/Integer"));
+        entries.put("BOOT-INF/lib/inner.jar", jarBytes(innerEntries));
+        entries.put("BOOT-INF/lib/broken.jar", new byte[]{1, 2, 3});
+        String jar = writeFile(tempDir.resolve("Main.jar"), jarBytes(entries));
+        String otherJar = writeFile(tempDir.resolve("Other.jar"), jarBytes(innerEntries));
+
+        IndexingOptions options = IndexingOptions.defaultOptions()
+                .withStringLiterals(true)
+                .withDuplicateClassPolicy(IndexingOptions.DuplicateClassPolicy.LAST);
+        ClassIndex classIndex = ClassIndex.fromJars(Arrays.asList(jar, otherJar), options);
+
+        BuildTimeInfo info = classIndex.getBuildTimeInfo();
+        assertEquals(1, info.getSkippedSources().length);
+        assertEquals(jar + "!/BOOT-INF/lib/broken.jar", info.getSkippedSources()[0].getName());
+        assertEquals(1, info.getDuplicateClasses().length);
+        assertEquals("java/lang/Runnable", info.getDuplicateClasses()[0].getName());
+        assertArrayEquals(new String[]{jar + "!/BOOT-INF/lib/inner.jar", otherJar}, info.getDuplicateClasses()[0].getSources());
+        assertEquals(otherJar, classIndex.findClass("java/lang", "Runnable").getSourceJar());
+
+        assertFalse(classIndex.isStale(Arrays.asList(otherJar, jar)));
+        assertTrue(classIndex.isStale(Collections.singletonList(jar)));
+
+        String indexFile = tempDir.resolve("index").toString();
+        classIndex.saveToFile(indexFile, CompressionCodec.ZSTD, 3);
+        ClassIndex loadedIndex = ClassIndex.fromFile(indexFile);
+        assertFalse(loadedIndex.isStale(Arrays.asList(jar, otherJar)));
+
+        // The options of the loaded index are used to read the paths again
+        ClassIndex updatedIndex = loadedIndex.update(Collections.emptyList(), Collections.singletonList(otherJar), Collections.emptyList());
+        assertEquals(0, updatedIndex.getBuildTimeInfo().getDuplicateClasses().length);
+        assertEquals(jar + "!/BOOT-INF/lib/inner.jar", updatedIndex.findClass("java/lang", "Runnable").getSourceJar());
+        assertTrue(updatedIndex.findClassesByStringLiteral("null", SearchOptions.defaultWith(SearchOptions.SearchMode.CONTAINS)).length > 0);
+
+        ClassIndex shallowIndex = ClassIndex.fromJars(Collections.singletonList(jar), IndexingOptions.defaultOptions().withMaxNestedJarDepth(0));
+        assertNull(shallowIndex.findClass("java/lang", "Runnable"));
+        assertEquals(0, shallowIndex.getBuildTimeInfo().getSkippedSources().length);
+
+        for (ClassIndex c : Arrays.asList(classIndex, loadedIndex, updatedIndex, shallowIndex))
+            c.destroy();
+    }
+
+    @Test
+    public void testReferences(@TempDir Path tempDir) {
+        Map<String, byte[]> entries = new LinkedHashMap<>();
+        for (String name : Arrays.asList("java/util/ArrayList", "java/util/AbstractList", "java/lang/Integer"))
+            entries.put(name + ".class", readClass(name));
+        String jar = writeFile(tempDir.resolve("Main.jar"), jarBytes(entries));
+
+        IndexingOptions options = IndexingOptions.defaultOptions().withReferences(true).withStringLiterals(true);
+        ClassIndex classIndex = ClassIndex.fromJars(Collections.singletonList(jar), options);
+
+        IndexedMethod superConstructor = Arrays.stream(classIndex.findClass("java/util", "AbstractList").getMethods())
+                .filter(m -> m.getName().equals("<init>")).findFirst().get();
+        assertTrue(Arrays.stream(superConstructor.findUsages()).anyMatch(m -> m.getDeclaringClass().getName().equals("ArrayList")));
+
+        IndexedMethod constructor = Arrays.stream(classIndex.findClass("java/util", "ArrayList").getMethods())
+                .filter(m -> m.getName().equals("<init>") && m.getDescriptorString().equals("()V")).findFirst().get();
+        IndexedMethod[] reachable = classIndex.findReachableMethods(new IndexedMethod[]{constructor});
+        assertTrue(Arrays.stream(reachable).anyMatch(m -> m.getDeclaringClass().getName().equals("AbstractList")));
+
+        IndexedClass[] classes = classIndex.findClassesByStringLiteral("null", SearchOptions.defaultWith(SearchOptions.SearchMode.CONTAINS));
+        assertTrue(Arrays.stream(classes).anyMatch(c -> c.getNameWithPackage().equals("java/lang/Integer")));
+        classIndex.destroy();
+    }
+
+    @Test
+    public void testFromPaths(@TempDir Path tempDir) {
+        Path classFile = tempDir.resolve("java/lang/Runnable.class");
+        assertDoesNotThrow(() -> Files.createDirectories(classFile.getParent()));
+        writeFile(classFile, readClass("java/lang/Runnable"));
+
+        ClassIndex classIndex = ClassIndex.fromPaths(Collections.singletonList(tempDir.toString()));
+        assertEquals(tempDir.toString(), classIndex.findClass("java/lang", "Runnable").getSourceJar());
+        assertFalse(classIndex.isStale(Collections.singletonList(tempDir.toString())));
+        classIndex.destroy();
+    }
+
+    private static byte[] readClass(String name) {
+        return assertDoesNotThrow(() -> {
+            try (InputStream stream = ClassLoader.getSystemResourceAsStream(name + ".class")) {
+                assertNotNull(stream);
+
+                ByteArrayOutputStream output = new ByteArrayOutputStream();
+                byte[] buf = new byte[4096];
+                int read;
+                while ((read = stream.read(buf)) != -1)
+                    output.write(buf, 0, read);
+                return output.toByteArray();
+            }
+        });
+    }
+
+    private static byte[] jarBytes(Map<String, byte[]> entries) {
+        ByteArrayOutputStream output = new ByteArrayOutputStream();
+        try (ZipOutputStream zipFile = new ZipOutputStream(output)) {
+            for (Map.Entry<String, byte[]> entry : entries.entrySet()) {
+                zipFile.putNextEntry(new ZipEntry(entry.getKey()));
+                zipFile.write(entry.getValue());
+                zipFile.closeEntry();
+            }
+        } catch (IOException e) {
+            fail(e);
+        }
+
+        return output.toByteArray();
+    }
+
+    private static String writeFile(Path path, byte[] data) {
+        assertDoesNotThrow(() -> Files.write(path, data));
+        return path.toString();
+    }
 }